@@ -9,7 +9,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     let mut motor = LinuxMotorDriverBuilder::new_linux()
         .with_dual_gpio_enable(&mut chip, 23, 24)?
-        .with_dual_pwm_channels(0, 0, 1, 1000)
+        .with_dual_pwm_channels(0, 0, 1, 1000.0, 1000)?
         .build_and_init()?;
     
     motor.enable()?;
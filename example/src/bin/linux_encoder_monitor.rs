@@ -1,75 +1,83 @@
 use motor_driver_hal::driver::linux::LinuxMotorDriverBuilder;
-use motor_driver_hal::MotorDriver;
+use motor_driver_hal::{EStop, MotorDriver};
 use linux_embedded_hal::gpio_cdev::Chip;
 use std::thread;
 use std::time::Duration;
 use std::io::{self, Write};
 use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicBool, Ordering};
-
-fn shutdown_motor<T: MotorDriver>(motor: &Arc<Mutex<T>>) {
-    if let Ok(mut m) = motor.lock() {
-        let _ = m.stop();
-        let _ = m.disable();
-        println!("Motor stopped safely.");
-    }
-}
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut chip = Chip::new("/dev/gpiochip0")?;
 
     let motor = LinuxMotorDriverBuilder::new_linux()
         .with_dual_gpio_enable(&mut chip, 23, 24)?
-        .with_dual_pwm_channels(0, 0, 1, 1000)
+        .with_dual_pwm_channels(0, 0, 1, 1000.0, 1000)?
+        .with_encoder_events(&mut chip, 25, 8)?
+        .with_ppr(1000)
         .build_and_init()?;
 
+    // Edges are counted by the driver, the counter can be read without the motor lock
+    let counter = motor.edge_counter().expect("edge counter configured");
     let motor = Arc::new(Mutex::new(motor));
     
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    let motor_clone = motor.clone();
+    // Ctrl+C brakes and disables every registered motor
+    let estop = EStop::new();
+    estop.register(motor.clone());
+    let handler = estop.clone();
     
     ctrlc::set_handler(move || {
         println!("\n\nCtrl+C detected! Shutting down safely...");
-        r.store(false, Ordering::SeqCst);
-        
-        shutdown_motor(&motor_clone);
-        std::process::exit(0);
+        handler.trigger();
     })?;
     
     motor.lock().unwrap().enable()?;
     
     println!("Starting encoder measurement...");
+    motor.lock().unwrap().reset_encoder();
     
+    // Run motor at 10% speed
     motor.lock().unwrap().set_speed(100)?;
     
+    let mut last_count = 0;
     let mut reading_count = 0;
     let start_time = std::time::Instant::now();
     
-    while running.load(Ordering::SeqCst) {
-        thread::sleep(Duration::from_millis(100));
+    // Main loop: Display encoder values
+    while !estop.is_triggered() {
+        thread::sleep(Duration::from_millis(100)); // Display every 100ms
         
+        let current_count = counter.count();
+        let delta = current_count - last_count;
         let elapsed = start_time.elapsed().as_secs_f64();
+        let velocity = motor.lock().unwrap().get_velocity()?;
         
         reading_count += 1;
         
-        print!("\r[{:4}] Speed: 100 | Time: {:.1}s", 
-               reading_count, elapsed);
+        print!("\r[{:4}] Pulses: {:6} | Delta: {:4} | Speed: {:6.0} pps {:6.1} RPM | Time: {:.1}s", 
+               reading_count, current_count, delta, velocity.pulses_per_sec, velocity.rpm, elapsed);
         io::stdout().flush()?;
         
+        last_count = current_count;
+        
+        // Auto-exit after 1 minute
         if elapsed > 60.0 {
             println!("\n\n1 minute elapsed, test completed");
-            running.store(false, Ordering::SeqCst);
             break;
         }
     }
     
+    // Final results
+    let final_count = counter.count();
     let total_time = start_time.elapsed().as_secs_f64();
     
     println!("\n\n=== Test Results ===");
+    println!("Total pulses: {} pulses", final_count);
     println!("Total time: {:.1} seconds", total_time);
+    println!("Average speed: {:.1} pulses/second", final_count as f64 / total_time);
     
-    shutdown_motor(&motor);
+    // Safely stop motor on normal exit
+    estop.trigger();
+    println!("Motor stopped safely.");
     
     println!("\nMotor stopped, program terminated");
     Ok(())
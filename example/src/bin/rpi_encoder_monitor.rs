@@ -1,20 +1,11 @@
 use motor_driver_hal::driver::rppal::RppalMotorDriverBuilder;
-use motor_driver_hal::MotorDriver;
+use motor_driver_hal::{EStop, MotorDriver};
 use rppal::gpio::Gpio;
 use rppal::pwm::Channel;
 use std::thread;
 use std::time::Duration;
 use std::io::{self, Write};
 use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicBool, Ordering};
-
-fn shutdown_motor<T: MotorDriver>(motor: &Arc<Mutex<T>>) {
-    if let Ok(mut m) = motor.lock() {
-        let _ = m.stop();
-        let _ = m.disable();
-        println!("Motor stopped safely.");
-    }
-}
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let gpio = Gpio::new()?;
@@ -22,23 +13,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let motor = RppalMotorDriverBuilder::new_rppal()
         .with_dual_gpio_enable(&gpio, 23, 24)?
         .with_dual_pwm_channels(Channel::Pwm1, Channel::Pwm2, 1000.0, 1000)?
-        .with_encoder_pins(&gpio, 25, 8)?
+        .with_encoder_interrupts(&gpio, 25, 8)?
         .with_ppr(1000)
         .build_and_init()?;
 
+    // Edges are counted by the driver, the counter can be read without the motor lock
+    let counter = motor.edge_counter().expect("edge counter configured");
     let motor = Arc::new(Mutex::new(motor));
     
-    // Setup Ctrl+C handler
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    let motor_clone = motor.clone();
+    // Ctrl+C brakes and disables every registered motor
+    let estop = EStop::new();
+    estop.register(motor.clone());
+    let handler = estop.clone();
     
     ctrlc::set_handler(move || {
         println!("\n\nCtrl+C detected! Shutting down safely...");
-        r.store(false, Ordering::SeqCst);
-        
-        shutdown_motor(&motor_clone);
-        std::process::exit(0);
+        handler.trigger();
     })?;
     
     motor.lock().unwrap().enable()?;
@@ -53,35 +43,19 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut reading_count = 0;
     let start_time = std::time::Instant::now();
     
-    // Encoder reading thread
-    let motor_read = motor.clone();
-    let running_read = running.clone();
-    let encoder_thread = thread::spawn(move || {
-        while running_read.load(Ordering::SeqCst) {
-            if let Ok(mut m) = motor_read.lock() {
-                let _ = m.read_encoder();
-            }
-            thread::sleep(Duration::from_micros(100)); // Faster reading rate
-        }
-    });
-    
     // Main loop: Display encoder values
-    while running.load(Ordering::SeqCst) {
+    while !estop.is_triggered() {
         thread::sleep(Duration::from_millis(100)); // Display every 100ms
         
-        let current_count = motor.lock().unwrap().get_pulse_count();
+        let current_count = counter.count();
         let delta = current_count - last_count;
         let elapsed = start_time.elapsed().as_secs_f64();
-        let pulses_per_sec = if elapsed > 0.0 {
-            (current_count as f64 / elapsed).round() as i32
-        } else {
-            0
-        };
+        let velocity = motor.lock().unwrap().get_velocity()?;
         
         reading_count += 1;
         
-        print!("\r[{:4}] Pulses: {:6} | Delta: {:4} | Speed: {:4} pps | Time: {:.1}s", 
-               reading_count, current_count, delta, pulses_per_sec, elapsed);
+        print!("\r[{:4}] Pulses: {:6} | Delta: {:4} | Speed: {:6.0} pps {:6.1} RPM | Time: {:.1}s", 
+               reading_count, current_count, delta, velocity.pulses_per_sec, velocity.rpm, elapsed);
         io::stdout().flush()?;
         
         last_count = current_count;
@@ -89,16 +63,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         // Auto-exit after 1 minute
         if elapsed > 60.0 {
             println!("\n\n1 minute elapsed, test completed");
-            running.store(false, Ordering::SeqCst);
             break;
         }
     }
     
-    // Wait for encoder thread to finish
-    let _ = encoder_thread.join();
-    
     // Final results
-    let final_count = motor.lock().unwrap().get_pulse_count();
+    let final_count = counter.count();
     let total_time = start_time.elapsed().as_secs_f64();
     
     println!("\n\n=== Test Results ===");
@@ -107,7 +77,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Average speed: {:.1} pulses/second", final_count as f64 / total_time);
     
     // Safely stop motor on normal exit
-    shutdown_motor(&motor);
+    estop.trigger();
+    println!("Motor stopped safely.");
     
     println!("\nMotor stopped, program terminated");
     Ok(())
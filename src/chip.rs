@@ -0,0 +1,735 @@
+//! Typed drivers for common H-bridge chips.
+//!
+//! Each chip type owns the pins of one bridge channel and drives them with
+//! the truth table from its datasheet, so `brake()` and `stop()` do the
+//! electrically correct thing for that part. Wrap a chip in a [`ChipDriver`]
+//! to get a [`MotorDriver`].
+//!
+//! | Chip        | Forward                 | Reverse                 | Brake              | Coast           |
+//! |-------------|-------------------------|-------------------------|--------------------|-----------------|
+//! | DRV8833     | IN1 = PWM, IN2 = L      | IN1 = L, IN2 = PWM      | IN1 = IN2 = H      | IN1 = IN2 = L   |
+//! | DRV8871     | IN1 = PWM, IN2 = L      | IN1 = L, IN2 = PWM      | IN1 = IN2 = H      | IN1 = IN2 = L   |
+//! | TB6612FNG   | IN1 = H, IN2 = L, PWM   | IN1 = L, IN2 = H, PWM   | IN1 = IN2 = H      | IN1 = IN2 = L, PWM = H |
+//! | L298N       | IN1 = H, IN2 = L, EN = PWM | IN1 = L, IN2 = H, EN = PWM | IN1 = IN2 = L, EN = H | EN = L    |
+//! | BTS7960     | RPWM = PWM, LPWM = L    | RPWM = L, LPWM = PWM    | RPWM = LPWM = L    | R_EN = L_EN = L |
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use motor_driver_hal::chip::{ChipDriver, Drv8833};
+//! use motor_driver_hal::MotorDriver;
+//!
+//! let bridge = Drv8833::new(ain1_pwm, ain2_pwm).with_sleep(nsleep_pin);
+//! let mut motor = ChipDriver::new(bridge, 1000);
+//!
+//! motor.initialize()?;
+//! motor.enable()?;
+//! motor.set_speed(600)?;
+//! motor.brake()?; // both inputs high, low-side short brake
+//! ```
+
+use crate::driver::{DecayMode, NoPin};
+use crate::state::DriverState;
+use crate::{FaultStatus, HalCause, MotorDriver, MotorDriverError, Operation, Signal};
+use embedded_hal::digital::OutputPin;
+use embedded_hal::pwm::SetDutyCycle;
+
+/// Pin-level control of a single H-bridge channel.
+///
+/// Implemented by the chip types in this module. Implement it for other
+/// parts to reuse [`ChipDriver`].
+pub trait ChipBridge {
+    /// Drives the motor in one direction with the given duty cycle.
+    fn drive(&mut self, forward: bool, duty: u16) -> Result<(), MotorDriverError>;
+
+    /// Shorts the motor terminals so it stops quickly.
+    fn brake(&mut self) -> Result<(), MotorDriverError>;
+
+    /// Leaves the motor terminals floating so it spins down freely.
+    fn coast(&mut self) -> Result<(), MotorDriverError>;
+
+    /// Wakes the chip up or puts it into sleep or standby.
+    ///
+    /// Chips without an enable, sleep or standby input ignore this.
+    fn set_enabled(&mut self, enabled: bool) -> Result<(), MotorDriverError>;
+}
+
+fn set_pin<P: OutputPin<Error: HalCause>>(pin: &mut P, signal: Signal, high: bool) -> Result<(), MotorDriverError> {
+    if high {
+        pin.set_high().map_err(MotorDriverError::gpio(signal, Operation::SetHigh))
+    } else {
+        pin.set_low().map_err(MotorDriverError::gpio(signal, Operation::SetLow))
+    }
+}
+
+fn set_duty<P: SetDutyCycle<Error: HalCause>>(pwm: &mut P, signal: Signal, duty: u16) -> Result<(), MotorDriverError> {
+    pwm.set_duty_cycle(duty).map_err(MotorDriverError::pwm(signal))
+}
+
+fn set_fully_on<P: SetDutyCycle<Error: HalCause>>(pwm: &mut P, signal: Signal) -> Result<(), MotorDriverError> {
+    pwm.set_duty_cycle_fully_on().map_err(MotorDriverError::pwm(signal))
+}
+
+/// Drives a bridge whose two inputs are both PWM, as on the DRV8833 and DRV8871.
+fn drive_pwm_pair<P1: SetDutyCycle<Error: HalCause>, P2: SetDutyCycle<Error: HalCause>>(in1: &mut P1, in2: &mut P2, forward: bool, duty: u16, decay: DecayMode) -> Result<(), MotorDriverError> {
+    match (decay, forward) {
+        (DecayMode::Fast, true) => {
+            set_duty(in2, Signal::Pwm2, 0)?;
+            set_duty(in1, Signal::Pwm, duty)
+        }
+        (DecayMode::Fast, false) => {
+            set_duty(in1, Signal::Pwm, 0)?;
+            set_duty(in2, Signal::Pwm2, duty)
+        }
+        (DecayMode::Slow, true) => {
+            set_fully_on(in1, Signal::Pwm)?;
+            let inverted = in2.max_duty_cycle().saturating_sub(duty);
+            set_duty(in2, Signal::Pwm2, inverted)
+        }
+        (DecayMode::Slow, false) => {
+            set_fully_on(in2, Signal::Pwm2)?;
+            let inverted = in1.max_duty_cycle().saturating_sub(duty);
+            set_duty(in1, Signal::Pwm, inverted)
+        }
+    }
+}
+
+/// TI DRV8833 dual H-bridge, one channel (xIN1/xIN2) with optional nSLEEP.
+///
+/// Both inputs must be PWM capable. nSLEEP is shared by both channels on
+/// the chip; give it to one of the two drivers and leave the other with `NoPin`.
+#[derive(Debug)]
+pub struct Drv8833<IN1, IN2, S = NoPin> {
+    in1: IN1,
+    in2: IN2,
+    sleep: S,
+    decay: DecayMode,
+}
+
+impl<IN1, IN2> Drv8833<IN1, IN2> {
+    /// Creates a DRV8833 channel with nSLEEP hard-wired high.
+    pub fn new(in1: IN1, in2: IN2) -> Self {
+        Self {
+            in1,
+            in2,
+            sleep: NoPin,
+            decay: DecayMode::Fast,
+        }
+    }
+}
+
+impl<IN1, IN2, S> Drv8833<IN1, IN2, S> {
+    /// Attaches the active-low nSLEEP pin, driven high while enabled.
+    pub fn with_sleep<S2: OutputPin>(self, sleep: S2) -> Drv8833<IN1, IN2, S2> {
+        Drv8833 {
+            in1: self.in1,
+            in2: self.in2,
+            sleep,
+            decay: self.decay,
+        }
+    }
+
+    /// Selects fast (IN = PWM/L) or slow (IN = H/inverted PWM) current decay.
+    pub fn with_decay_mode(mut self, decay: DecayMode) -> Self {
+        self.decay = decay;
+        self
+    }
+}
+
+impl<IN1: SetDutyCycle<Error: HalCause>, IN2: SetDutyCycle<Error: HalCause>, S: OutputPin<Error: HalCause>> ChipBridge for Drv8833<IN1, IN2, S> {
+    fn drive(&mut self, forward: bool, duty: u16) -> Result<(), MotorDriverError> {
+        drive_pwm_pair(&mut self.in1, &mut self.in2, forward, duty, self.decay)
+    }
+
+    fn brake(&mut self) -> Result<(), MotorDriverError> {
+        set_fully_on(&mut self.in1, Signal::Pwm)?;
+        set_fully_on(&mut self.in2, Signal::Pwm2)
+    }
+
+    fn coast(&mut self) -> Result<(), MotorDriverError> {
+        set_duty(&mut self.in1, Signal::Pwm, 0)?;
+        set_duty(&mut self.in2, Signal::Pwm2, 0)
+    }
+
+    fn set_enabled(&mut self, enabled: bool) -> Result<(), MotorDriverError> {
+        set_pin(&mut self.sleep, Signal::Enable, enabled)
+    }
+}
+
+/// TI DRV8871 single H-bridge with PWM on IN1/IN2.
+///
+/// The chip has no sleep pin; it enters sleep on its own while both inputs are low.
+#[derive(Debug)]
+pub struct Drv8871<IN1, IN2> {
+    in1: IN1,
+    in2: IN2,
+    decay: DecayMode,
+}
+
+impl<IN1, IN2> Drv8871<IN1, IN2> {
+    /// Creates a DRV8871 driver from its two PWM inputs.
+    pub fn new(in1: IN1, in2: IN2) -> Self {
+        Self {
+            in1,
+            in2,
+            decay: DecayMode::Fast,
+        }
+    }
+
+    /// Selects fast (IN = PWM/L) or slow (IN = H/inverted PWM) current decay.
+    pub fn with_decay_mode(mut self, decay: DecayMode) -> Self {
+        self.decay = decay;
+        self
+    }
+}
+
+impl<IN1: SetDutyCycle<Error: HalCause>, IN2: SetDutyCycle<Error: HalCause>> ChipBridge for Drv8871<IN1, IN2> {
+    fn drive(&mut self, forward: bool, duty: u16) -> Result<(), MotorDriverError> {
+        drive_pwm_pair(&mut self.in1, &mut self.in2, forward, duty, self.decay)
+    }
+
+    fn brake(&mut self) -> Result<(), MotorDriverError> {
+        set_fully_on(&mut self.in1, Signal::Pwm)?;
+        set_fully_on(&mut self.in2, Signal::Pwm2)
+    }
+
+    fn coast(&mut self) -> Result<(), MotorDriverError> {
+        set_duty(&mut self.in1, Signal::Pwm, 0)?;
+        set_duty(&mut self.in2, Signal::Pwm2, 0)
+    }
+
+    fn set_enabled(&mut self, _enabled: bool) -> Result<(), MotorDriverError> {
+        Ok(())
+    }
+}
+
+/// Toshiba TB6612FNG dual H-bridge, one channel (xIN1/xIN2/PWMx) with optional STBY.
+///
+/// The direction inputs are plain GPIO and speed comes from the PWM input.
+/// During the PWM off-time the chip short-brakes the motor (slow decay).
+/// STBY is shared by both channels; give it to one of the two drivers.
+#[derive(Debug)]
+pub struct Tb6612fng<IN1, IN2, P, S = NoPin> {
+    in1: IN1,
+    in2: IN2,
+    pwm: P,
+    standby: S,
+}
+
+impl<IN1, IN2, P> Tb6612fng<IN1, IN2, P> {
+    /// Creates a TB6612FNG channel with STBY hard-wired high.
+    pub fn new(in1: IN1, in2: IN2, pwm: P) -> Self {
+        Self {
+            in1,
+            in2,
+            pwm,
+            standby: NoPin,
+        }
+    }
+}
+
+impl<IN1, IN2, P, S> Tb6612fng<IN1, IN2, P, S> {
+    /// Attaches the active-low STBY pin, driven high while enabled.
+    pub fn with_standby<S2: OutputPin>(self, standby: S2) -> Tb6612fng<IN1, IN2, P, S2> {
+        Tb6612fng {
+            in1: self.in1,
+            in2: self.in2,
+            pwm: self.pwm,
+            standby,
+        }
+    }
+}
+
+impl<IN1: OutputPin<Error: HalCause>, IN2: OutputPin<Error: HalCause>, P: SetDutyCycle<Error: HalCause>, S: OutputPin<Error: HalCause>> ChipBridge for Tb6612fng<IN1, IN2, P, S> {
+    fn drive(&mut self, forward: bool, duty: u16) -> Result<(), MotorDriverError> {
+        set_pin(&mut self.in1, Signal::Direction, forward)?;
+        set_pin(&mut self.in2, Signal::Direction2, !forward)?;
+        set_duty(&mut self.pwm, Signal::Pwm, duty)
+    }
+
+    fn brake(&mut self) -> Result<(), MotorDriverError> {
+        set_pin(&mut self.in1, Signal::Direction, true)?;
+        set_pin(&mut self.in2, Signal::Direction2, true)?;
+        set_fully_on(&mut self.pwm, Signal::Pwm)
+    }
+
+    fn coast(&mut self) -> Result<(), MotorDriverError> {
+        set_pin(&mut self.in1, Signal::Direction, false)?;
+        set_pin(&mut self.in2, Signal::Direction2, false)?;
+        set_fully_on(&mut self.pwm, Signal::Pwm)
+    }
+
+    fn set_enabled(&mut self, enabled: bool) -> Result<(), MotorDriverError> {
+        set_pin(&mut self.standby, Signal::Enable, enabled)
+    }
+}
+
+/// ST L298N dual full bridge, one channel (ENx/INx/INy).
+///
+/// Speed is applied as PWM on the enable input and direction on the two
+/// logic inputs. The enable input doubles as the coast control, so
+/// `set_enabled` has no separate pin to drive.
+#[derive(Debug)]
+pub struct L298n<EN, IN1, IN2> {
+    enable: EN,
+    in1: IN1,
+    in2: IN2,
+}
+
+impl<EN, IN1, IN2> L298n<EN, IN1, IN2> {
+    /// Creates an L298N channel from its PWM enable input and two direction inputs.
+    pub fn new(enable: EN, in1: IN1, in2: IN2) -> Self {
+        Self { enable, in1, in2 }
+    }
+}
+
+impl<EN: SetDutyCycle<Error: HalCause>, IN1: OutputPin<Error: HalCause>, IN2: OutputPin<Error: HalCause>> ChipBridge for L298n<EN, IN1, IN2> {
+    fn drive(&mut self, forward: bool, duty: u16) -> Result<(), MotorDriverError> {
+        set_duty(&mut self.enable, Signal::Pwm, 0)?;
+        set_pin(&mut self.in1, Signal::Direction, forward)?;
+        set_pin(&mut self.in2, Signal::Direction2, !forward)?;
+        set_duty(&mut self.enable, Signal::Pwm, duty)
+    }
+
+    fn brake(&mut self) -> Result<(), MotorDriverError> {
+        set_pin(&mut self.in1, Signal::Direction, false)?;
+        set_pin(&mut self.in2, Signal::Direction2, false)?;
+        set_fully_on(&mut self.enable, Signal::Pwm)
+    }
+
+    fn coast(&mut self) -> Result<(), MotorDriverError> {
+        set_duty(&mut self.enable, Signal::Pwm, 0)
+    }
+
+    fn set_enabled(&mut self, _enabled: bool) -> Result<(), MotorDriverError> {
+        Ok(())
+    }
+}
+
+/// Infineon BTS7960 (IBT-2 module) high-current half-bridge pair.
+///
+/// RPWM/LPWM select the half-bridge outputs and R_EN/L_EN enable them.
+/// Modules with R_EN and L_EN bridged can pass `NoPin` for `L_EN`.
+#[derive(Debug)]
+pub struct Bts7960<RP, LP, REN, LEN> {
+    rpwm: RP,
+    lpwm: LP,
+    r_enable: REN,
+    l_enable: LEN,
+    enabled: bool,
+}
+
+impl<RP, LP, REN, LEN> Bts7960<RP, LP, REN, LEN> {
+    /// Creates a BTS7960 driver from its two PWM inputs and two enable pins.
+    pub fn new(rpwm: RP, lpwm: LP, r_enable: REN, l_enable: LEN) -> Self {
+        Self {
+            rpwm,
+            lpwm,
+            r_enable,
+            l_enable,
+            enabled: false,
+        }
+    }
+}
+
+impl<RP: SetDutyCycle<Error: HalCause>, LP: SetDutyCycle<Error: HalCause>, REN: OutputPin<Error: HalCause>, LEN: OutputPin<Error: HalCause>> Bts7960<RP, LP, REN, LEN> {
+    fn set_outputs(&mut self, on: bool) -> Result<(), MotorDriverError> {
+        set_pin(&mut self.r_enable, Signal::Enable, on)?;
+        set_pin(&mut self.l_enable, Signal::Enable2, on)
+    }
+}
+
+impl<RP: SetDutyCycle<Error: HalCause>, LP: SetDutyCycle<Error: HalCause>, REN: OutputPin<Error: HalCause>, LEN: OutputPin<Error: HalCause>> ChipBridge for Bts7960<RP, LP, REN, LEN> {
+    fn drive(&mut self, forward: bool, duty: u16) -> Result<(), MotorDriverError> {
+        drive_pwm_pair(&mut self.rpwm, &mut self.lpwm, forward, duty, DecayMode::Fast)?;
+        self.set_outputs(self.enabled)
+    }
+
+    fn brake(&mut self) -> Result<(), MotorDriverError> {
+        set_duty(&mut self.rpwm, Signal::Pwm, 0)?;
+        set_duty(&mut self.lpwm, Signal::Pwm2, 0)?;
+        self.set_outputs(self.enabled)
+    }
+
+    fn coast(&mut self) -> Result<(), MotorDriverError> {
+        self.set_outputs(false)?;
+        set_duty(&mut self.rpwm, Signal::Pwm, 0)?;
+        set_duty(&mut self.lpwm, Signal::Pwm2, 0)
+    }
+
+    fn set_enabled(&mut self, enabled: bool) -> Result<(), MotorDriverError> {
+        self.enabled = enabled;
+        if !enabled {
+            self.set_outputs(false)?;
+        }
+        Ok(())
+    }
+}
+
+/// Motor driver for a typed H-bridge chip.
+///
+/// Speed is a signed duty cycle in `-max_duty..=max_duty` passed directly
+/// to the chip's PWM inputs. A speed of zero coasts like `stop()`.
+///
+/// The driver follows the same [`DriverState`] lifecycle as the other
+/// drivers, so speed and direction commands are only accepted once enabled.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let bridge = Tb6612fng::new(ain1, ain2, pwma).with_standby(stby);
+/// let mut motor = ChipDriver::new(bridge, 1000);
+/// motor.initialize()?;
+/// motor.enable()?;
+/// motor.set_speed(-400)?;
+/// ```
+#[derive(Debug)]
+pub struct ChipDriver<B> {
+    bridge: B,
+    max_duty: u16,
+    current_speed: i16,
+    direction: bool,
+    ppr: i16,
+    state: DriverState,
+}
+
+impl<B: ChipBridge> ChipDriver<B> {
+    /// Wraps a chip bridge whose PWM inputs use `max_duty` as full scale.
+    pub fn new(bridge: B, max_duty: u16) -> Self {
+        Self {
+            bridge,
+            max_duty,
+            current_speed: 0,
+            direction: true,
+            ppr: 0,
+            state: DriverState::Uninitialized,
+        }
+    }
+
+    /// Returns the configured maximum duty cycle value.
+    pub fn max_duty(&self) -> u16 {
+        self.max_duty
+    }
+
+    /// Returns the current lifecycle state.
+    pub fn state(&self) -> DriverState {
+        self.state
+    }
+
+    /// Returns a reference to the chip bridge.
+    pub fn bridge(&self) -> &B {
+        &self.bridge
+    }
+
+    /// Returns a mutable reference to the chip bridge.
+    pub fn bridge_mut(&mut self) -> &mut B {
+        &mut self.bridge
+    }
+
+    /// Consumes the driver and returns the chip bridge.
+    pub fn into_inner(self) -> B {
+        self.bridge
+    }
+
+    /// Moves between `Enabled` and `Running` to match the commanded speed.
+    fn settle_state(&mut self) {
+        if self.state.can_drive() {
+            self.state = if self.current_speed != 0 {
+                DriverState::Running
+            } else {
+                DriverState::Enabled
+            };
+        }
+    }
+
+    fn apply(&mut self) -> Result<(), MotorDriverError> {
+        if self.current_speed == 0 {
+            self.bridge.coast()
+        } else {
+            let duty = self.current_speed.unsigned_abs().min(self.max_duty);
+            self.bridge.drive(self.direction, duty)
+        }
+    }
+}
+
+impl<B: ChipBridge> MotorDriver for ChipDriver<B> {
+    type Error = MotorDriverError;
+
+    fn initialize(&mut self) -> Result<(), Self::Error> {
+        self.bridge.set_enabled(false)?;
+        self.bridge.coast()?;
+        self.current_speed = 0;
+        self.state = DriverState::Disabled;
+        Ok(())
+    }
+
+    fn enable(&mut self) -> Result<(), Self::Error> {
+        if !self.state.is_initialized() {
+            return Err(MotorDriverError::NotInitialized);
+        }
+        self.bridge.set_enabled(true)?;
+        self.state = DriverState::Enabled;
+        self.settle_state();
+        Ok(())
+    }
+
+    fn disable(&mut self) -> Result<(), Self::Error> {
+        if !self.state.is_initialized() {
+            return Err(MotorDriverError::NotInitialized);
+        }
+        self.bridge.set_enabled(false)?;
+        if self.state.can_drive() {
+            self.state = DriverState::Disabled;
+        }
+        Ok(())
+    }
+
+    fn set_speed(&mut self, speed: i16) -> Result<(), Self::Error> {
+        if !self.state.is_initialized() {
+            return Err(MotorDriverError::NotInitialized);
+        }
+        if speed.unsigned_abs() > self.max_duty {
+            return Err(MotorDriverError::InvalidSpeed);
+        }
+        if !self.state.can_drive() {
+            return Err(MotorDriverError::InvalidState);
+        }
+
+        self.current_speed = speed;
+        if speed != 0 {
+            self.direction = speed > 0;
+        }
+        self.settle_state();
+        self.apply()
+    }
+
+    fn set_direction(&mut self, forward: bool) -> Result<(), Self::Error> {
+        if !self.state.is_initialized() {
+            return Err(MotorDriverError::NotInitialized);
+        }
+        if !self.state.can_drive() {
+            return Err(MotorDriverError::InvalidState);
+        }
+
+        self.direction = forward;
+        self.current_speed = if forward {
+            self.current_speed.saturating_abs()
+        } else {
+            -self.current_speed.saturating_abs()
+        };
+        self.apply()
+    }
+
+    fn stop(&mut self) -> Result<(), Self::Error> {
+        if !self.state.is_initialized() {
+            return Err(MotorDriverError::NotInitialized);
+        }
+
+        self.current_speed = 0;
+        self.settle_state();
+        self.bridge.coast()
+    }
+
+    fn brake(&mut self) -> Result<(), Self::Error> {
+        if !self.state.is_initialized() {
+            return Err(MotorDriverError::NotInitialized);
+        }
+
+        self.current_speed = 0;
+        self.settle_state();
+        self.bridge.brake()
+    }
+
+    fn get_speed(&self) -> Result<i16, Self::Error> {
+        if !self.state.is_initialized() {
+            return Err(MotorDriverError::NotInitialized);
+        }
+        Ok(self.current_speed)
+    }
+
+    fn get_direction(&self) -> Result<bool, Self::Error> {
+        if !self.state.is_initialized() {
+            return Err(MotorDriverError::NotInitialized);
+        }
+        Ok(self.direction)
+    }
+
+    fn set_ppr(&mut self, ppr: i16) -> Result<bool, Self::Error> {
+        if !self.state.is_initialized() {
+            return Err(MotorDriverError::NotInitialized);
+        }
+        if ppr <= 0 {
+            return Err(MotorDriverError::InvalidSpeed);
+        }
+        self.ppr = ppr;
+        Ok(true)
+    }
+
+    fn check_ppr(&mut self) -> Result<(), Self::Error> {
+        if self.ppr == 0 {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+        Ok(())
+    }
+
+    fn get_current(&self) -> Result<f32, Self::Error> {
+        Err(MotorDriverError::HardwareFault)
+    }
+
+    fn get_voltage(&self) -> Result<f32, Self::Error> {
+        Err(MotorDriverError::HardwareFault)
+    }
+
+    fn get_temperature(&self) -> Result<f32, Self::Error> {
+        Err(MotorDriverError::HardwareFault)
+    }
+
+    fn get_fault_status(&self) -> Result<FaultStatus, Self::Error> {
+        if !self.state.is_initialized() {
+            return Err(MotorDriverError::NotInitialized);
+        }
+        Ok(FaultStatus::empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Event, Log, MockPwm};
+    use Event::{Duty, Pin};
+
+    fn drv8833(log: &Log) -> ChipDriver<Drv8833<MockPwm, MockPwm>> {
+        ChipDriver::new(Drv8833::new(log.pwm("in1"), log.pwm("in2")), 1000)
+    }
+
+    fn start<B: ChipBridge>(motor: &mut ChipDriver<B>) {
+        motor.initialize().unwrap();
+        motor.enable().unwrap();
+    }
+
+    #[test]
+    fn drv8833_truth_table() {
+        let log = Log::new();
+        let mut motor = ChipDriver::new(Drv8833::new(log.pwm("in1"), log.pwm("in2")).with_sleep(log.pin("nsleep")), 1000);
+        start(&mut motor);
+        assert_eq!(log.take(), [Pin("nsleep", false), Duty("in1", 0), Duty("in2", 0), Pin("nsleep", true)]);
+
+        motor.set_speed(400).unwrap();
+        assert_eq!(log.take(), [Duty("in2", 0), Duty("in1", 400)]);
+        motor.set_speed(-250).unwrap();
+        assert_eq!(log.take(), [Duty("in1", 0), Duty("in2", 250)]);
+        motor.brake().unwrap();
+        assert_eq!(log.take(), [Duty("in1", 1000), Duty("in2", 1000)]);
+        motor.stop().unwrap();
+        assert_eq!(log.take(), [Duty("in1", 0), Duty("in2", 0)]);
+    }
+
+    #[test]
+    fn drv8871_truth_table() {
+        let log = Log::new();
+        let mut motor = ChipDriver::new(Drv8871::new(log.pwm("in1"), log.pwm("in2")), 1000);
+        start(&mut motor);
+        assert_eq!(log.take(), [Duty("in1", 0), Duty("in2", 0)]);
+
+        motor.set_speed(700).unwrap();
+        assert_eq!(log.take(), [Duty("in2", 0), Duty("in1", 700)]);
+        motor.set_speed(-1000).unwrap();
+        assert_eq!(log.take(), [Duty("in1", 0), Duty("in2", 1000)]);
+        motor.brake().unwrap();
+        assert_eq!(log.take(), [Duty("in1", 1000), Duty("in2", 1000)]);
+        motor.stop().unwrap();
+        assert_eq!(log.take(), [Duty("in1", 0), Duty("in2", 0)]);
+    }
+
+    #[test]
+    fn tb6612fng_truth_table() {
+        let log = Log::new();
+        let bridge = Tb6612fng::new(log.pin("in1"), log.pin("in2"), log.pwm("pwm")).with_standby(log.pin("stby"));
+        let mut motor = ChipDriver::new(bridge, 1000);
+        start(&mut motor);
+        assert_eq!(
+            log.take(),
+            [Pin("stby", false), Pin("in1", false), Pin("in2", false), Duty("pwm", 1000), Pin("stby", true)]
+        );
+
+        motor.set_speed(600).unwrap();
+        assert_eq!(log.take(), [Pin("in1", true), Pin("in2", false), Duty("pwm", 600)]);
+        motor.set_speed(-300).unwrap();
+        assert_eq!(log.take(), [Pin("in1", false), Pin("in2", true), Duty("pwm", 300)]);
+        motor.brake().unwrap();
+        assert_eq!(log.take(), [Pin("in1", true), Pin("in2", true), Duty("pwm", 1000)]);
+        motor.stop().unwrap();
+        assert_eq!(log.take(), [Pin("in1", false), Pin("in2", false), Duty("pwm", 1000)]);
+    }
+
+    #[test]
+    fn l298n_truth_table() {
+        let log = Log::new();
+        let mut motor = ChipDriver::new(L298n::new(log.pwm("en"), log.pin("in1"), log.pin("in2")), 1000);
+        start(&mut motor);
+        assert_eq!(log.take(), [Duty("en", 0)]);
+
+        // The enable input is dropped while the direction inputs change
+        motor.set_speed(500).unwrap();
+        assert_eq!(log.take(), [Duty("en", 0), Pin("in1", true), Pin("in2", false), Duty("en", 500)]);
+        motor.set_speed(-800).unwrap();
+        assert_eq!(log.take(), [Duty("en", 0), Pin("in1", false), Pin("in2", true), Duty("en", 800)]);
+        motor.brake().unwrap();
+        assert_eq!(log.take(), [Pin("in1", false), Pin("in2", false), Duty("en", 1000)]);
+        motor.stop().unwrap();
+        assert_eq!(log.take(), [Duty("en", 0)]);
+    }
+
+    #[test]
+    fn bts7960_truth_table() {
+        let log = Log::new();
+        let bridge = Bts7960::new(log.pwm("rpwm"), log.pwm("lpwm"), log.pin("r_en"), log.pin("l_en"));
+        let mut motor = ChipDriver::new(bridge, 1000);
+        start(&mut motor);
+        assert_eq!(
+            log.take(),
+            [Pin("r_en", false), Pin("l_en", false), Pin("r_en", false), Pin("l_en", false), Duty("rpwm", 0), Duty("lpwm", 0)]
+        );
+
+        motor.set_speed(900).unwrap();
+        assert_eq!(log.take(), [Duty("lpwm", 0), Duty("rpwm", 900), Pin("r_en", true), Pin("l_en", true)]);
+        motor.set_speed(-100).unwrap();
+        assert_eq!(log.take(), [Duty("rpwm", 0), Duty("lpwm", 100), Pin("r_en", true), Pin("l_en", true)]);
+        motor.brake().unwrap();
+        assert_eq!(log.take(), [Duty("rpwm", 0), Duty("lpwm", 0), Pin("r_en", true), Pin("l_en", true)]);
+        motor.stop().unwrap();
+        assert_eq!(log.take(), [Pin("r_en", false), Pin("l_en", false), Duty("rpwm", 0), Duty("lpwm", 0)]);
+
+        // Disabling switches both half-bridges off
+        motor.disable().unwrap();
+        assert_eq!(log.take(), [Pin("r_en", false), Pin("l_en", false)]);
+    }
+
+    #[test]
+    fn follows_driver_state_transitions() {
+        let log = Log::new();
+        let mut motor = drv8833(&log);
+        assert_eq!(motor.set_speed(300), Err(MotorDriverError::NotInitialized));
+
+        motor.initialize().unwrap();
+        assert_eq!(motor.state(), DriverState::Disabled);
+        log.take();
+        assert_eq!(motor.set_speed(300), Err(MotorDriverError::InvalidState));
+        assert_eq!(motor.set_direction(false), Err(MotorDriverError::InvalidState));
+        assert!(log.take().is_empty());
+
+        motor.enable().unwrap();
+        assert_eq!(motor.state(), DriverState::Enabled);
+        motor.set_speed(300).unwrap();
+        assert_eq!(motor.state(), DriverState::Running);
+        motor.set_direction(false).unwrap();
+        assert_eq!(motor.get_speed(), Ok(-300));
+        motor.brake().unwrap();
+        assert_eq!(motor.state(), DriverState::Enabled);
+
+        motor.set_speed(300).unwrap();
+        motor.disable().unwrap();
+        assert_eq!(motor.state(), DriverState::Disabled);
+        assert_eq!(motor.set_speed(300), Err(MotorDriverError::InvalidState));
+    }
+}
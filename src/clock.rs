@@ -0,0 +1,192 @@
+//! Monotonic time sources for time-dependent features.
+//!
+//! Velocity estimation, ramps, watchdogs and control loops read time through
+//! the [`Clock`] trait, so they run on `std::time::Instant`, on a hardware
+//! timer in `no_std` builds, or on a [`MockClock`] that tests advance by hand.
+
+use core::cell::Cell;
+
+/// A monotonic microsecond time source.
+///
+/// Implement this for a free-running hardware timer to use the time-dependent
+/// features in `no_std` builds. The absolute value is irrelevant, only
+/// differences between readings are used.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// struct TimerClock(Timer);
+///
+/// impl Clock for TimerClock {
+///     fn now_micros(&self) -> u64 {
+///         self.0.now().duration_since_epoch().to_micros()
+///     }
+/// }
+/// ```
+pub trait Clock {
+    /// Returns the current time in microseconds. Must never go backwards.
+    fn now_micros(&self) -> u64;
+}
+
+impl<T: Clock + ?Sized> Clock for &T {
+    fn now_micros(&self) -> u64 {
+        (**self).now_micros()
+    }
+}
+
+/// Clock backed by `std::time::Instant`, counting from its creation.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct StdClock {
+    epoch: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl StdClock {
+    /// Creates a clock reading zero now.
+    pub fn new() -> Self {
+        Self {
+            epoch: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for StdClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    fn now_micros(&self) -> u64 {
+        self.epoch.elapsed().as_micros() as u64
+    }
+}
+
+/// Manually advanced clock for deterministic tests and simulations.
+///
+/// Share it with a driver by reference, `&MockClock` implements [`Clock`] too.
+///
+/// # Example
+///
+/// ```rust
+/// use motor_driver_hal::clock::{Clock, MockClock};
+///
+/// let clock = MockClock::new();
+/// clock.advance(1_500);
+/// assert_eq!((&clock).now_micros(), 1_500);
+/// ```
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now: Cell<u64>,
+}
+
+impl MockClock {
+    /// Creates a clock reading zero.
+    pub const fn new() -> Self {
+        Self { now: Cell::new(0) }
+    }
+
+    /// Moves the clock forward by `micros` microseconds.
+    pub fn advance(&self, micros: u64) {
+        self.now.set(self.now.get().saturating_add(micros));
+    }
+
+    /// Sets the current time. Times earlier than the current reading are
+    /// ignored, so the clock never runs backwards.
+    pub fn set(&self, micros: u64) {
+        self.now.set(self.now.get().max(micros));
+    }
+}
+
+impl Clock for MockClock {
+    fn now_micros(&self) -> u64 {
+        self.now.get()
+    }
+}
+
+/// Clock that never advances.
+///
+/// The default for `no_std` builds until a real clock is supplied with
+/// `with_clock()`. Time-dependent features see no time passing, so for
+/// example velocity estimates stay at zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoClock;
+
+impl Clock for NoClock {
+    fn now_micros(&self) -> u64 {
+        0
+    }
+}
+
+/// Clock used by drivers unless another one is supplied with `with_clock()`.
+#[cfg(feature = "std")]
+pub type DefaultClock = StdClock;
+
+/// Clock used by drivers unless another one is supplied with `with_clock()`.
+#[cfg(not(feature = "std"))]
+pub type DefaultClock = NoClock;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_clock_only_moves_forward() {
+        let clock = MockClock::new();
+        assert_eq!(clock.now_micros(), 0);
+
+        clock.advance(250);
+        clock.advance(750);
+        assert_eq!(clock.now_micros(), 1_000);
+
+        clock.set(5_000);
+        assert_eq!(clock.now_micros(), 5_000);
+        clock.set(4_000);
+        assert_eq!(clock.now_micros(), 5_000);
+
+        clock.advance(u64::MAX);
+        assert_eq!(clock.now_micros(), u64::MAX);
+    }
+
+    #[test]
+    fn mock_clock_is_shared_by_reference() {
+        fn reader<C: Clock>(clock: C) -> C {
+            clock
+        }
+
+        let clock = MockClock::new();
+        let first = reader(&clock);
+        let second = first;
+        clock.advance(1_234);
+        assert_eq!(first.now_micros(), 1_234);
+        assert_eq!(second.now_micros(), 1_234);
+
+        second.advance(66);
+        assert_eq!(first.now_micros(), 1_300);
+        assert_eq!(clock.now_micros(), 1_300);
+    }
+
+    #[test]
+    fn no_clock_stays_at_zero() {
+        let clock = NoClock;
+        let copy = clock;
+        for _ in 0..3 {
+            assert_eq!(clock.now_micros(), 0);
+            assert_eq!(copy.now_micros(), 0);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn std_clock_is_monotonic() {
+        let clock = StdClock::new();
+        let first = clock.now_micros();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = clock.now_micros();
+        assert!(second >= first + 2_000);
+        assert!(clock.now_micros() >= second);
+    }
+}
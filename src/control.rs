@@ -0,0 +1,745 @@
+//! Closed-loop motor control built on encoder feedback.
+
+use crate::clock::{Clock, DefaultClock};
+use crate::current::{NoSense, PowerSense};
+use crate::profile::MotionProfile;
+use crate::{HalCause, HBridgeMotorDriver, MotorDriver, MotorDriverError, NoEncoder, NoPin};
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal::pwm::SetDutyCycle;
+
+/// Rounds to the nearest integer duty value, saturating at the `i16` range.
+pub(crate) fn round_duty(value: f32) -> i16 {
+    (value + 0.5f32.copysign(value)) as i16
+}
+
+/// Rounds to the nearest pulse count, saturating at the `i32` range.
+pub(crate) fn round_pulses(value: f32) -> i32 {
+    (value + 0.5f32.copysign(value)) as i32
+}
+
+/// PID controller with output clamping and integrator anti-windup.
+///
+/// The integrator is frozen whenever the output is saturated and the error
+/// would push it further into saturation, so the loop recovers immediately
+/// once the error changes sign.
+///
+/// # Example
+///
+/// ```rust
+/// use motor_driver_hal::control::Pid;
+///
+/// let mut pid = Pid::new(2.0, 0.5, 0.0).with_output_limits(-1000.0, 1000.0);
+/// let output = pid.update(10.0, 0.01);
+/// assert!(output > 0.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pid {
+    kp: f32,
+    ki: f32,
+    kd: f32,
+    output_min: f32,
+    output_max: f32,
+    integral: f32,
+    last_error: Option<f32>,
+}
+
+impl Pid {
+    /// Creates a PID controller with the given gains and unlimited output.
+    ///
+    /// # Arguments
+    ///
+    /// * `kp` - Proportional gain
+    /// * `ki` - Integral gain (per second)
+    /// * `kd` - Derivative gain (seconds)
+    pub fn new(kp: f32, ki: f32, kd: f32) -> Self {
+        Self {
+            kp,
+            ki,
+            kd,
+            output_min: f32::NEG_INFINITY,
+            output_max: f32::INFINITY,
+            integral: 0.0,
+            last_error: None,
+        }
+    }
+
+    /// Limits the controller output to `min..=max`.
+    pub fn with_output_limits(mut self, min: f32, max: f32) -> Self {
+        self.output_min = min;
+        self.output_max = max;
+        self
+    }
+
+    /// Runs one controller step and returns the clamped output.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - Setpoint minus measurement
+    /// * `dt` - Time since the previous step in seconds
+    pub fn update(&mut self, error: f32, dt: f32) -> f32 {
+        let derivative = match self.last_error {
+            Some(last) if dt > 0.0 => (error - last) / dt,
+            _ => 0.0,
+        };
+        self.last_error = Some(error);
+
+        let integral = self.integral + error * dt;
+        let unclamped = self.kp * error + self.ki * integral + self.kd * derivative;
+        let output = unclamped.clamp(self.output_min, self.output_max);
+
+        let winding_up = (unclamped > self.output_max && error > 0.0)
+            || (unclamped < self.output_min && error < 0.0);
+        if !winding_up {
+            self.integral = integral;
+        }
+
+        output
+    }
+
+    /// Clears the integrator and derivative history.
+    pub fn reset(&mut self) {
+        self.integral = 0.0;
+        self.last_error = None;
+    }
+}
+
+/// Measures the time between control steps on the driver's clock.
+///
+/// Falls back to the nominal period for the first step and whenever the
+/// clock does not advance, e.g. with `NoClock`.
+#[derive(Debug, Clone, Copy)]
+struct LoopTimer {
+    period: f32,
+    last_us: Option<u64>,
+}
+
+impl LoopTimer {
+    fn new(period: f32) -> Self {
+        Self { period, last_us: None }
+    }
+
+    fn with_rate(self, rate_hz: f32) -> Result<Self, MotorDriverError> {
+        if !rate_hz.is_finite() || rate_hz <= 0.0 {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+        Ok(Self::new(1.0 / rate_hz))
+    }
+
+    /// Returns the seconds elapsed since the previous step.
+    fn step(&mut self, now_us: u64) -> f32 {
+        let dt = match self.last_us {
+            Some(last) if now_us > last => (now_us - last) as f32 * 1e-6,
+            _ => self.period,
+        };
+        self.last_us = Some(now_us);
+        dt
+    }
+
+    fn reset(&mut self) {
+        self.last_us = None;
+    }
+}
+
+/// Motion profile being followed by a controller.
+#[derive(Debug, Clone, Copy)]
+struct ActiveProfile {
+    profile: MotionProfile,
+    start: i32,
+    elapsed: f32,
+}
+
+impl ActiveProfile {
+    fn new(profile: MotionProfile, start: i32) -> Self {
+        Self { profile, start, elapsed: 0.0 }
+    }
+}
+
+/// Closed-loop velocity controller for encoder-equipped H-bridge drivers.
+///
+/// The controller estimates the motor speed in RPM from the change in
+/// `get_pulse_count()` between steps and the driver's pulses per revolution,
+/// and drives `set_speed()` through a [`Pid`] whose output is clamped to the
+/// driver's maximum duty cycle. The time between `update()` calls is measured
+/// on the driver's clock, so late or jittery iterations do not skew the speed
+/// estimate or the I and D terms. The configured loop rate is only used for
+/// the first step and with a clock that does not advance, such as `NoClock`.
+/// The encoder itself has to be kept up to date by polling `read_encoder()`
+/// or by an edge counter.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let mut controller = VelocityController::new(motor, Pid::new(4.0, 20.0, 0.0))?
+///     .with_loop_rate(200.0)?;
+///
+/// controller.set_target_rpm(120.0);
+/// loop {
+///     controller.update()?;
+///     delay.delay_ms(5);
+/// }
+/// ```
+pub struct VelocityController<E1, E2, P1, P2, Enc1, Enc2, D = NoPin, F = NoEncoder, S = NoSense, C = DefaultClock> {
+    driver: HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>,
+    pid: Pid,
+    timer: LoopTimer,
+    target_rpm: f32,
+    measured_rpm: f32,
+    last_count: i32,
+    profile: Option<ActiveProfile>,
+}
+
+impl<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C> VelocityController<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>
+where
+    E1: OutputPin<Error: HalCause>,
+    E2: OutputPin<Error: HalCause>,
+    P1: SetDutyCycle<Error: HalCause>,
+    P2: SetDutyCycle<Error: HalCause>,
+    Enc1: InputPin<Error: HalCause>,
+    Enc2: InputPin<Error: HalCause>,
+    D: OutputPin<Error: HalCause>,
+    F: InputPin<Error: HalCause>,
+    S: PowerSense,
+    C: Clock,
+{
+    /// Wraps an initialized driver with a velocity loop running at 100 Hz.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if the driver has no pulses per revolution configured.
+    pub fn new(driver: HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>, pid: Pid) -> Result<Self, MotorDriverError> {
+        if driver.ppr() == 0 {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+
+        let max_duty = driver.max_duty() as f32;
+        Ok(Self {
+            last_count: driver.get_pulse_count(),
+            driver,
+            pid: pid.with_output_limits(-max_duty, max_duty),
+            timer: LoopTimer::new(0.01),
+            target_rpm: 0.0,
+            measured_rpm: 0.0,
+            profile: None,
+        })
+    }
+
+    /// Sets the nominal rate in Hz at which `update()` is called.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` unless `rate_hz` is finite and positive.
+    pub fn with_loop_rate(mut self, rate_hz: f32) -> Result<Self, MotorDriverError> {
+        self.timer = self.timer.with_rate(rate_hz)?;
+        Ok(self)
+    }
+
+    /// Sets the velocity setpoint in RPM (negative values run in reverse).
+    ///
+    /// This cancels any motion profile being followed.
+    pub fn set_target_rpm(&mut self, rpm: f32) {
+        self.profile = None;
+        self.target_rpm = rpm;
+    }
+
+    /// Follows the velocity of a motion profile given in pulses.
+    ///
+    /// Each `update()` advances the profile by the measured step time and uses
+    /// its velocity as the setpoint. The setpoint returns to zero once the profile ends.
+    pub fn follow_profile(&mut self, profile: MotionProfile) {
+        self.profile = Some(ActiveProfile::new(profile, self.driver.get_pulse_count()));
+    }
+
+    /// Returns `true` while a motion profile is being followed.
+    pub fn is_following_profile(&self) -> bool {
+        self.profile.is_some()
+    }
+
+    /// Returns the velocity setpoint in RPM.
+    pub fn target_rpm(&self) -> f32 {
+        self.target_rpm
+    }
+
+    /// Returns the speed measured during the last `update()` in RPM.
+    pub fn measured_rpm(&self) -> f32 {
+        self.measured_rpm
+    }
+
+    /// Runs one control step and returns the commanded duty.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error raised by the driver while applying the new speed.
+    pub fn update(&mut self) -> Result<i16, MotorDriverError> {
+        let dt = self.timer.step(self.driver.now_micros());
+        let count = self.driver.get_pulse_count();
+        let delta = count.wrapping_sub(self.last_count);
+        self.last_count = count;
+        self.measured_rpm = delta as f32 / self.driver.ppr() as f32 / dt * 60.0;
+
+        if let Some(ref mut active) = self.profile {
+            active.elapsed += dt;
+            let setpoint = active.profile.sample(active.elapsed);
+            self.target_rpm = setpoint.velocity / self.driver.ppr() as f32 * 60.0;
+            if active.profile.is_finished(active.elapsed) {
+                self.profile = None;
+            }
+        }
+
+        let output = self.pid.update(self.target_rpm - self.measured_rpm, dt);
+        let duty = round_duty(output);
+        self.driver.set_speed(duty)?;
+        Ok(duty)
+    }
+
+    /// Clears the controller state and resynchronizes with the encoder.
+    pub fn reset(&mut self) {
+        self.pid.reset();
+        self.timer.reset();
+        self.last_count = self.driver.get_pulse_count();
+        self.measured_rpm = 0.0;
+        self.profile = None;
+    }
+
+    /// Returns a reference to the wrapped driver.
+    pub fn driver(&self) -> &HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C> {
+        &self.driver
+    }
+
+    /// Returns a mutable reference to the wrapped driver.
+    pub fn driver_mut(&mut self) -> &mut HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C> {
+        &mut self.driver
+    }
+
+    /// Consumes the controller and returns the wrapped driver.
+    pub fn into_inner(self) -> HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C> {
+        self.driver
+    }
+}
+
+/// Closed-loop position controller for encoder-equipped H-bridge drivers.
+///
+/// Targets are absolute, multi-turn pulse counts relative to the encoder
+/// reset point and are stored as the driver's target pulse. Each `update()`
+/// runs a [`Pid`] on the position error and drives `set_speed()` with the
+/// output clamped to the driver's maximum duty cycle. As with
+/// [`VelocityController`], the step time is measured on the driver's clock
+/// and the encoder has to be kept up to date.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let mut controller = PositionController::new(motor, Pid::new(3.0, 0.5, 0.05))?
+///     .with_loop_rate(500.0)?;
+///
+/// controller.move_to_revolutions(2.5);
+/// while !controller.is_at_target(4) {
+///     controller.update()?;
+///     delay.delay_ms(2);
+/// }
+/// controller.driver_mut().brake()?;
+/// ```
+pub struct PositionController<E1, E2, P1, P2, Enc1, Enc2, D = NoPin, F = NoEncoder, S = NoSense, C = DefaultClock> {
+    driver: HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>,
+    pid: Pid,
+    timer: LoopTimer,
+    profile: Option<ActiveProfile>,
+}
+
+impl<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C> PositionController<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>
+where
+    E1: OutputPin<Error: HalCause>,
+    E2: OutputPin<Error: HalCause>,
+    P1: SetDutyCycle<Error: HalCause>,
+    P2: SetDutyCycle<Error: HalCause>,
+    Enc1: InputPin<Error: HalCause>,
+    Enc2: InputPin<Error: HalCause>,
+    D: OutputPin<Error: HalCause>,
+    F: InputPin<Error: HalCause>,
+    S: PowerSense,
+    C: Clock,
+{
+    /// Wraps an initialized driver with a position loop running at 100 Hz.
+    ///
+    /// The current encoder position becomes the initial target, so the motor
+    /// holds still until a move is requested.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if the driver has no encoder feedback.
+    pub fn new(mut driver: HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>, pid: Pid) -> Result<Self, MotorDriverError> {
+        if !driver.has_encoder() {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+
+        let max_duty = driver.max_duty() as f32;
+        driver.set_target_pulse(driver.get_pulse_count());
+        Ok(Self {
+            driver,
+            pid: pid.with_output_limits(-max_duty, max_duty),
+            timer: LoopTimer::new(0.01),
+            profile: None,
+        })
+    }
+
+    /// Sets the nominal rate in Hz at which `update()` is called.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` unless `rate_hz` is finite and positive.
+    pub fn with_loop_rate(mut self, rate_hz: f32) -> Result<Self, MotorDriverError> {
+        self.timer = self.timer.with_rate(rate_hz)?;
+        Ok(self)
+    }
+
+    /// Moves to an absolute pulse count relative to the encoder reset point.
+    ///
+    /// This cancels any motion profile being followed.
+    pub fn move_to(&mut self, pulses: i32) {
+        self.profile = None;
+        self.driver.set_target_pulse(pulses);
+    }
+
+    /// Moves by a number of pulses relative to the current target.
+    ///
+    /// Relative moves accumulate on the target rather than the measured
+    /// position, so repeated moves do not drift.
+    pub fn move_by(&mut self, pulses: i32) {
+        self.move_to(self.driver.target_pulse().saturating_add(pulses));
+    }
+
+    /// Moves along a motion profile given in pulses, relative to the current target.
+    ///
+    /// Each `update()` advances the profile by the measured step time and uses
+    /// its position as the target. When the profile ends the target is set to
+    /// exactly the start plus the profile distance.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let profile = MotionProfile::s_curve(4000.0, 2000.0, 8000.0, 40000.0)?;
+    /// controller.move_profiled(profile);
+    /// ```
+    pub fn move_profiled(&mut self, profile: MotionProfile) {
+        self.profile = Some(ActiveProfile::new(profile, self.driver.target_pulse()));
+    }
+
+    /// Returns `true` while a motion profile is being followed.
+    pub fn is_following_profile(&self) -> bool {
+        self.profile.is_some()
+    }
+
+    /// Moves to an absolute position given in (possibly fractional) revolutions.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if the driver has no pulses per revolution configured.
+    pub fn move_to_revolutions(&mut self, revolutions: f32) -> Result<(), MotorDriverError> {
+        if self.driver.ppr() == 0 {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+        self.move_to(round_pulses(revolutions * self.driver.ppr() as f32));
+        Ok(())
+    }
+
+    /// Returns the absolute target pulse count.
+    pub fn target(&self) -> i32 {
+        self.driver.target_pulse()
+    }
+
+    /// Returns the signed distance from the current position to the target in pulses.
+    pub fn position_error(&self) -> i32 {
+        self.driver.target_pulse().saturating_sub(self.driver.get_pulse_count())
+    }
+
+    /// Checks whether the position is within `tolerance` pulses of the target.
+    pub fn is_at_target(&self, tolerance: u32) -> bool {
+        self.driver.is_at_target(tolerance)
+    }
+
+    /// Runs one control step and returns the commanded duty.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error raised by the driver while applying the new speed.
+    pub fn update(&mut self) -> Result<i16, MotorDriverError> {
+        let dt = self.timer.step(self.driver.now_micros());
+        if let Some(ref mut active) = self.profile {
+            active.elapsed += dt;
+            let offset = active.profile.sample(active.elapsed).position;
+            self.driver.set_target_pulse(active.start.saturating_add(round_pulses(offset)));
+            if active.profile.is_finished(active.elapsed) {
+                self.profile = None;
+            }
+        }
+
+        let output = self.pid.update(self.position_error() as f32, dt);
+        let duty = round_duty(output);
+        self.driver.set_speed(duty)?;
+        Ok(duty)
+    }
+
+    /// Clears the controller state and holds the current position.
+    pub fn reset(&mut self) {
+        self.pid.reset();
+        self.timer.reset();
+        self.profile = None;
+        self.driver.set_target_pulse(self.driver.get_pulse_count());
+    }
+
+    /// Returns a reference to the wrapped driver.
+    pub fn driver(&self) -> &HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C> {
+        &self.driver
+    }
+
+    /// Returns a mutable reference to the wrapped driver.
+    pub fn driver_mut(&mut self) -> &mut HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C> {
+        &mut self.driver
+    }
+
+    /// Consumes the controller and returns the wrapped driver.
+    pub fn into_inner(self) -> HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C> {
+        self.driver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::mock::{Log, MockEncoder, MockPin, MockPwm};
+    use crate::driver::HBridgeMotorDriverBuilder;
+
+    type Driver<'a> = HBridgeMotorDriver<MockPin, MockPin, MockPwm, MockPwm, MockPin, MockPin, NoPin, NoEncoder, NoSense, &'a MockClock>;
+
+    fn encoder_driver<'a>(log: &Log, encoder: &MockEncoder, clock: &'a MockClock, ppr: u16) -> Driver<'a> {
+        let (a, b) = encoder.pins();
+        let mut motor = HBridgeMotorDriverBuilder::new()
+            .with_enable(log.pin("en"))
+            .with_dual_pwm(log.pwm("fwd"), log.pwm("rev"))
+            .with_encoder(a, b)
+            .with_ppr(ppr)
+            .with_clock(clock)
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        motor
+    }
+
+    #[test]
+    fn pid_freezes_the_integral_while_saturated() {
+        let mut pid = Pid::new(1.0, 1.0, 0.0).with_output_limits(-10.0, 10.0);
+        for _ in 0..5 {
+            assert_eq!(pid.update(100.0, 0.1), 10.0);
+        }
+        assert_eq!(pid.integral, 0.0);
+
+        // Without a wound-up integral the output follows the error as soon as it changes sign
+        assert!((pid.update(-1.0, 0.1) + 1.1).abs() < 1e-6);
+        assert!((pid.integral + 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn pid_integrates_below_saturation() {
+        let mut pid = Pid::new(1.0, 2.0, 0.0).with_output_limits(-10.0, 10.0);
+        pid.update(1.0, 0.5);
+        pid.update(1.0, 0.5);
+        assert!((pid.integral - 1.0).abs() < 1e-6);
+        assert!((pid.update(1.0, 0.5) - 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn velocity_controller_requires_ppr() {
+        let log = Log::new();
+        let clock = MockClock::new();
+        let motor = encoder_driver(&log, &MockEncoder::new(&log), &clock, 0);
+        assert!(matches!(VelocityController::new(motor, Pid::new(1.0, 0.0, 0.0)), Err(MotorDriverError::InvalidConfiguration)));
+    }
+
+    #[test]
+    fn velocity_controller_clamps_to_max_duty() {
+        let log = Log::new();
+        let clock = MockClock::new();
+        let motor = encoder_driver(&log, &MockEncoder::new(&log), &clock, 100);
+        let mut controller = VelocityController::new(motor, Pid::new(100.0, 0.0, 0.0)).unwrap();
+
+        controller.set_target_rpm(1000.0);
+        assert_eq!(controller.update().unwrap(), 1000);
+        assert_eq!(log.duty("fwd"), Some(1000));
+
+        controller.set_target_rpm(-1000.0);
+        clock.advance(10_000);
+        assert_eq!(controller.update().unwrap(), -1000);
+        assert_eq!(log.duty("rev"), Some(1000));
+    }
+
+    #[test]
+    fn velocity_controller_estimates_rpm_from_pulse_deltas() {
+        let log = Log::new();
+        let clock = MockClock::new();
+        let mut encoder = MockEncoder::new(&log);
+        let motor = encoder_driver(&log, &encoder, &clock, 100);
+        let mut controller = VelocityController::new(motor, Pid::new(0.0, 0.0, 0.0)).unwrap();
+
+        // The first step has no previous timestamp and uses the nominal 10 ms period
+        encoder.turn(5, || controller.driver_mut().read_encoder().unwrap());
+        controller.update().unwrap();
+        assert!((controller.measured_rpm() - 300.0).abs() < 1e-3);
+
+        // 10 pulses in 20 ms at 100 pulses per revolution
+        clock.advance(20_000);
+        encoder.turn(10, || controller.driver_mut().read_encoder().unwrap());
+        controller.update().unwrap();
+        assert!((controller.measured_rpm() - 300.0).abs() < 1e-3);
+
+        clock.advance(5_000);
+        encoder.turn(-2, || controller.driver_mut().read_encoder().unwrap());
+        controller.update().unwrap();
+        assert!((controller.measured_rpm() + 240.0).abs() < 1e-3);
+
+        clock.advance(5_000);
+        controller.update().unwrap();
+        assert_eq!(controller.measured_rpm(), 0.0);
+    }
+
+    #[test]
+    fn position_controller_requires_an_encoder() {
+        let log = Log::new();
+        let mut motor = HBridgeMotorDriverBuilder::<_, MockPin, _, _, NoEncoder, NoEncoder>::new()
+            .with_enable(log.pin("en"))
+            .with_dual_pwm(log.pwm("fwd"), log.pwm("rev"))
+            .with_clock(MockClock::new())
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        assert!(matches!(PositionController::new(motor, Pid::new(1.0, 0.0, 0.0)), Err(MotorDriverError::InvalidConfiguration)));
+    }
+
+    #[test]
+    fn position_controller_starts_at_the_current_position() {
+        let log = Log::new();
+        let clock = MockClock::new();
+        let mut encoder = MockEncoder::new(&log);
+        let mut motor = encoder_driver(&log, &encoder, &clock, 100);
+        encoder.turn(-30, || motor.read_encoder().unwrap());
+
+        let mut controller = PositionController::new(motor, Pid::new(1.0, 0.0, 0.0)).unwrap();
+        assert_eq!(controller.target(), -30);
+        assert_eq!(controller.position_error(), 0);
+        assert_eq!(controller.update().unwrap(), 0);
+    }
+
+    #[test]
+    fn position_controller_targets() {
+        let log = Log::new();
+        let clock = MockClock::new();
+        let motor = encoder_driver(&log, &MockEncoder::new(&log), &clock, 400);
+        let mut controller = PositionController::new(motor, Pid::new(1.0, 0.0, 0.0)).unwrap();
+
+        controller.move_to(250);
+        assert_eq!(controller.target(), 250);
+        assert_eq!(controller.position_error(), 250);
+
+        // Relative moves build on the target, not on the position reached so far
+        controller.move_by(100);
+        controller.move_by(-30);
+        assert_eq!(controller.target(), 320);
+
+        // Targets are multi-turn and can be negative
+        controller.move_to(-5 * 400 - 10);
+        assert_eq!(controller.position_error(), -2010);
+
+        controller.move_to_revolutions(2.5).unwrap();
+        assert_eq!(controller.target(), 1000);
+        controller.move_to_revolutions(-0.75).unwrap();
+        assert_eq!(controller.target(), -300);
+    }
+
+    #[test]
+    fn move_to_revolutions_requires_ppr() {
+        let log = Log::new();
+        let clock = MockClock::new();
+        let motor = encoder_driver(&log, &MockEncoder::new(&log), &clock, 0);
+        let mut controller = PositionController::new(motor, Pid::new(1.0, 0.0, 0.0)).unwrap();
+
+        assert_eq!(controller.move_to_revolutions(1.0), Err(MotorDriverError::InvalidConfiguration));
+        assert_eq!(controller.target(), 0);
+    }
+
+    #[test]
+    fn position_controller_is_at_target_within_tolerance() {
+        let log = Log::new();
+        let clock = MockClock::new();
+        let mut encoder = MockEncoder::new(&log);
+        let motor = encoder_driver(&log, &encoder, &clock, 100);
+        let mut controller = PositionController::new(motor, Pid::new(1.0, 0.0, 0.0)).unwrap();
+
+        controller.move_to(10);
+        encoder.turn(7, || controller.driver_mut().read_encoder().unwrap());
+        assert!(!controller.is_at_target(2));
+        assert!(controller.is_at_target(3));
+
+        encoder.turn(6, || controller.driver_mut().read_encoder().unwrap());
+        assert!(!controller.is_at_target(2));
+        assert!(controller.is_at_target(3));
+        assert!(controller.update().unwrap() < 0);
+    }
+
+    #[cfg(feature = "sim")]
+    #[test]
+    fn position_controller_settles_on_a_simulated_motor() {
+        use crate::sim::{DcMotorParams, SimMotor};
+
+        let sim = SimMotor::new(DcMotorParams::default());
+        let clock = MockClock::new();
+        let mut motor = HBridgeMotorDriver::<_, NoPin, _, _, _, _>::builder_with_encoder()
+            .with_enable(sim.enable_pin())
+            .with_dual_pwm(sim.pwm_forward(1000), sim.pwm_reverse(1000))
+            .with_encoder(sim.encoder_a(), sim.encoder_b())
+            .with_ppr(1000)
+            .with_clock(&clock)
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        let mut controller = PositionController::new(motor, Pid::new(4.0, 0.0, 0.2))
+            .unwrap()
+            .with_loop_rate(1000.0)
+            .unwrap();
+
+        controller.move_to_revolutions(1.5).unwrap();
+        for _ in 0..2000 {
+            for _ in 0..10 {
+                sim.step(1e-4);
+                controller.driver_mut().read_encoder().unwrap();
+            }
+            clock.advance(1_000);
+            controller.update().unwrap();
+        }
+
+        assert!(controller.is_at_target(5), "error {}", controller.position_error());
+        assert!(sim.velocity().abs() < 0.1);
+        assert_eq!(controller.driver().get_pulse_count() as i64, sim.encoder_count());
+    }
+
+    #[test]
+    fn loop_timer_measures_step_time() {
+        let mut timer = LoopTimer::new(0.01);
+        assert_eq!(timer.step(1_000), 0.01);
+        assert!((timer.step(3_500) - 0.0025).abs() < 1e-7);
+        assert!((timer.step(23_500) - 0.02).abs() < 1e-7);
+
+        // A clock that does not advance falls back to the nominal period
+        assert_eq!(timer.step(23_500), 0.01);
+
+        timer.reset();
+        assert_eq!(timer.step(90_000), 0.01);
+    }
+
+    #[test]
+    fn loop_rate_must_be_positive_and_finite() {
+        let timer = LoopTimer::new(0.01);
+        for rate in [0.0, -10.0, f32::NAN, f32::INFINITY] {
+            assert_eq!(timer.with_rate(rate).err(), Some(MotorDriverError::InvalidConfiguration));
+        }
+        assert_eq!(timer.with_rate(200.0).unwrap().period, 0.005);
+    }
+}
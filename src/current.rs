@@ -0,0 +1,440 @@
+//! Motor current and supply voltage sensing.
+
+use crate::{FaultStatus, MotorDriverError};
+
+/// Source of current and voltage readings for a motor driver.
+///
+/// Drivers sample it from `set_speed()`, `update()` and `check_fault()`, latch
+/// the faults it reports and serve `get_current()` and `get_voltage()` from
+/// its last readings. Implemented by [`CurrentSense`] and, with the `ina2xx`
+/// feature, by the INA219/INA226 power monitors.
+pub trait PowerSense {
+    /// Prepares the sensor, called from the driver's `initialize()`.
+    fn init(&mut self) -> Result<(), MotorDriverError> {
+        Ok(())
+    }
+
+    /// Takes a new reading and returns the faults it detected.
+    fn sample(&mut self) -> Result<FaultStatus, MotorDriverError>;
+
+    /// Returns the motor current in amps from the last reading, `None` if not measured.
+    fn current(&self) -> Option<f32>;
+
+    /// Returns the supply voltage in volts from the last reading, `None` if not measured.
+    fn voltage(&self) -> Option<f32>;
+
+    /// Discards filter state so the next reading starts fresh.
+    fn reset(&mut self) {}
+}
+
+/// Placeholder for drivers without a current or voltage sensor.
+///
+/// It measures nothing and never reports a fault.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoSense;
+
+impl PowerSense for NoSense {
+    fn sample(&mut self) -> Result<FaultStatus, MotorDriverError> {
+        Ok(FaultStatus::empty())
+    }
+
+    fn current(&self) -> Option<f32> {
+        None
+    }
+
+    fn voltage(&self) -> Option<f32> {
+        None
+    }
+}
+
+/// Single-ended analog input returning a voltage.
+///
+/// embedded-hal 1.0 has no ADC trait, so implement this for the ADC channel
+/// that samples the shunt amplifier or sense pin.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// struct SenseChannel(Ads1115);
+///
+/// impl AdcChannel for SenseChannel {
+///     type Error = ads1x1x::Error;
+///
+///     fn read_volts(&mut self) -> Result<f32, Self::Error> {
+///         Ok(self.0.read(ChannelSelection::SingleA0)? as f32 * 0.000_125)
+///     }
+/// }
+/// ```
+pub trait AdcChannel {
+    /// Error returned when a conversion fails.
+    ///
+    /// Its `Debug` output is kept as the cause of the resulting `HalError`.
+    type Error: core::fmt::Debug;
+
+    /// Performs a conversion and returns the input voltage in volts.
+    fn read_volts(&mut self) -> Result<f32, Self::Error>;
+}
+
+/// Conversion from sensed voltage to motor current.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CurrentScaling {
+    /// Low-side or high-side shunt resistor followed by an amplifier.
+    Shunt {
+        /// Shunt resistance in ohms
+        ohms: f32,
+        /// Voltage gain of the sense amplifier, 1.0 without one
+        gain: f32,
+    },
+    /// Current-proportional sense output such as the IPROPI or CS pin of a driver chip.
+    SensePin {
+        /// Sense output in millivolts per amp
+        mv_per_amp: f32,
+    },
+}
+
+impl CurrentScaling {
+    /// Returns the sense voltage per amp of motor current.
+    pub fn volts_per_amp(&self) -> f32 {
+        match *self {
+            CurrentScaling::Shunt { ohms, gain } => ohms * gain,
+            CurrentScaling::SensePin { mv_per_amp } => mv_per_amp / 1000.0,
+        }
+    }
+}
+
+/// Current-sense input with optional filtering and overcurrent limit.
+///
+/// Each sample subtracts the zero-current offset, converts the voltage with
+/// the configured scaling and feeds an exponential moving average.
+///
+/// # Example
+///
+/// ```rust
+/// use motor_driver_hal::current::{AdcChannel, CurrentScaling, CurrentSense};
+///
+/// struct Fixed(f32);
+///
+/// impl AdcChannel for Fixed {
+///     type Error = ();
+///
+///     fn read_volts(&mut self) -> Result<f32, ()> {
+///         Ok(self.0)
+///     }
+/// }
+///
+/// // 10 mOhm shunt with a x20 amplifier: 0.2 V per amp
+/// let scaling = CurrentScaling::Shunt { ohms: 0.01, gain: 20.0 };
+/// let mut sense = CurrentSense::new(Fixed(0.3), scaling).unwrap().with_limit(1.0).unwrap();
+/// assert!((sense.sample().unwrap() - 1.5).abs() < 1e-6);
+/// assert!(sense.is_over_limit());
+/// ```
+#[derive(Debug)]
+pub struct CurrentSense<A> {
+    adc: A,
+    volts_per_amp: f32,
+    offset: f32,
+    alpha: f32,
+    limit: Option<f32>,
+    current: Option<f32>,
+}
+
+impl<A: AdcChannel> CurrentSense<A> {
+    /// Creates an unfiltered current sense without offset or limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `adc` - Analog input connected to the sense amplifier or sense pin
+    /// * `scaling` - Conversion from sensed voltage to amps
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if the scaling is not positive.
+    pub fn new(adc: A, scaling: CurrentScaling) -> Result<Self, MotorDriverError> {
+        let volts_per_amp = scaling.volts_per_amp();
+        if !(volts_per_amp > 0.0 && volts_per_amp.is_finite()) {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+
+        Ok(Self {
+            adc,
+            volts_per_amp,
+            offset: 0.0,
+            alpha: 1.0,
+            limit: None,
+            current: None,
+        })
+    }
+
+    /// Sets the sensed voltage at zero current, e.g. VCC/2 for bidirectional amplifiers.
+    pub fn with_offset(mut self, volts: f32) -> Self {
+        self.offset = volts;
+        self
+    }
+
+    /// Enables an exponential moving average over the samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `alpha` - Weight of each new sample in (0, 1], 1.0 disables filtering
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if `alpha` is outside (0, 1].
+    pub fn with_filter(mut self, alpha: f32) -> Result<Self, MotorDriverError> {
+        if !(alpha > 0.0 && alpha <= 1.0) {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+        self.alpha = alpha;
+        Ok(self)
+    }
+
+    /// Sets the current magnitude above which the driver latches an overcurrent fault.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if the limit is not positive.
+    pub fn with_limit(mut self, amps: f32) -> Result<Self, MotorDriverError> {
+        if !(amps > 0.0 && amps.is_finite()) {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+        self.limit = Some(amps);
+        Ok(self)
+    }
+
+    /// Reads the ADC and returns the filtered current in amps.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CommunicationError` on `Signal::Sensor` with `Operation::Read`
+    /// if the conversion fails.
+    pub fn sample(&mut self) -> Result<f32, MotorDriverError> {
+        let volts = self.adc.read_volts().map_err(MotorDriverError::adc())?;
+        let amps = (volts - self.offset) / self.volts_per_amp;
+        let current = match self.current {
+            Some(previous) => previous + self.alpha * (amps - previous),
+            None => amps,
+        };
+        self.current = Some(current);
+        Ok(current)
+    }
+
+    /// Returns the filtered current from the last sample, 0.0 before the first one.
+    pub fn current(&self) -> f32 {
+        self.current.unwrap_or(0.0)
+    }
+
+    /// Returns the configured overcurrent limit in amps.
+    pub fn limit(&self) -> Option<f32> {
+        self.limit
+    }
+
+    /// Returns `true` if the last filtered current exceeds the limit.
+    pub fn is_over_limit(&self) -> bool {
+        match self.limit {
+            Some(limit) => self.current().abs() > limit,
+            None => false,
+        }
+    }
+
+    /// Discards the filter state so the next sample starts fresh.
+    pub fn reset(&mut self) {
+        self.current = None;
+    }
+
+    /// Releases the ADC channel.
+    pub fn release(self) -> A {
+        self.adc
+    }
+}
+
+impl<A: AdcChannel> PowerSense for CurrentSense<A> {
+    fn sample(&mut self) -> Result<FaultStatus, MotorDriverError> {
+        CurrentSense::sample(self)?;
+        if self.is_over_limit() {
+            Ok(FaultStatus::OVERCURRENT)
+        } else {
+            Ok(FaultStatus::empty())
+        }
+    }
+
+    fn current(&self) -> Option<f32> {
+        self.current
+    }
+
+    fn voltage(&self) -> Option<f32> {
+        None
+    }
+
+    fn reset(&mut self) {
+        CurrentSense::reset(self);
+    }
+}
+
+/// Continuous and peak current limits evaluated by a driver's `update()`.
+///
+/// Above the continuous limit the duty scale drops to `continuous / current`,
+/// and is never raised while the current stays over the limit, so repeated
+/// readings do not compound the fold-back. It recovers towards full duty at
+/// the recovery rate once the current is back under the limit. Above the peak limit the driver trips:
+/// it stops, disables the bridge and latches `FaultStatus::OVERCURRENT`.
+///
+/// # Example
+///
+/// ```rust
+/// use motor_driver_hal::current::CurrentLimit;
+/// use motor_driver_hal::MotorDriverError;
+///
+/// let mut limit = CurrentLimit::new(2.0, 5.0).unwrap();
+/// assert_eq!(limit.update(1.0, 0), Ok(1.0));
+/// assert_eq!(limit.update(4.0, 10_000), Ok(0.5));
+/// assert_eq!(limit.update(1.0, 260_000), Ok(0.75));
+/// assert_eq!(limit.update(6.0, 270_000), Err(MotorDriverError::OverCurrent));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurrentLimit {
+    continuous: f32,
+    peak: f32,
+    recovery: f32,
+    scale: f32,
+    last_update: Option<u64>,
+}
+
+impl CurrentLimit {
+    /// Creates limits that recover from fold-back to full duty within one second.
+    ///
+    /// # Arguments
+    ///
+    /// * `continuous` - Current in amps above which the duty is folded back
+    /// * `peak` - Current in amps above which the driver trips
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` unless `0 < continuous <= peak`.
+    pub fn new(continuous: f32, peak: f32) -> Result<Self, MotorDriverError> {
+        if !(continuous > 0.0 && continuous <= peak && peak.is_finite()) {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+
+        Ok(Self {
+            continuous,
+            peak,
+            recovery: 1.0,
+            scale: 1.0,
+            last_update: None,
+        })
+    }
+
+    /// Sets how fast the duty scale returns to 1.0 once the current is under the continuous limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `per_sec` - Increase of the duty scale per second
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if the rate is not positive.
+    pub fn with_recovery(mut self, per_sec: f32) -> Result<Self, MotorDriverError> {
+        if !(per_sec > 0.0 && per_sec.is_finite()) {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+        self.recovery = per_sec;
+        Ok(self)
+    }
+
+    /// Returns the continuous limit in amps.
+    pub fn continuous(&self) -> f32 {
+        self.continuous
+    }
+
+    /// Returns the peak limit in amps.
+    pub fn peak(&self) -> f32 {
+        self.peak
+    }
+
+    /// Returns the factor currently applied to the commanded duty, in (0, 1].
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Evaluates a current reading taken at `now_us`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(scale)` - Factor to apply to the commanded duty
+    /// * `Err(MotorDriverError::OverCurrent)` if the reading exceeds the peak limit
+    pub fn update(&mut self, current: f32, now_us: u64) -> Result<f32, MotorDriverError> {
+        let elapsed = match self.last_update {
+            Some(last) => now_us.saturating_sub(last) as f32 / 1e6,
+            None => 0.0,
+        };
+        self.last_update = Some(now_us);
+
+        let current = current.abs();
+        if current > self.peak {
+            return Err(MotorDriverError::OverCurrent);
+        }
+
+        if current > self.continuous {
+            self.scale = self.scale.min(self.continuous / current);
+        } else {
+            self.scale = (self.scale + self.recovery * elapsed).min(1.0);
+        }
+        Ok(self.scale)
+    }
+
+    /// Restores full duty and forgets the last update time.
+    pub fn reset(&mut self) {
+        self.scale = 1.0;
+        self.last_update = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HalErrorKind, Operation, Signal};
+
+    struct FailingAdc;
+
+    impl AdcChannel for FailingAdc {
+        type Error = &'static str;
+
+        fn read_volts(&mut self) -> Result<f32, Self::Error> {
+            Err("conversion timed out")
+        }
+    }
+
+    #[test]
+    fn adc_failures_name_the_sensor_read() {
+        let scaling = CurrentScaling::Shunt { ohms: 0.01, gain: 20.0 };
+        let mut sense = CurrentSense::new(FailingAdc, scaling).unwrap();
+        let Err(MotorDriverError::CommunicationError(error)) = sense.sample() else {
+            panic!("expected a communication error");
+        };
+        assert_eq!((error.signal(), error.operation(), error.kind()), (Signal::Sensor, Operation::Read, HalErrorKind::Adc));
+    }
+
+    #[test]
+    fn fold_back_does_not_compound_over_repeated_readings() {
+        let mut limit = CurrentLimit::new(2.0, 5.0).unwrap();
+        for step in 0..100 {
+            assert_eq!(limit.update(2.5, step * 1_000), Ok(0.8));
+        }
+
+        // A larger overshoot folds back further, a smaller one keeps the lower scale
+        assert_eq!(limit.update(4.0, 100_000), Ok(0.5));
+        assert_eq!(limit.update(2.5, 101_000), Ok(0.5));
+    }
+
+    #[test]
+    fn recovers_at_configured_rate_and_trips_above_peak() {
+        let mut limit = CurrentLimit::new(2.0, 5.0).unwrap().with_recovery(2.0).unwrap();
+        assert_eq!(limit.update(4.0, 0), Ok(0.5));
+        assert_eq!(limit.update(1.0, 100_000), Ok(0.7));
+        assert_eq!(limit.update(1.0, 1_000_000), Ok(1.0));
+        assert_eq!(limit.update(-5.5, 1_100_000), Err(MotorDriverError::OverCurrent));
+
+        limit.reset();
+        assert_eq!(limit.scale(), 1.0);
+    }
+}
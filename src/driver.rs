@@ -1,6 +1,20 @@
-use crate::{MotorDriver, MotorDriverError};
+use crate::clock::{Clock, DefaultClock};
+use crate::current::{CurrentLimit, NoSense, PowerSense};
+use crate::ramp::RampLimiter;
+use crate::stall::StallDetector;
+use crate::state::DriverState;
+use crate::typestate::TypedBuilder;
+use crate::watchdog::{Watchdog, WatchdogAction};
+use crate::velocity::{Velocity, VelocityEstimation, VelocityEstimator};
+use crate::{ConfigError, FaultStatus, HalCause, MotorDriver, MotorDriverError, Operation, Signal};
 use embedded_hal::digital::{OutputPin, InputPin};
 use embedded_hal::pwm::SetDutyCycle;
+#[cfg(feature = "std")]
+use crate::encoder::QuadratureCounter;
+#[cfg(feature = "std")]
+use crate::estop::{EStop, EStopTarget};
+#[cfg(feature = "std")]
+use std::sync::Arc;
 
 /// Placeholder encoder implementation for motors without encoder feedback.
 /// 
@@ -26,6 +40,15 @@ pub struct NoEncoder;
 #[derive(Debug)]
 pub struct NoEncoderError;
 
+impl core::fmt::Display for NoEncoderError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "No encoder")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NoEncoderError {}
+
 impl embedded_hal::digital::Error for NoEncoderError {
     fn kind(&self) -> embedded_hal::digital::ErrorKind {
         embedded_hal::digital::ErrorKind::Other
@@ -46,6 +69,95 @@ impl InputPin for NoEncoder {
     }
 }
 
+/// Placeholder for optional output pins that are not connected.
+///
+/// Writes to a `NoPin` are ignored. It stands in for control lines that are
+/// hard-wired on the board, such as a sleep or standby pin tied high.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// // STBY is tied to VCC, so no standby pin is given
+/// let bridge = Tb6612fng::new(ain1, ain2, pwma);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoPin;
+
+impl embedded_hal::digital::ErrorType for NoPin {
+    type Error = core::convert::Infallible;
+}
+
+impl OutputPin for NoPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Current decay mode used during the PWM off-time of dual-PWM bridges.
+/// 
+/// In fast decay the active input carries the duty cycle and the other input
+/// is held low, so the bridge floats during the off-time and the winding
+/// current decays quickly through the body diodes. In slow decay the
+/// active input is held high and the opposite input gets the inverted duty,
+/// so the winding is shorted during the off-time. Slow decay gives a more
+/// linear speed-vs-duty curve and more torque at low speeds.
+/// 
+/// With slow decay a speed of zero shorts the motor (brake) instead of
+/// letting it coast; `stop()` still coasts.
+/// 
+/// # Example
+/// 
+/// ```rust,ignore
+/// let motor = HBridgeMotorDriver::builder()
+///     .with_dual_enable(enable1, enable2)
+///     .with_dual_pwm(pwm1, pwm2)
+///     .with_decay_mode(DecayMode::Slow)
+///     .build();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecayMode {
+    /// Active input PWM, opposite input low (coast during off-time).
+    #[default]
+    Fast,
+    /// Active input high, opposite input inverted PWM (brake during off-time).
+    Slow,
+}
+
+/// How a signed speed is mapped onto the bridge inputs.
+/// 
+/// # Example
+/// 
+/// ```rust,ignore
+/// // PH/EN driver: PWM on EN, direction on PH
+/// let motor = HBridgeMotorDriver::builder()
+///     .with_enable(sleep_pin)
+///     .with_pwm(en_pwm)
+///     .with_direction_pin(ph_pin)
+///     .build();
+/// 
+/// // Locked-antiphase: a single PWM where 50% duty means stop
+/// let motor = HBridgeMotorDriver::builder()
+///     .with_enable(enable_pin)
+///     .with_pwm(pwm_channel)
+///     .with_drive_mode(DriveMode::LockedAntiphase)
+///     .build();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DriveMode {
+    /// PWM carries the speed magnitude and direction comes from a direction
+    /// pin (single PWM) or from which input is driven (dual PWM).
+    #[default]
+    SignMagnitude,
+    /// PWM duty encodes both speed and direction: 0% is full reverse, 50% is
+    /// stop and 100% is full forward. With dual PWM the second channel gets
+    /// the complementary duty.
+    LockedAntiphase,
+}
+
 #[derive(Copy, Clone, PartialEq)]
 enum Level {
     Low = 0,
@@ -68,6 +180,7 @@ enum Level {
 /// * `P2` - Secondary PWM channel type implementing `SetDutyCycle` (optional)
 /// * `Enc1` - Encoder A channel type implementing `InputPin` (optional)
 /// * `Enc2` - Encoder B channel type implementing `InputPin` (optional)
+/// * `C` - Time source implementing `Clock` (defaults to `DefaultClock`)
 /// 
 /// # Example
 /// 
@@ -82,7 +195,7 @@ enum Level {
 ///     enable1, enable2, pwm1, pwm2, enc_a, enc_b, 1000
 /// );
 /// ```
-pub struct HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2> {
+pub struct HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D = NoPin, F = NoEncoder, S = NoSense, C = DefaultClock> {
     enable1: E1,
     enable2: Option<E2>,
     pwm1: P1,
@@ -98,10 +211,31 @@ pub struct HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2> {
     last_enc_a: Level,
     last_enc_b: Level,
     direction: bool,
-    initialized: bool,
+    state: DriverState,
+    velocity: VelocityEstimator,
+    ramp: Option<RampLimiter>,
+    decay_mode: DecayMode,
+    direction_pin: Option<D>,
+    direction_pin2: Option<D>,
+    pin_direction: Option<bool>,
+    drive_direction: Option<bool>,
+    dead_time_us: u32,
+    dead_time_start: Option<u64>,
+    drive_mode: DriveMode,
+    fault_pin: Option<F>,
+    fault: FaultStatus,
+    current_sense: Option<S>,
+    current_limit: Option<CurrentLimit>,
+    stall: Option<StallDetector>,
+    watchdog: Option<Watchdog>,
+    clock: C,
+    #[cfg(feature = "std")]
+    edge_counter: Option<Arc<QuadratureCounter>>,
+    #[cfg(feature = "std")]
+    estop: Option<EStop>,
 }
 
-const QEM: [i8; 16] = [
+pub(crate) const QEM: [i8; 16] = [
      0, -1,  1,  0,
      1,  0,  0, -1,
     -1,  0,  0,  1,
@@ -118,6 +252,7 @@ const QEM: [i8; 16] = [
 /// * `E1, E2` - Enable pin types implementing `OutputPin`
 /// * `P1, P2` - PWM channel types implementing `SetDutyCycle`
 /// * `Enc1, Enc2` - Encoder channel types implementing `InputPin`
+/// * `C` - Time source implementing `Clock`
 /// 
 /// # Example
 /// 
@@ -131,7 +266,7 @@ const QEM: [i8; 16] = [
 ///     .with_ppr(1024)
 ///     .build();
 /// ```
-pub struct HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2> {
+pub struct HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2, D = NoPin, F = NoEncoder, S = NoSense, C = DefaultClock> {
     enable1: Option<E1>,
     enable2: Option<E2>,
     pwm1: Option<P1>,
@@ -141,6 +276,23 @@ pub struct HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2> {
     max_duty: Option<u16>,
     ppr: Option<u16>,
     initial_speed: Option<i16>,
+    velocity_estimation: Option<VelocityEstimation>,
+    ramp: Option<RampLimiter>,
+    decay_mode: Option<DecayMode>,
+    direction_pin: Option<D>,
+    direction_pin2: Option<D>,
+    dead_time_us: Option<u32>,
+    drive_mode: Option<DriveMode>,
+    fault_pin: Option<F>,
+    current_sense: Option<S>,
+    current_limit: Option<CurrentLimit>,
+    stall: Option<StallDetector>,
+    watchdog: Option<Watchdog>,
+    clock: C,
+    #[cfg(feature = "std")]
+    edge_counter: Option<Arc<QuadratureCounter>>,
+    #[cfg(feature = "std")]
+    estop: Option<EStop>,
 }
 
 impl<E1, E2, P1, P2, Enc1, Enc2> HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2> {
@@ -166,9 +318,28 @@ impl<E1, E2, P1, P2, Enc1, Enc2> HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1,
             max_duty: None,
             ppr: None,
             initial_speed: None,
+            velocity_estimation: None,
+            ramp: None,
+            decay_mode: None,
+            direction_pin: None,
+            direction_pin2: None,
+            dead_time_us: None,
+            drive_mode: None,
+            fault_pin: None,
+            current_sense: None,
+            current_limit: None,
+            stall: None,
+            watchdog: None,
+            clock: DefaultClock::default(),
+            #[cfg(feature = "std")]
+            edge_counter: None,
+            #[cfg(feature = "std")]
+            estop: None,
         }
     }
+}
 
+impl<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C> HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C> {
     /// Sets the primary enable pin for the motor driver.
     /// 
     /// # Arguments
@@ -278,6 +449,61 @@ impl<E1, E2, P1, P2, Enc1, Enc2> HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1,
         self
     }
 
+    /// Sets an edge-driven pulse counter as the encoder position source.
+    /// 
+    /// When a counter is attached, `read_encoder()` no longer polls the encoder
+    /// pins and `get_pulse_count()` reads the counter directly. The counter is
+    /// expected to be fed from interrupt handlers or event threads.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `counter` - Shared quadrature counter updated on every encoder edge
+    /// 
+    /// # Returns
+    /// 
+    /// The builder instance for method chaining
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// let counter = Arc::new(QuadratureCounter::new());
+    /// let builder = builder.with_edge_counter(counter.clone());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_edge_counter(mut self, counter: Arc<QuadratureCounter>) -> Self {
+        self.edge_counter = Some(counter);
+        self
+    }
+
+    /// Attaches a shared emergency stop.
+    /// 
+    /// While the stop is latched, `set_speed()`, `enable()` and `update()`
+    /// brake and disable the motor and return
+    /// `MotorDriverError::EmergencyStop`. They also poll the stop's hardware
+    /// input, so `update()` alone is enough to catch a pressed switch.
+    /// `EStop::register()` attaches the stop as well and also has `trigger()`
+    /// stop the motor immediately.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `estop` - Handle shared with the other motors and the trigger source
+    /// 
+    /// # Returns
+    /// 
+    /// The builder instance for method chaining
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// let estop = EStop::new();
+    /// let builder = builder.with_estop(estop.clone());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_estop(mut self, estop: EStop) -> Self {
+        self.estop = Some(estop);
+        self
+    }
+
     /// Sets the maximum duty cycle value for PWM control.
     /// 
     /// This value determines the resolution and maximum speed of the motor.
@@ -347,265 +573,1123 @@ impl<E1, E2, P1, P2, Enc1, Enc2> HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1,
         self
     }
 
-    /// Builds the motor driver instance from the configured parameters.
+    /// Sets the method used by `get_velocity()` to estimate motor speed.
+    /// 
+    /// Defaults to a 10ms windowed difference. Edge-period estimation gives
+    /// better resolution at very low speeds.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `estimation` - Velocity estimation method
     /// 
     /// # Returns
     /// 
-    /// A configured `HBridgeMotorDriver` instance ready for initialization
+    /// The builder instance for method chaining
     /// 
-    /// # Panics
+    /// # Example
     /// 
-    /// Panics if required parameters (enable pin and PWM channel) are not set
+    /// ```rust,ignore
+    /// let builder = builder.with_velocity_estimation(VelocityEstimation::EdgePeriod { timeout_us: 200_000 });
+    /// ```
+    pub fn with_velocity_estimation(mut self, estimation: VelocityEstimation) -> Self {
+        self.velocity_estimation = Some(estimation);
+        self
+    }
+
+    /// Limits how fast the commanded duty cycle may change.
+    /// 
+    /// With a ramp configured, `set_speed()` only changes the target and the
+    /// output approaches it over successive `update()` calls, accelerating and
+    /// decelerating at the limiter's rates. `stop()` and `brake()` still act
+    /// immediately.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `ramp` - Ramp limiter with acceleration and deceleration rates in duty units per second
+    /// 
+    /// # Returns
+    /// 
+    /// The builder instance for method chaining
     /// 
     /// # Example
     /// 
-    /// ```rust
+    /// ```rust,ignore
     /// let motor = HBridgeMotorDriver::builder()
     ///     .with_enable(enable_pin)
     ///     .with_pwm(pwm_channel)
+    ///     .with_ramp(RampLimiter::new(2000.0, 4000.0)?)
     ///     .build();
     /// ```
-    pub fn build(self) -> HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2> {
-        HBridgeMotorDriver {
-            enable1: self.enable1.expect("Enable pin is required"),
-            enable2: self.enable2,
-            pwm1: self.pwm1.expect("PWM channel is required"),
-            pwm2: self.pwm2,
-            encoder1: self.encoder1,
-            encoder2: self.encoder2,
-            max_duty: self.max_duty.unwrap_or(1000),
-            current_speed: self.initial_speed.unwrap_or(0),
-            pulse_count: 0,
-            pulse_offset: 0,
-            target_pulse: 0,
-            ppr: self.ppr.unwrap_or(0),
-            last_enc_a: Level::Low,
-            last_enc_b: Level::Low,
-            direction: true,
-            initialized: false,
-        }
+    pub fn with_ramp(mut self, ramp: RampLimiter) -> Self {
+        self.ramp = Some(ramp);
+        self
     }
 
-    /// Builds and initializes the motor driver in one step.
+    /// Sets continuous and peak current limits enforced by `update()`.
     /// 
-    /// This convenience method combines `build()` and `initialize()` operations,
-    /// returning a ready-to-use motor driver instance.
+    /// Requires a sensor attached with `with_current_sense()`. Above the
+    /// continuous limit the applied duty is folded back; above the peak limit
+    /// the driver stops, disables the bridge and latches
+    /// `FaultStatus::OVERCURRENT` until `clear_fault()` is called.
     /// 
-    /// # Returns
+    /// # Arguments
     /// 
-    /// * `Ok(driver)` - Initialized motor driver ready for use
-    /// * `Err(MotorDriverError)` - If building fails or initialization fails
+    /// * `limit` - Continuous and peak limits in amps
     /// 
-    /// # Errors
+    /// # Returns
     /// 
-    /// Returns error if required parameters are missing or hardware initialization fails.
+    /// The builder instance for method chaining
     /// 
     /// # Example
     /// 
-    /// ```rust
+    /// ```rust,ignore
     /// let motor = HBridgeMotorDriver::builder()
     ///     .with_enable(enable_pin)
     ///     .with_pwm(pwm_channel)
-    ///     .build_and_init()?;
+    ///     .with_current_sense(sense)
+    ///     .with_current_limit(CurrentLimit::new(1.5, 4.0)?)
+    ///     .build();
     /// ```
-    pub fn build_and_init(self) -> Result<HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2>, MotorDriverError>
-    where
-        E1: OutputPin,
-        E2: OutputPin,
-        P1: SetDutyCycle,
-        P2: SetDutyCycle,
-        Enc1: InputPin,
-        Enc2: InputPin,
-    {
-        let mut driver = self.build();
-        driver.initialize()?;
-        Ok(driver)
+    pub fn with_current_limit(mut self, limit: CurrentLimit) -> Self {
+        self.current_limit = Some(limit);
+        self
     }
-}
 
-impl<E1, E2, P1, P2> HBridgeMotorDriver<E1, E2, P1, P2, NoEncoder, NoEncoder>
-where
-    E1: OutputPin,
-    E2: OutputPin,
-    P1: SetDutyCycle,
-    P2: SetDutyCycle,
-{   
-    /// Creates a new builder for motor drivers without encoder support.
+    /// Enables a command watchdog evaluated by `update()`.
+    /// 
+    /// `set_speed()`, `enable()` and `feed()` restart the timer. If none of
+    /// them is called within the timeout, `update()` applies the watchdog
+    /// action once and returns `MotorDriverError::WatchdogTimeout` until the
+    /// watchdog is fed again. Either action disables the bridge, so call
+    /// `enable()` before driving again. The timeout is only checked inside
+    /// `update()`, so call it at least once per timeout. With `NoClock` the
+    /// watchdog never expires.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `watchdog` - Timeout and action on expiry
     /// 
     /// # Returns
     /// 
-    /// A new builder instance configured for NoEncoder types
+    /// The builder instance for method chaining
     /// 
     /// # Example
     /// 
-    /// ```rust
+    /// ```rust,ignore
     /// let motor = HBridgeMotorDriver::builder()
     ///     .with_enable(enable_pin)
     ///     .with_pwm(pwm_channel)
+    ///     .with_watchdog(Watchdog::new(200)?)
     ///     .build();
     /// ```
-    pub fn builder() -> HBridgeMotorDriverBuilder<E1, E2, P1, P2, NoEncoder, NoEncoder> {
-        HBridgeMotorDriverBuilder::new()
+    pub fn with_watchdog(mut self, watchdog: Watchdog) -> Self {
+        self.watchdog = Some(watchdog);
+        self
     }
 
-    /// Creates a motor driver with single PWM channel configuration.
+    /// Enables stall detection evaluated by `update()`.
     /// 
-    /// This is a convenience constructor for the most common motor driver
-    /// configuration using one enable pin and one PWM channel.
+    /// Needs encoder pins or an edge counter, which `try_build()` checks, and
+    /// `read_encoder()` must keep being polled so the pulse count stays current.
+    /// The detector only runs while the driver is `Running`. When the commanded duty stays
+    /// above the detector's threshold while `get_pulse_count()` does not
+    /// change for its timeout, `update()` latches `FaultStatus::STALL` and
+    /// returns `MotorDriverError::Stalled` until `clear_fault()` is called.
+    /// The motor is stopped on a stall, and a detector created
+    /// `with_cut_power(true)` also disables the bridge.
     /// 
     /// # Arguments
     /// 
-    /// * `enable` - GPIO pin for enabling/disabling the motor driver
-    /// * `pwm` - PWM channel for speed control
-    /// * `max_duty` - Maximum duty cycle value for speed scaling
+    /// * `stall` - Duty threshold, timeout and power-cut behaviour
     /// 
     /// # Returns
     /// 
-    /// A configured motor driver instance (not yet initialized)
+    /// The builder instance for method chaining
     /// 
     /// # Example
     /// 
-    /// ```rust
-    /// let motor = HBridgeMotorDriver::single_pwm(enable_pin, pwm_channel, 1000);
+    /// ```rust,ignore
+    /// let motor = HBridgeMotorDriver::builder_with_encoder()
+    ///     .with_enable(enable_pin)
+    ///     .with_pwm(pwm_channel)
+    ///     .with_encoder(enc_a, enc_b)
+    ///     .with_stall_detection(StallDetector::new(200, 250)?.with_cut_power(true))
+    ///     .build();
     /// ```
-    pub fn single_pwm(enable: E1, pwm: P1, max_duty: u16) -> Self {
-        Self::builder()
-            .with_enable(enable)
-            .with_pwm(pwm)
-            .with_max_duty(max_duty)
-            .build()
+    pub fn with_stall_detection(mut self, stall: StallDetector) -> Self {
+        self.stall = Some(stall);
+        self
     }
 
-    /// Creates a motor driver with dual PWM and dual enable configuration.
+    /// Sets the current decay mode for dual-PWM configurations.
     /// 
-    /// This configuration provides the most control options with separate
-    /// PWM channels for each direction and separate enable pins.
+    /// Defaults to `DecayMode::Fast`. Single-PWM configurations ignore it.
     /// 
     /// # Arguments
     /// 
-    /// * `enable1` - Primary enable pin
-    /// * `enable2` - Secondary enable pin
-    /// * `pwm1` - Primary PWM channel (forward direction)
-    /// * `pwm2` - Secondary PWM channel (reverse direction)
-    /// * `max_duty` - Maximum duty cycle value for both PWM channels
+    /// * `mode` - Fast or slow decay during the PWM off-time
     /// 
     /// # Returns
     /// 
-    /// A configured motor driver instance (not yet initialized)
+    /// The builder instance for method chaining
     /// 
     /// # Example
     /// 
-    /// ```rust
-    /// let motor = HBridgeMotorDriver::dual_pwm(
-    ///     enable1, enable2, pwm1, pwm2, 1000
-    /// );
+    /// ```rust,ignore
+    /// let builder = builder.with_decay_mode(DecayMode::Slow);
     /// ```
-    pub fn dual_pwm(enable1: E1, enable2: E2, pwm1: P1, pwm2: P2, max_duty: u16) -> Self {
-        Self::builder()
-            .with_dual_enable(enable1, enable2)
-            .with_dual_pwm(pwm1, pwm2)
-            .with_max_duty(max_duty)
-            .build()
+    pub fn with_decay_mode(mut self, mode: DecayMode) -> Self {
+        self.decay_mode = Some(mode);
+        self
     }
-}
 
-impl<E1, E2, P1, P2, Enc1, Enc2> HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2>
-where
-    E1: OutputPin,
-    E2: OutputPin,
-    P1: SetDutyCycle,
-    P2: SetDutyCycle,
-    Enc1: InputPin,
-    Enc2: InputPin,
-{
-    /// Creates a new builder for motor drivers with encoder support.
+    /// Sets how the signed speed is mapped onto the PWM channels.
+    /// 
+    /// Defaults to `DriveMode::SignMagnitude`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `mode` - Sign-magnitude or locked-antiphase drive
     /// 
     /// # Returns
     /// 
-    /// A new builder instance configured for encoder types Enc1 and Enc2
+    /// The builder instance for method chaining
     /// 
     /// # Example
     /// 
-    /// ```rust
-    /// let motor = HBridgeMotorDriver::builder_with_encoder()
-    ///     .with_dual_enable(enable1, enable2)
-    ///     .with_dual_pwm(pwm1, pwm2)
-    ///     .with_encoder(enc_a, enc_b)
-    ///     .with_ppr(1024)
+    /// ```rust,ignore
+    /// let builder = builder.with_drive_mode(DriveMode::LockedAntiphase);
+    /// ```
+    pub fn with_drive_mode(mut self, mode: DriveMode) -> Self {
+        self.drive_mode = Some(mode);
+        self
+    }
+
+    /// Sets the direction output for sign-magnitude drive with a single PWM.
+    /// 
+    /// The pin is driven high for forward and low for reverse, as expected by
+    /// PH/EN and DIR/PWM style drivers.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `direction` - GPIO pin implementing `OutputPin` connected to PH or DIR
+    /// 
+    /// # Returns
+    /// 
+    /// The builder instance for method chaining
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// let motor = HBridgeMotorDriver::builder()
+    ///     .with_enable(sleep_pin)
+    ///     .with_pwm(en_pwm)
+    ///     .with_direction_pin(ph_pin)
     ///     .build();
     /// ```
-    pub fn builder_with_encoder() -> HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2> {
-        HBridgeMotorDriverBuilder::new()
+    pub fn with_direction_pin<Dir: OutputPin>(self, direction: Dir) -> HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2, Dir, F, S, C> {
+        HBridgeMotorDriverBuilder {
+            enable1: self.enable1,
+            enable2: self.enable2,
+            pwm1: self.pwm1,
+            pwm2: self.pwm2,
+            encoder1: self.encoder1,
+            encoder2: self.encoder2,
+            max_duty: self.max_duty,
+            ppr: self.ppr,
+            initial_speed: self.initial_speed,
+            velocity_estimation: self.velocity_estimation,
+            ramp: self.ramp,
+            decay_mode: self.decay_mode,
+            direction_pin: Some(direction),
+            direction_pin2: None,
+            dead_time_us: self.dead_time_us,
+            drive_mode: self.drive_mode,
+            fault_pin: self.fault_pin,
+            current_sense: self.current_sense,
+            current_limit: self.current_limit,
+            stall: self.stall,
+            watchdog: self.watchdog,
+            clock: self.clock,
+            #[cfg(feature = "std")]
+            edge_counter: self.edge_counter,
+            #[cfg(feature = "std")]
+            estop: self.estop,
+        }
     }
-    
-    /// Creates a motor driver with dual PWM, dual enable, and encoder support.
+
+    /// Sets a pair of direction inputs for single-PWM bridges such as the
+    /// L298N or TB6612FNG, where the PWM drives the enable input.
     /// 
-    /// This is the most feature-complete configuration providing precise
-    /// motor control with position feedback.
+    /// Forward drives IN1 high and IN2 low, reverse the opposite. `brake()`
+    /// drives both high with full duty on the PWM.
     /// 
     /// # Arguments
     /// 
-    /// * `enable1` - Primary enable pin
-    /// * `enable2` - Secondary enable pin  
-    /// * `pwm1` - Primary PWM channel (forward direction)
-    /// * `pwm2` - Secondary PWM channel (reverse direction)
-    /// * `encoder1` - Encoder A channel pin
-    /// * `encoder2` - Encoder B channel pin
-    /// * `max_duty` - Maximum duty cycle value
+    /// * `in1` - GPIO pin implementing `OutputPin` connected to IN1
+    /// * `in2` - GPIO pin implementing `OutputPin` connected to IN2
     /// 
     /// # Returns
     /// 
-    /// A configured motor driver instance with encoder support
+    /// The builder instance for method chaining
     /// 
     /// # Example
     /// 
-    /// ```rust
-    /// let motor = HBridgeMotorDriver::dual_pwm_with_encoder(
-    ///     enable1, enable2, pwm1, pwm2, enc_a, enc_b, 1000
-    /// );
+    /// ```rust,ignore
+    /// let motor = HBridgeMotorDriver::builder()
+    ///     .with_enable(standby_pin)
+    ///     .with_pwm(ena_pwm)
+    ///     .with_direction_pins(in1_pin, in2_pin)
+    ///     .build();
     /// ```
-    pub fn dual_pwm_with_encoder(enable1: E1, enable2: E2, pwm1: P1, pwm2: P2, encoder1: Enc1, encoder2: Enc2, max_duty: u16) -> Self {
-        Self::builder_with_encoder()
-            .with_dual_enable(enable1, enable2)
-            .with_dual_pwm(pwm1, pwm2)
-            .with_encoder(encoder1, encoder2)
-            .with_max_duty(max_duty)
-            .build()
+    pub fn with_direction_pins<Dir: OutputPin>(self, in1: Dir, in2: Dir) -> HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2, Dir, F, S, C> {
+        let mut builder = self.with_direction_pin(in1);
+        builder.direction_pin2 = Some(in2);
+        builder
     }
 
+    /// Sets a dead time between a direction pin flip and the PWM being re-enabled.
+    /// 
+    /// While the dead time runs the PWM is held at zero duty. It is released
+    /// by `update()` once the clock has advanced far enough, so call it
+    /// periodically when a dead time is configured. A reversal that passes
+    /// through `brake()` still counts as a flip.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `micros` - Dead time in microseconds, 0 disables it
+    /// 
+    /// # Returns
+    /// 
+    /// The builder instance for method chaining
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// let builder = builder.with_direction_pin(dir_pin).with_dead_time(500);
+    /// ```
+    pub fn with_dead_time(mut self, micros: u32) -> Self {
+        self.dead_time_us = Some(micros);
+        self
+    }
+
+    /// Sets an active-low fault input such as the nFAULT pin of DRV88xx or TB67H drivers.
+    /// 
+    /// The pin is sampled by `set_speed()`, `update()` and `check_fault()`. A
+    /// low level latches `FaultStatus::GENERIC` until `clear_fault()` is called.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `fault` - GPIO pin implementing `InputPin` connected to nFAULT
+    /// 
+    /// # Returns
+    /// 
+    /// The builder instance for method chaining
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// let motor = HBridgeMotorDriver::builder()
+    ///     .with_enable(sleep_pin)
+    ///     .with_dual_pwm(in1_pwm, in2_pwm)
+    ///     .with_fault_pin(nfault_pin)
+    ///     .build();
+    /// ```
+    pub fn with_fault_pin<Flt: InputPin>(self, fault: Flt) -> HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2, D, Flt, S, C> {
+        HBridgeMotorDriverBuilder {
+            enable1: self.enable1,
+            enable2: self.enable2,
+            pwm1: self.pwm1,
+            pwm2: self.pwm2,
+            encoder1: self.encoder1,
+            encoder2: self.encoder2,
+            max_duty: self.max_duty,
+            ppr: self.ppr,
+            initial_speed: self.initial_speed,
+            velocity_estimation: self.velocity_estimation,
+            ramp: self.ramp,
+            decay_mode: self.decay_mode,
+            direction_pin: self.direction_pin,
+            direction_pin2: self.direction_pin2,
+            dead_time_us: self.dead_time_us,
+            drive_mode: self.drive_mode,
+            fault_pin: Some(fault),
+            current_sense: self.current_sense,
+            current_limit: self.current_limit,
+            stall: self.stall,
+            watchdog: self.watchdog,
+            clock: self.clock,
+            #[cfg(feature = "std")]
+            edge_counter: self.edge_counter,
+            #[cfg(feature = "std")]
+            estop: self.estop,
+        }
+    }
+
+    /// Sets the sensor reported by `get_current()` and `get_voltage()`.
+    /// 
+    /// The sensor is sampled by `set_speed()`, `update()`, `check_fault()`
+    /// and `sample_current()`, and the faults it reports are latched, e.g.
+    /// `FaultStatus::OVERCURRENT` when a `CurrentSense` exceeds its limit.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `sense` - A `CurrentSense` or any other `PowerSense` such as an INA219/INA226
+    /// 
+    /// # Returns
+    /// 
+    /// The builder instance for method chaining
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// let sense = CurrentSense::new(adc, CurrentScaling::SensePin { mv_per_amp: 500.0 })?
+    ///     .with_filter(0.2)?
+    ///     .with_limit(3.0)?;
+    /// let builder = builder.with_current_sense(sense);
+    /// ```
+    pub fn with_current_sense<S2: PowerSense>(self, sense: S2) -> HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2, D, F, S2, C> {
+        HBridgeMotorDriverBuilder {
+            enable1: self.enable1,
+            enable2: self.enable2,
+            pwm1: self.pwm1,
+            pwm2: self.pwm2,
+            encoder1: self.encoder1,
+            encoder2: self.encoder2,
+            max_duty: self.max_duty,
+            ppr: self.ppr,
+            initial_speed: self.initial_speed,
+            velocity_estimation: self.velocity_estimation,
+            ramp: self.ramp,
+            decay_mode: self.decay_mode,
+            direction_pin: self.direction_pin,
+            direction_pin2: self.direction_pin2,
+            dead_time_us: self.dead_time_us,
+            drive_mode: self.drive_mode,
+            fault_pin: self.fault_pin,
+            current_sense: Some(sense),
+            current_limit: self.current_limit,
+            stall: self.stall,
+            watchdog: self.watchdog,
+            clock: self.clock,
+            #[cfg(feature = "std")]
+            edge_counter: self.edge_counter,
+            #[cfg(feature = "std")]
+            estop: self.estop,
+        }
+    }
+
+    /// Replaces the time source used by time-dependent features.
+    /// 
+    /// Defaults to `StdClock` with the `std` feature and to `NoClock`
+    /// otherwise, so `no_std` targets should supply a hardware timer here.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `clock` - Monotonic time source implementing `Clock`
+    /// 
+    /// # Returns
+    /// 
+    /// The builder instance for method chaining
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// let clock = MockClock::new();
+    /// let motor = HBridgeMotorDriver::builder()
+    ///     .with_enable(enable_pin)
+    ///     .with_pwm(pwm_channel)
+    ///     .with_clock(&clock)
+    ///     .build();
+    /// ```
+    pub fn with_clock<C2: Clock>(self, clock: C2) -> HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C2> {
+        HBridgeMotorDriverBuilder {
+            enable1: self.enable1,
+            enable2: self.enable2,
+            pwm1: self.pwm1,
+            pwm2: self.pwm2,
+            encoder1: self.encoder1,
+            encoder2: self.encoder2,
+            max_duty: self.max_duty,
+            ppr: self.ppr,
+            initial_speed: self.initial_speed,
+            velocity_estimation: self.velocity_estimation,
+            ramp: self.ramp,
+            decay_mode: self.decay_mode,
+            direction_pin: self.direction_pin,
+            direction_pin2: self.direction_pin2,
+            dead_time_us: self.dead_time_us,
+            drive_mode: self.drive_mode,
+            fault_pin: self.fault_pin,
+            current_sense: self.current_sense,
+            current_limit: self.current_limit,
+            stall: self.stall,
+            watchdog: self.watchdog,
+            clock,
+            #[cfg(feature = "std")]
+            edge_counter: self.edge_counter,
+            #[cfg(feature = "std")]
+            estop: self.estop,
+        }
+    }
+
+    /// Builds the motor driver instance from the configured parameters.
+    /// 
+    /// # Returns
+    /// 
+    /// A configured `HBridgeMotorDriver` instance ready for initialization
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if required parameters (enable pin and PWM channel) are not set
+    /// 
+    /// Use `try_build()` to get an error instead, with the configuration
+    /// validated as well.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// let motor = HBridgeMotorDriver::builder()
+    ///     .with_enable(enable_pin)
+    ///     .with_pwm(pwm_channel)
+    ///     .build();
+    /// ```
+    pub fn build(self) -> HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C> {
+        HBridgeMotorDriver {
+            enable1: self.enable1.expect("Enable pin is required"),
+            enable2: self.enable2,
+            pwm1: self.pwm1.expect("PWM channel is required"),
+            pwm2: self.pwm2,
+            encoder1: self.encoder1,
+            encoder2: self.encoder2,
+            max_duty: self.max_duty.unwrap_or(1000),
+            current_speed: self.initial_speed.unwrap_or(0),
+            pulse_count: 0,
+            pulse_offset: 0,
+            target_pulse: 0,
+            ppr: self.ppr.unwrap_or(0),
+            last_enc_a: Level::Low,
+            last_enc_b: Level::Low,
+            direction: true,
+            state: DriverState::Uninitialized,
+            velocity: VelocityEstimator::new(self.velocity_estimation.unwrap_or_default()),
+            ramp: self.ramp.map(|mut ramp| {
+                ramp.reset(self.initial_speed.unwrap_or(0));
+                ramp
+            }),
+            decay_mode: self.decay_mode.unwrap_or_default(),
+            direction_pin: self.direction_pin,
+            direction_pin2: self.direction_pin2,
+            pin_direction: None,
+            drive_direction: None,
+            dead_time_us: self.dead_time_us.unwrap_or(0),
+            dead_time_start: None,
+            drive_mode: self.drive_mode.unwrap_or_default(),
+            fault_pin: self.fault_pin,
+            fault: FaultStatus::empty(),
+            current_sense: self.current_sense,
+            current_limit: self.current_limit,
+            stall: self.stall,
+            watchdog: self.watchdog,
+            clock: self.clock,
+            #[cfg(feature = "std")]
+            edge_counter: self.edge_counter,
+            #[cfg(feature = "std")]
+            estop: self.estop,
+        }
+    }
+
+    /// Builds the motor driver after validating the configuration.
+    /// 
+    /// Unlike `build()`, missing pins and inconsistent settings are reported
+    /// instead of panicking or being silently replaced by defaults.
+    /// 
+    /// # Errors
+    /// 
+    /// * `MissingEnable` / `MissingPwm` - A required pin is not set
+    /// * `MaxDutyExceedsChannel` - `max_duty` (1000 if unset) is above a PWM
+    ///   channel's `max_duty_cycle()`
+    /// * `InitialSpeedOutOfRange` - The initial speed magnitude exceeds `max_duty`
+    /// * `PprWithoutEncoder` - `ppr` is set without encoder pins or edge counter
+    /// * `IncompleteEncoder` - Only one encoder channel is set
+    /// * `StallWithoutEncoder` - Stall detection is set without encoder pins or edge counter
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// let motor = HBridgeMotorDriver::builder()
+    ///     .with_enable(enable_pin)
+    ///     .with_pwm(pwm_channel)
+    ///     .with_max_duty(255)
+    ///     .try_build()?;
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn try_build(self) -> Result<HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>, ConfigError>
+    where
+        P1: SetDutyCycle<Error: HalCause>,
+        P2: SetDutyCycle<Error: HalCause>,
+    {
+        if self.enable1.is_none() {
+            return Err(ConfigError::MissingEnable);
+        }
+
+        let max_duty = self.max_duty.unwrap_or(1000);
+        let pwm1 = self.pwm1.as_ref().ok_or(ConfigError::MissingPwm)?;
+        let channel_max = match self.pwm2.as_ref() {
+            Some(pwm2) => pwm1.max_duty_cycle().min(pwm2.max_duty_cycle()),
+            None => pwm1.max_duty_cycle(),
+        };
+        if max_duty > channel_max {
+            return Err(ConfigError::MaxDutyExceedsChannel { max_duty, channel_max });
+        }
+
+        if let Some(speed) = self.initial_speed {
+            if speed.unsigned_abs() > max_duty {
+                return Err(ConfigError::InitialSpeedOutOfRange { speed, max_duty });
+            }
+        }
+
+        if self.encoder1.is_some() != self.encoder2.is_some() {
+            return Err(ConfigError::IncompleteEncoder);
+        }
+
+        #[cfg(feature = "std")]
+        let has_counter = self.edge_counter.is_some();
+        #[cfg(not(feature = "std"))]
+        let has_counter = false;
+        if self.ppr.is_some() && self.encoder1.is_none() && !has_counter {
+            return Err(ConfigError::PprWithoutEncoder);
+        }
+        if self.stall.is_some() && self.encoder1.is_none() && !has_counter {
+            return Err(ConfigError::StallWithoutEncoder);
+        }
+
+        Ok(self.build())
+    }
+
+    /// Builds and initializes the motor driver in one step.
+    /// 
+    /// This convenience method combines `build()` and `initialize()` operations,
+    /// returning a ready-to-use motor driver instance.
+    /// 
+    /// # Returns
+    /// 
+    /// * `Ok(driver)` - Initialized motor driver ready for use
+    /// * `Err(MotorDriverError)` - If building fails or initialization fails
+    /// 
+    /// # Errors
+    /// 
+    /// Returns error if required parameters are missing or hardware initialization fails.
+    /// 
+    /// Like `build()`, this does not validate the remaining settings. Call
+    /// `try_build()?` and then `initialize()` to have them checked.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// let motor = HBridgeMotorDriver::builder()
+    ///     .with_enable(enable_pin)
+    ///     .with_pwm(pwm_channel)
+    ///     .build_and_init()?;
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn build_and_init(self) -> Result<HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>, MotorDriverError>
+    where
+        E1: OutputPin<Error: HalCause>,
+        E2: OutputPin<Error: HalCause>,
+        P1: SetDutyCycle<Error: HalCause>,
+        P2: SetDutyCycle<Error: HalCause>,
+        Enc1: InputPin<Error: HalCause>,
+        Enc2: InputPin<Error: HalCause>,
+        D: OutputPin<Error: HalCause>,
+        F: InputPin<Error: HalCause>,
+        S: PowerSense,
+        C: Clock,
+    {
+        let mut driver = self.build();
+        driver.initialize()?;
+        Ok(driver)
+    }
+}
+
+impl<E1, E2, P1, P2, Enc1, Enc2> Default for HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E1, E2, P1, P2> HBridgeMotorDriver<E1, E2, P1, P2, NoEncoder, NoEncoder>
+where
+    E1: OutputPin<Error: HalCause>,
+    E2: OutputPin<Error: HalCause>,
+    P1: SetDutyCycle<Error: HalCause>,
+    P2: SetDutyCycle<Error: HalCause>,
+{   
+    /// Creates a new builder for motor drivers without encoder support.
+    /// 
+    /// # Returns
+    /// 
+    /// A new builder instance configured for NoEncoder types
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// let motor = HBridgeMotorDriver::builder()
+    ///     .with_enable(enable_pin)
+    ///     .with_pwm(pwm_channel)
+    ///     .build();
+    /// ```
+    pub fn builder() -> HBridgeMotorDriverBuilder<E1, E2, P1, P2, NoEncoder, NoEncoder> {
+        HBridgeMotorDriverBuilder::new()
+    }
+
+    /// Creates a type-state builder for motor drivers without encoder support.
+    /// 
+    /// Unlike `builder()`, building does not compile until the enable pin
+    /// and PWM channel are supplied, and `build_and_init()` returns an
+    /// `Initialized` driver that must be enabled before it accepts speeds.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// let mut motor = HBridgeMotorDriver::typed_builder()
+    ///     .with_enable(enable_pin)
+    ///     .with_pwm(pwm_channel)
+    ///     .build_and_init()?;
+    /// motor.enable()?.set_speed(300)?;
+    /// ```
+    pub fn typed_builder() -> TypedBuilder<HBridgeMotorDriverBuilder<E1, E2, P1, P2, NoEncoder, NoEncoder>> {
+        TypedBuilder::new(HBridgeMotorDriverBuilder::new())
+    }
+
+    /// Creates a motor driver with single PWM channel configuration.
+    /// 
+    /// This is a convenience constructor for the most common motor driver
+    /// configuration using one enable pin and one PWM channel.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `enable` - GPIO pin for enabling/disabling the motor driver
+    /// * `pwm` - PWM channel for speed control
+    /// * `max_duty` - Maximum duty cycle value for speed scaling
+    /// 
+    /// # Returns
+    /// 
+    /// A configured motor driver instance (not yet initialized)
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// let motor = HBridgeMotorDriver::single_pwm(enable_pin, pwm_channel, 1000);
+    /// ```
+    pub fn single_pwm(enable: E1, pwm: P1, max_duty: u16) -> Self {
+        Self::builder()
+            .with_enable(enable)
+            .with_pwm(pwm)
+            .with_max_duty(max_duty)
+            .build()
+    }
+
+    /// Creates a motor driver with dual PWM and dual enable configuration.
+    /// 
+    /// This configuration provides the most control options with separate
+    /// PWM channels for each direction and separate enable pins.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `enable1` - Primary enable pin
+    /// * `enable2` - Secondary enable pin
+    /// * `pwm1` - Primary PWM channel (forward direction)
+    /// * `pwm2` - Secondary PWM channel (reverse direction)
+    /// * `max_duty` - Maximum duty cycle value for both PWM channels
+    /// 
+    /// # Returns
+    /// 
+    /// A configured motor driver instance (not yet initialized)
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// let motor = HBridgeMotorDriver::dual_pwm(
+    ///     enable1, enable2, pwm1, pwm2, 1000
+    /// );
+    /// ```
+    pub fn dual_pwm(enable1: E1, enable2: E2, pwm1: P1, pwm2: P2, max_duty: u16) -> Self {
+        Self::builder()
+            .with_dual_enable(enable1, enable2)
+            .with_dual_pwm(pwm1, pwm2)
+            .with_max_duty(max_duty)
+            .build()
+    }
+}
+
+impl<E1, E2, P1, P2, Enc1, Enc2> HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2>
+where
+    E1: OutputPin<Error: HalCause>,
+    E2: OutputPin<Error: HalCause>,
+    P1: SetDutyCycle<Error: HalCause>,
+    P2: SetDutyCycle<Error: HalCause>,
+    Enc1: InputPin<Error: HalCause>,
+    Enc2: InputPin<Error: HalCause>,
+{
+    /// Creates a new builder for motor drivers with encoder support.
+    /// 
+    /// # Returns
+    /// 
+    /// A new builder instance configured for encoder types Enc1 and Enc2
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// let motor = HBridgeMotorDriver::builder_with_encoder()
+    ///     .with_dual_enable(enable1, enable2)
+    ///     .with_dual_pwm(pwm1, pwm2)
+    ///     .with_encoder(enc_a, enc_b)
+    ///     .with_ppr(1024)
+    ///     .build();
+    /// ```
+    pub fn builder_with_encoder() -> HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2> {
+        HBridgeMotorDriverBuilder::new()
+    }
+
+    /// Creates a type-state builder for motor drivers with encoder support.
+    /// 
+    /// See `typed_builder()`; the encoder is set with `configure()`.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// let mut motor = HBridgeMotorDriver::typed_builder_with_encoder()
+    ///     .with_dual_enable(enable1, enable2)
+    ///     .with_dual_pwm(pwm1, pwm2)
+    ///     .configure(|builder| builder.with_encoder(enc_a, enc_b).with_ppr(1024))
+    ///     .build_and_init()?;
+    /// ```
+    pub fn typed_builder_with_encoder() -> TypedBuilder<HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2>> {
+        TypedBuilder::new(HBridgeMotorDriverBuilder::new())
+    }
+    
+    /// Creates a motor driver with dual PWM, dual enable, and encoder support.
+    /// 
+    /// This is the most feature-complete configuration providing precise
+    /// motor control with position feedback.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `enable1` - Primary enable pin
+    /// * `enable2` - Secondary enable pin  
+    /// * `pwm1` - Primary PWM channel (forward direction)
+    /// * `pwm2` - Secondary PWM channel (reverse direction)
+    /// * `encoder1` - Encoder A channel pin
+    /// * `encoder2` - Encoder B channel pin
+    /// * `max_duty` - Maximum duty cycle value
+    /// 
+    /// # Returns
+    /// 
+    /// A configured motor driver instance with encoder support
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// let motor = HBridgeMotorDriver::dual_pwm_with_encoder(
+    ///     enable1, enable2, pwm1, pwm2, enc_a, enc_b, 1000
+    /// );
+    /// ```
+    pub fn dual_pwm_with_encoder(enable1: E1, enable2: E2, pwm1: P1, pwm2: P2, encoder1: Enc1, encoder2: Enc2, max_duty: u16) -> Self {
+        Self::builder_with_encoder()
+            .with_dual_enable(enable1, enable2)
+            .with_dual_pwm(pwm1, pwm2)
+            .with_encoder(encoder1, encoder2)
+            .with_max_duty(max_duty)
+            .build()
+    }
+}
+
+impl<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C> HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>
+where
+    E1: OutputPin<Error: HalCause>,
+    E2: OutputPin<Error: HalCause>,
+    P1: SetDutyCycle<Error: HalCause>,
+    P2: SetDutyCycle<Error: HalCause>,
+    Enc1: InputPin<Error: HalCause>,
+    Enc2: InputPin<Error: HalCause>,
+    D: OutputPin<Error: HalCause>,
+    F: InputPin<Error: HalCause>,
+    S: PowerSense,
+    C: Clock,
+{
     fn update_pwm(&mut self) -> Result<(), MotorDriverError> {
         let duty = if self.current_speed < 0 {
             (-self.current_speed as u16).min(self.max_duty)
         } else {
             (self.current_speed as u16).min(self.max_duty)
         };
+        let duty = match self.current_limit {
+            Some(ref limit) => (duty as f32 * limit.scale()) as u16,
+            None => duty,
+        };
+
+        if self.drive_mode == DriveMode::LockedAntiphase {
+            let signed = if self.direction { duty as i32 } else { -(duty as i32) };
+            let forward = ((self.max_duty as i32 + signed) / 2) as u16;
+            self.pwm1.set_duty_cycle(forward).map_err(MotorDriverError::pwm(Signal::Pwm))?;
+            if let Some(ref mut pwm2) = self.pwm2 {
+                pwm2.set_duty_cycle(self.max_duty - forward).map_err(MotorDriverError::pwm(Signal::Pwm2))?;
+            }
+            return Ok(());
+        }
+
+        if self.direction_pin.is_some() && self.pin_direction != Some(self.direction) {
+            // Braking clears `pin_direction`, so flips are judged against the last
+            // driving direction to keep the dead time on Forward -> Brake -> Reverse.
+            let flipped = self.drive_direction.is_some_and(|last| last != self.direction);
+            if self.dead_time_us > 0 && flipped {
+                self.pwm1.set_duty_cycle(0).map_err(MotorDriverError::pwm(Signal::Pwm))?;
+                self.dead_time_start = Some(self.clock.now_micros());
+            }
+            self.write_direction_pins(self.direction, !self.direction)?;
+            self.pin_direction = Some(self.direction);
+            self.drive_direction = Some(self.direction);
+        }
+
+        let duty = match self.dead_time_start {
+            Some(start) if self.clock.now_micros().saturating_sub(start) < self.dead_time_us as u64 => 0,
+            _ => {
+                self.dead_time_start = None;
+                duty
+            }
+        };
+
+        let (active, opposite) = match self.decay_mode {
+            DecayMode::Fast => (duty, 0),
+            DecayMode::Slow => (self.max_duty, self.max_duty - duty),
+        };
 
         match (&mut self.pwm2, self.direction) {
             (Some(pwm2), true) => {
-                self.pwm1.set_duty_cycle(duty).map_err(|_| MotorDriverError::PwmError)?;
-                pwm2.set_duty_cycle(0).map_err(|_| MotorDriverError::PwmError)?;
+                self.pwm1.set_duty_cycle(active).map_err(MotorDriverError::pwm(Signal::Pwm))?;
+                pwm2.set_duty_cycle(opposite).map_err(MotorDriverError::pwm(Signal::Pwm2))?;
             }
             (Some(pwm2), false) => {
-                self.pwm1.set_duty_cycle(0).map_err(|_| MotorDriverError::PwmError)?;
-                pwm2.set_duty_cycle(duty).map_err(|_| MotorDriverError::PwmError)?;
+                self.pwm1.set_duty_cycle(opposite).map_err(MotorDriverError::pwm(Signal::Pwm))?;
+                pwm2.set_duty_cycle(active).map_err(MotorDriverError::pwm(Signal::Pwm2))?;
             }
             (None, _) => {
-                self.pwm1.set_duty_cycle(duty).map_err(|_| MotorDriverError::PwmError)?;
+                self.pwm1.set_duty_cycle(duty).map_err(MotorDriverError::pwm(Signal::Pwm))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_direction_pins(&mut self, in1: bool, in2: bool) -> Result<(), MotorDriverError> {
+        if let Some(ref mut pin) = self.direction_pin {
+            if in1 {
+                pin.set_high().map_err(MotorDriverError::gpio(Signal::Direction, Operation::SetHigh))?;
+            } else {
+                pin.set_low().map_err(MotorDriverError::gpio(Signal::Direction, Operation::SetLow))?;
+            }
+        }
+        if let Some(ref mut pin) = self.direction_pin2 {
+            if in2 {
+                pin.set_high().map_err(MotorDriverError::gpio(Signal::Direction2, Operation::SetHigh))?;
+            } else {
+                pin.set_low().map_err(MotorDriverError::gpio(Signal::Direction2, Operation::SetLow))?;
             }
         }
         Ok(())
     }
 
+    /// Duty that produces no drive on a single-PWM bridge, 50% in locked-antiphase mode.
+    fn neutral_duty(&self) -> u16 {
+        if self.drive_mode == DriveMode::LockedAntiphase && self.pwm2.is_none() {
+            self.max_duty / 2
+        } else {
+            0
+        }
+    }
+
+    fn apply_speed(&mut self, speed: i16) -> Result<(), MotorDriverError> {
+        self.current_speed = speed;
+        self.direction = speed >= 0;
+        self.settle_state();
+        self.update_pwm()
+    }
+
+    fn check_estop(&mut self) -> Result<(), MotorDriverError> {
+        #[cfg(feature = "std")]
+        if self.estop.as_ref().is_some_and(EStop::poll_from_driver) {
+            // Try both so a failing PWM write still drops the enable pins
+            let braked = self.brake();
+            let disabled = self.disable();
+            self.state = DriverState::EStopped;
+            braked.and(disabled)?;
+            return Err(MotorDriverError::EmergencyStop);
+        }
+        Ok(())
+    }
+
+    /// Enters `Faulted` if sampling the fault inputs latched a fault.
+    fn check_faulted(&mut self) -> Result<(), MotorDriverError> {
+        if let Some(error) = self.check_fault()?.error() {
+            self.state = DriverState::Faulted;
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    /// Moves between `Enabled` and `Running` to match the commanded speed.
+    fn settle_state(&mut self) {
+        if self.state.can_drive() {
+            self.state = if self.current_speed != 0 || self.target_speed() != 0 {
+                DriverState::Running
+            } else {
+                DriverState::Enabled
+            };
+        }
+    }
+
+    fn check_watchdog(&mut self) -> Result<(), MotorDriverError> {
+        let now = self.clock.now_micros();
+        let Some(ref mut watchdog) = self.watchdog else {
+            return Ok(());
+        };
+        let action = watchdog.action();
+        if watchdog.update(now) {
+            let halted = match action {
+                WatchdogAction::Stop => self.stop(),
+                WatchdogAction::Brake => self.brake(),
+            };
+            // Try both so a failing PWM write still drops the enable pins
+            let disabled = self.disable();
+            halted.and(disabled)?;
+            return Err(MotorDriverError::WatchdogTimeout);
+        }
+        if watchdog.is_expired() {
+            return Err(MotorDriverError::WatchdogTimeout);
+        }
+        Ok(())
+    }
+
+    fn detect_stall(&mut self) -> Result<(), MotorDriverError> {
+        // Only a driven motor can stall; in any other state the timer restarts.
+        let duty = match self.state {
+            DriverState::Running => self.current_speed.unsigned_abs(),
+            _ => 0,
+        };
+        let pulse_count = self.get_pulse_count();
+        let now = self.clock.now_micros();
+        let Some(ref mut stall) = self.stall else {
+            return Ok(());
+        };
+        if !stall.update(duty, pulse_count, now) {
+            return Ok(());
+        }
+
+        // A Faulted driver must not keep driving, so the PWM is zeroed either way
+        let cut_power = stall.cuts_power();
+        self.fault |= FaultStatus::STALL;
+        self.state = DriverState::Faulted;
+        let stopped = self.stop();
+        let disabled = if cut_power { self.disable() } else { Ok(()) };
+        stopped.and(disabled)?;
+        Err(MotorDriverError::Stalled)
+    }
+
+    /// Folds back the duty above the continuous limit and trips above the peak limit.
+    fn limit_current(&mut self) -> Result<(), MotorDriverError> {
+        let current = match (&self.current_limit, &self.current_sense) {
+            (Some(_), Some(sense)) => sense.current(),
+            _ => None,
+        };
+        let (Some(current), Some(limit)) = (current, self.current_limit.as_mut()) else {
+            return Ok(());
+        };
+
+        let previous = limit.scale();
+        match limit.update(current, self.clock.now_micros()) {
+            Ok(scale) if scale != previous => self.update_pwm(),
+            Ok(_) => Ok(()),
+            Err(error) => {
+                self.fault |= FaultStatus::OVERCURRENT;
+                self.state = DriverState::Faulted;
+                let stopped = self.stop();
+                let disabled = self.disable();
+                stopped.and(disabled)?;
+                Err(error)
+            }
+        }
+    }
+
+    /// Advances the ramp limiter and applies the resulting duty cycle.
+    /// 
+    /// Call this periodically while a ramp, dead time or current limit is
+    /// configured so the output keeps moving towards the speed last passed to
+    /// `set_speed()`, the PWM is re-enabled after a direction flip and the
+    /// current limits are enforced. Without any of them it only checks for
+    /// faults.
+    /// 
+    /// # Returns
+    /// 
+    /// * `Ok(speed)` - Duty cycle currently applied to the motor
+    /// * `Err(MotorDriverError::NotInitialized)` if the driver is not initialized
+    /// * `Err(MotorDriverError::OverCurrent)` if the peak current limit tripped
+    /// * `Err(MotorDriverError::EmergencyStop)` while the emergency stop is latched
+    /// * `Err(MotorDriverError::WatchdogTimeout)` while the command watchdog is expired
+    /// * `Err(MotorDriverError::Stalled)` if stall detection latched a stall
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// motor.set_speed(-800)?;
+    /// while motor.get_speed()? != motor.target_speed() {
+    ///     motor.update()?;
+    ///     delay.delay_ms(10);
+    /// }
+    /// ```
+    pub fn update(&mut self) -> Result<i16, MotorDriverError> {
+        if !self.state.is_initialized() {
+            return Err(MotorDriverError::NotInitialized);
+        }
+        self.check_estop()?;
+        self.check_faulted()?;
+        self.check_watchdog()?;
+        self.limit_current()?;
+
+        if let Some(ref mut ramp) = self.ramp {
+            let speed = ramp.update(self.clock.now_micros());
+            if speed != self.current_speed {
+                self.apply_speed(speed)?;
+            }
+        }
+        if self.dead_time_start.is_some() {
+            self.update_pwm()?;
+        }
+        self.detect_stall()?;
+        Ok(self.current_speed)
+    }
+
+    /// Returns the speed the motor is ramping towards.
+    /// 
+    /// Equal to `get_speed()` unless a ramp is configured and still moving.
+    pub fn target_speed(&self) -> i16 {
+        match self.ramp {
+            Some(ref ramp) => ramp.target(),
+            None => self.current_speed,
+        }
+    }
+
     /// Reads the current encoder state and updates pulse count.
     /// 
     /// This method implements quadrature encoder decoding using a state machine
     /// to track motor position. It should be called regularly (typically in a
     /// timer interrupt or polling loop) to maintain accurate position tracking.
+    /// When an edge counter is attached it does nothing, since edges are
+    /// counted as they occur.
     /// 
     /// # Returns
     /// 
     /// * `Ok(())` if encoder reading succeeds
-    /// * `Err(MotorDriverError::GpioError)` if encoder pin reading fails
+    /// * `Err(MotorDriverError::GpioError(_))` if encoder pin reading fails
     /// * `Err(MotorDriverError::HardwareFault)` if encoders are not configured
     /// 
     /// # Example
@@ -616,13 +1700,19 @@ where
     /// let position = motor.get_pulse_count();
     /// ```
     pub fn read_encoder(&mut self) -> Result<(), MotorDriverError> {
+        #[cfg(feature = "std")]
+        if self.edge_counter.is_some() {
+            self.update_velocity();
+            return Ok(());
+        }
+
         if let (Some(ref mut enc_a), Some(ref mut enc_b)) = (&mut self.encoder1, &mut self.encoder2) {
-            let level_a = if enc_a.is_high().map_err(|_| MotorDriverError::GpioError)? { 
+            let level_a = if enc_a.is_high().map_err(MotorDriverError::gpio(Signal::EncoderA, Operation::Read))? { 
                 Level::High 
             } else { 
                 Level::Low 
             };
-            let level_b = if enc_b.is_high().map_err(|_| MotorDriverError::GpioError)? { 
+            let level_b = if enc_b.is_high().map_err(MotorDriverError::gpio(Signal::EncoderB, Operation::Read))? { 
                 Level::High 
             } else { 
                 Level::Low 
@@ -636,6 +1726,7 @@ where
             self.pulse_count += QEM[index as usize] as i32;
             self.last_enc_a = level_a;
             self.last_enc_b = level_b;
+            self.update_velocity();
             
             Ok(())
         } else {
@@ -643,45 +1734,283 @@ where
         }
     }
 
-    /// Gets the current encoder pulse count relative to the last reset.
+    /// Gets the current encoder pulse count relative to the last reset.
+    /// 
+    /// The pulse count is automatically adjusted by the pulse offset set
+    /// by `reset_encoder()` to provide relative position measurements.
+    /// 
+    /// # Returns
+    /// 
+    /// Current pulse count since last encoder reset
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// motor.reset_encoder(); // Reset to zero
+    /// // ... motor movement ...
+    /// let position = motor.get_pulse_count(); // Position since reset
+    /// ```
+    pub fn get_pulse_count(&self) -> i32 {
+        #[cfg(feature = "std")]
+        if let Some(ref counter) = self.edge_counter {
+            return counter.count();
+        }
+
+        self.pulse_count - self.pulse_offset
+    }
+
+    /// Resets the encoder position counter to zero.
+    /// 
+    /// This sets the current position as the new reference point (zero).
+    /// Subsequent calls to `get_pulse_count()` will return values relative
+    /// to this reset point.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// motor.reset_encoder(); // Set current position as zero
+    /// ```
+    pub fn reset_encoder(&mut self) {
+        #[cfg(feature = "std")]
+        if let Some(ref counter) = self.edge_counter {
+            counter.reset();
+        }
+
+        self.pulse_offset = self.pulse_count;
+        self.velocity.reset();
+    }
+
+    fn update_velocity(&mut self) {
+        let now_us = self.clock.now_micros();
+        let count = self.get_pulse_count();
+        self.velocity.update(count, now_us);
+    }
+
+    /// Returns the current motor velocity estimated from the encoder.
+    /// 
+    /// The estimate is refreshed from the current pulse count on every
+    /// `read_encoder()` and on every call to this method, using the method
+    /// configured with `with_velocity_estimation()` and the driver's clock.
+    /// 
+    /// # Returns
+    /// 
+    /// * `Ok(velocity)` - Velocity in pulses/s, RPM and rad/s
+    /// * `Err(MotorDriverError::InvalidConfiguration)` if PPR is not configured
+    /// * `Err(MotorDriverError::HardwareFault)` if no encoder is configured
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// let velocity = motor.get_velocity()?;
+    /// println!("{:.1} RPM", velocity.rpm);
+    /// ```
+    pub fn get_velocity(&mut self) -> Result<Velocity, MotorDriverError> {
+        if !self.has_encoder() {
+            return Err(MotorDriverError::HardwareFault);
+        }
+        if self.ppr == 0 {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+
+        self.update_velocity();
+        Ok(Velocity::from_pulses_per_sec(self.velocity.pulses_per_sec(), self.ppr))
+    }
+
+    /// Returns the edge-driven pulse counter, if one is attached.
+    /// 
+    /// The returned handle can be read from other threads without locking
+    /// the motor driver.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// let counter = motor.edge_counter().unwrap();
+    /// thread::spawn(move || println!("{}", counter.count()));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn edge_counter(&self) -> Option<Arc<QuadratureCounter>> {
+        self.edge_counter.clone()
+    }
+
+    /// Returns the configured maximum duty cycle value.
+    pub fn max_duty(&self) -> u16 {
+        self.max_duty
+    }
+
+    /// Reads the driver's clock, shared with the control loops.
+    pub(crate) fn now_micros(&self) -> u64 {
+        self.clock.now_micros()
+    }
+
+    /// Returns the configured current decay mode.
+    pub fn decay_mode(&self) -> DecayMode {
+        self.decay_mode
+    }
+
+    /// Changes the current decay mode and re-applies the current speed.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// motor.set_decay_mode(DecayMode::Slow)?;
+    /// ```
+    pub fn set_decay_mode(&mut self, mode: DecayMode) -> Result<(), MotorDriverError> {
+        self.decay_mode = mode;
+        if self.state.is_initialized() {
+            self.update_pwm()?;
+        }
+        Ok(())
+    }
+
+    /// Samples the fault pin and current sense and returns the latched fault status.
+    /// 
+    /// A low level on the fault pin latches `FaultStatus::GENERIC` and a
+    /// current above the sense limit latches `FaultStatus::OVERCURRENT`.
+    /// Without either input the latched status is returned unchanged.
+    /// 
+    /// # Returns
+    /// 
+    /// * `Ok(status)` - Latched faults, empty if none
+    /// * `Err(MotorDriverError::GpioError(_))` if the fault pin cannot be read
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// if !motor.check_fault()?.is_empty() {
+    ///     motor.disable()?;
+    /// }
+    /// ```
+    pub fn check_fault(&mut self) -> Result<FaultStatus, MotorDriverError> {
+        if let Some(ref mut pin) = self.fault_pin {
+            if pin.is_low().map_err(MotorDriverError::gpio(Signal::Fault, Operation::Read))? {
+                self.fault |= FaultStatus::GENERIC;
+            }
+        }
+        if let Some(ref mut sense) = self.current_sense {
+            self.fault |= sense.sample()?;
+        }
+        Ok(self.fault)
+    }
+
+    /// Samples the current sensor and returns the motor current in amps.
     /// 
-    /// The pulse count is automatically adjusted by the pulse offset set
-    /// by `reset_encoder()` to provide relative position measurements.
+    /// Latches the faults reported by the sensor, such as `FaultStatus::OVERCURRENT`.
     /// 
-    /// # Returns
+    /// # Errors
     /// 
-    /// Current pulse count since last encoder reset
+    /// Returns `HardwareFault` if no sensor is configured, it measures no current or the conversion fails.
+    pub fn sample_current(&mut self) -> Result<f32, MotorDriverError> {
+        let sense = self.current_sense.as_mut().ok_or(MotorDriverError::HardwareFault)?;
+        self.fault |= sense.sample()?;
+        sense.current().ok_or(MotorDriverError::HardwareFault)
+    }
+
+    /// Clears the latched faults and samples the fault pin again.
     /// 
-    /// # Example
+    /// A `Faulted` driver with no remaining fault is stopped and returns to
+    /// `Disabled`; call `enable()` to drive it again.
     /// 
-    /// ```rust
-    /// motor.reset_encoder(); // Reset to zero
-    /// // ... motor movement ...
-    /// let position = motor.get_pulse_count(); // Position since reset
-    /// ```
-    pub fn get_pulse_count(&self) -> i32 {
-        self.pulse_count - self.pulse_offset
+    /// # Returns
+    /// 
+    /// * `Ok(status)` - Faults still present after clearing, empty if the driver recovered
+    /// * `Err(MotorDriverError::GpioError(_))` if the fault pin cannot be read
+    pub fn clear_fault(&mut self) -> Result<FaultStatus, MotorDriverError> {
+        self.fault = FaultStatus::empty();
+        if let Some(ref mut sense) = self.current_sense {
+            sense.reset();
+        }
+        if let Some(ref mut limit) = self.current_limit {
+            limit.reset();
+        }
+        if let Some(ref mut stall) = self.stall {
+            stall.reset();
+        }
+
+        let status = self.check_fault()?;
+        if status.is_empty() && self.state == DriverState::Faulted {
+            self.stop()?;
+            self.disable()?;
+            self.state = DriverState::Disabled;
+        }
+        Ok(status)
     }
 
-    /// Resets the encoder position counter to zero.
+    /// Returns the lifecycle state of the driver.
     /// 
-    /// This sets the current position as the new reference point (zero).
-    /// Subsequent calls to `get_pulse_count()` will return values relative
-    /// to this reset point.
+    /// Reports `EStopped` as soon as an attached emergency stop is latched,
+    /// even before the next call has braked the motor.
     /// 
     /// # Example
     /// 
-    /// ```rust
-    /// motor.reset_encoder(); // Set current position as zero
+    /// ```rust,ignore
+    /// motor.initialize()?;
+    /// assert_eq!(motor.state(), DriverState::Disabled);
+    /// motor.enable()?;
+    /// motor.set_speed(300)?;
+    /// assert_eq!(motor.state(), DriverState::Running);
     /// ```
-    pub fn reset_encoder(&mut self) {
-        self.pulse_offset = self.pulse_count;
+    pub fn state(&self) -> DriverState {
+        #[cfg(feature = "std")]
+        if self.state.is_initialized() && self.estop.as_ref().is_some_and(EStop::is_triggered) {
+            return DriverState::EStopped;
+        }
+        self.state
+    }
+
+    /// Restarts the command watchdog without changing the speed.
+    /// 
+    /// `set_speed()` and `enable()` feed the watchdog too; call this from a
+    /// control loop that holds a constant speed.
+    pub fn feed(&mut self) {
+        let now = self.clock.now_micros();
+        if let Some(ref mut watchdog) = self.watchdog {
+            watchdog.feed(now);
+        }
+    }
+
+    /// Returns `true` if the command watchdog has expired and not been fed since.
+    pub fn is_watchdog_expired(&self) -> bool {
+        self.watchdog.is_some_and(|watchdog| watchdog.is_expired())
+    }
+
+    /// Returns `true` if stall detection has latched a stall.
+    pub fn is_stalled(&self) -> bool {
+        self.stall.is_some_and(|stall| stall.is_stalled())
+    }
+
+    /// Returns the duty fold-back factor applied by the current limit, 1.0 without one.
+    pub fn current_limit_scale(&self) -> f32 {
+        match self.current_limit {
+            Some(ref limit) => limit.scale(),
+            None => 1.0,
+        }
+    }
+
+    /// Returns the configured drive mode.
+    pub fn drive_mode(&self) -> DriveMode {
+        self.drive_mode
+    }
+
+    /// Returns the configured pulses per revolution (0 if unset).
+    pub fn ppr(&self) -> u16 {
+        self.ppr
+    }
+
+    /// Returns `true` if encoder feedback (pins or edge counter) is configured.
+    pub fn has_encoder(&self) -> bool {
+        #[cfg(feature = "std")]
+        if self.edge_counter.is_some() {
+            return true;
+        }
+
+        self.encoder1.is_some() && self.encoder2.is_some()
     }
 
     /// Sets the target pulse count for position control.
     /// 
-    /// This target is used by `check_ppr()` to verify that the motor
-    /// has reached the desired position.
+    /// The target is an absolute, multi-turn pulse count relative to the
+    /// encoder reset point. It is followed by a `PositionController` and
+    /// checked with `is_at_target()`.
     /// 
     /// # Arguments
     /// 
@@ -690,131 +2019,205 @@ where
     /// # Example
     /// 
     /// ```rust
-    /// motor.set_target_pulse(1000); // Move 1000 pulses from current position
+    /// motor.set_target_pulse(1000); // Target 1000 pulses from the reset point
     /// ```
     pub fn set_target_pulse(&mut self, target: i32) {
         self.target_pulse = target;
     }
+
+    /// Returns the target pulse count for position control.
+    pub fn target_pulse(&self) -> i32 {
+        self.target_pulse
+    }
+
+    /// Checks whether the encoder position is within `tolerance` pulses of the target.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// motor.read_encoder()?;
+    /// if motor.is_at_target(5) {
+    ///     motor.brake()?;
+    /// }
+    /// ```
+    pub fn is_at_target(&self, tolerance: u32) -> bool {
+        self.get_pulse_count().abs_diff(self.target_pulse) <= tolerance
+    }
 }
 
-impl<E1, E2, P1, P2, Enc1, Enc2> MotorDriver for HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2>
+impl<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C> MotorDriver for HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>
 where
-    E1: OutputPin,
-    E2: OutputPin,
-    P1: SetDutyCycle,
-    P2: SetDutyCycle,
-    Enc1: InputPin,
-    Enc2: InputPin,
+    E1: OutputPin<Error: HalCause>,
+    E2: OutputPin<Error: HalCause>,
+    P1: SetDutyCycle<Error: HalCause>,
+    P2: SetDutyCycle<Error: HalCause>,
+    Enc1: InputPin<Error: HalCause>,
+    Enc2: InputPin<Error: HalCause>,
+    D: OutputPin<Error: HalCause>,
+    F: InputPin<Error: HalCause>,
+    S: PowerSense,
+    C: Clock,
 {
     type Error = MotorDriverError;
     
     fn initialize(&mut self) -> Result<(), Self::Error> {
-        self.enable1.set_low().map_err(|_| MotorDriverError::GpioError)?;
+        self.enable1.set_low().map_err(MotorDriverError::gpio(Signal::Enable, Operation::SetLow))?;
         if let Some(ref mut enable2) = self.enable2 {
-            enable2.set_low().map_err(|_| MotorDriverError::GpioError)?;
+            enable2.set_low().map_err(MotorDriverError::gpio(Signal::Enable2, Operation::SetLow))?;
         }
         
-        self.pwm1.set_duty_cycle(0).map_err(|_| MotorDriverError::PwmError)?;
+        let neutral = self.neutral_duty();
+        self.pwm1.set_duty_cycle(neutral).map_err(MotorDriverError::pwm(Signal::Pwm))?;
         if let Some(ref mut pwm2) = self.pwm2 {
-            pwm2.set_duty_cycle(0).map_err(|_| MotorDriverError::PwmError)?;
+            pwm2.set_duty_cycle(0).map_err(MotorDriverError::pwm(Signal::Pwm2))?;
+        }
+        
+        if let Some(ref mut sense) = self.current_sense {
+            sense.init()?;
         }
         
-        self.initialized = true;
+        self.state = DriverState::Disabled;
         Ok(())
     }
 
     fn set_speed(&mut self, speed: i16) -> Result<(), Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
         
         if speed.unsigned_abs() > self.max_duty {
             return Err(MotorDriverError::InvalidSpeed);
         }
+        self.check_estop()?;
+        self.check_faulted()?;
+        if !self.state.can_drive() {
+            return Err(MotorDriverError::InvalidState);
+        }
+        self.feed();
         
-        self.current_speed = speed;
-        self.direction = speed >= 0;
+        let speed = match self.ramp {
+            Some(ref mut ramp) => ramp.set_target(speed, self.clock.now_micros()),
+            None => speed,
+        };
         
-        self.update_pwm()
+        self.apply_speed(speed)
     }
 
     fn set_direction(&mut self, forward: bool) -> Result<(), Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
-        
-        self.direction = forward;
-        self.update_pwm()
+        self.check_estop()?;
+        self.check_faulted()?;
+        if !self.state.can_drive() {
+            return Err(MotorDriverError::InvalidState);
+        }
+
+        let magnitude = self.target_speed().saturating_abs();
+        if magnitude == 0 {
+            // Nothing is driven, so only the reported direction changes
+            self.direction = forward;
+            return Ok(());
+        }
+        let target = if forward { magnitude } else { -magnitude };
+        let speed = match self.ramp {
+            Some(ref mut ramp) => ramp.set_target(target, self.clock.now_micros()),
+            None => target,
+        };
+        self.apply_speed(speed)
     }
 
     fn stop(&mut self) -> Result<(), Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
         
         self.current_speed = 0;
-        self.pwm1.set_duty_cycle(0).map_err(|_| MotorDriverError::PwmError)?;
+        if let Some(ref mut ramp) = self.ramp {
+            ramp.reset(0);
+        }
+        self.settle_state();
+        let neutral = self.neutral_duty();
+        self.pwm1.set_duty_cycle(neutral).map_err(MotorDriverError::pwm(Signal::Pwm))?;
         if let Some(ref mut pwm2) = self.pwm2 {
-            pwm2.set_duty_cycle(0).map_err(|_| MotorDriverError::PwmError)?;
+            pwm2.set_duty_cycle(0).map_err(MotorDriverError::pwm(Signal::Pwm2))?;
         }
         Ok(())
     }
 
     fn brake(&mut self) -> Result<(), Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
         
         self.current_speed = 0;
+        if let Some(ref mut ramp) = self.ramp {
+            ramp.reset(0);
+        }
+        self.settle_state();
+        self.dead_time_start = None;
         if let Some(ref mut pwm2) = self.pwm2 {
-            self.pwm1.set_duty_cycle(self.max_duty).map_err(|_| MotorDriverError::PwmError)?;
-            pwm2.set_duty_cycle(self.max_duty).map_err(|_| MotorDriverError::PwmError)?;
+            self.pwm1.set_duty_cycle(self.max_duty).map_err(MotorDriverError::pwm(Signal::Pwm))?;
+            pwm2.set_duty_cycle(self.max_duty).map_err(MotorDriverError::pwm(Signal::Pwm2))?;
+        } else if self.direction_pin2.is_some() && self.drive_mode == DriveMode::SignMagnitude {
+            self.write_direction_pins(true, true)?;
+            self.pin_direction = None;
+            self.pwm1.set_duty_cycle(self.max_duty).map_err(MotorDriverError::pwm(Signal::Pwm))?;
         } else {
-            self.pwm1.set_duty_cycle(0).map_err(|_| MotorDriverError::PwmError)?;
+            let neutral = self.neutral_duty();
+            self.pwm1.set_duty_cycle(neutral).map_err(MotorDriverError::pwm(Signal::Pwm))?;
         }
         Ok(())
     }
 
     fn enable(&mut self) -> Result<(), Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
+        self.check_estop()?;
+        self.check_faulted()?;
         
-        self.enable1.set_high().map_err(|_| MotorDriverError::GpioError)?;
+        self.enable1.set_high().map_err(MotorDriverError::gpio(Signal::Enable, Operation::SetHigh))?;
         if let Some(ref mut enable2) = self.enable2 {
-            enable2.set_high().map_err(|_| MotorDriverError::GpioError)?;
+            enable2.set_high().map_err(MotorDriverError::gpio(Signal::Enable2, Operation::SetHigh))?;
         }
+        self.state = DriverState::Enabled;
+        self.settle_state();
+        self.feed();
         Ok(())
     }
 
     fn disable(&mut self) -> Result<(), Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
         
-        self.enable1.set_low().map_err(|_| MotorDriverError::GpioError)?;
+        self.enable1.set_low().map_err(MotorDriverError::gpio(Signal::Enable, Operation::SetLow))?;
         if let Some(ref mut enable2) = self.enable2 {
-            enable2.set_low().map_err(|_| MotorDriverError::GpioError)?;
+            enable2.set_low().map_err(MotorDriverError::gpio(Signal::Enable2, Operation::SetLow))?;
+        }
+        if self.state.can_drive() {
+            self.state = DriverState::Disabled;
         }
         Ok(())
     }
 
     fn get_speed(&self) -> Result<i16, Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
         Ok(self.current_speed)
     }
 
     fn get_direction(&self) -> Result<bool, Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
         Ok(self.direction)
     }
 
     fn set_ppr(&mut self, ppr: i16) -> Result<bool, Self::Error> {        
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
         if ppr <= 0 {
@@ -826,57 +2229,76 @@ where
 
     fn check_ppr(&mut self) -> Result<(), Self::Error> {
         if self.ppr == 0 {
-            return Err(MotorDriverError::NotInitialized);
+            return Err(MotorDriverError::InvalidConfiguration);
         }
         
-        self.read_encoder()?;
-        
-        let current_pulse = self.get_pulse_count();
-        let current_rotation_pulse = current_pulse % (self.ppr as i32);
-        let target_rotation_pulse = self.target_pulse % (self.ppr as i32);
-        
-        if current_rotation_pulse == target_rotation_pulse {
-            Ok(())
-        } else {
-            Err(MotorDriverError::HardwareFault)
-        }
+        self.read_encoder()
     }
 
-
     fn get_current(&self) -> Result<f32, Self::Error> {
-        Err(MotorDriverError::HardwareFault)
+        self.current_sense
+            .as_ref()
+            .and_then(|sense| sense.current())
+            .ok_or(MotorDriverError::HardwareFault)
     }
 
     fn get_voltage(&self) -> Result<f32, Self::Error> {
-        Err(MotorDriverError::HardwareFault)
+        self.current_sense
+            .as_ref()
+            .and_then(|sense| sense.voltage())
+            .ok_or(MotorDriverError::HardwareFault)
     }
 
     fn get_temperature(&self) -> Result<f32, Self::Error> {
         Err(MotorDriverError::HardwareFault)
     }
 
-    fn get_fault_status(&self) -> Result<u8, Self::Error> {
-        if !self.initialized {
+    fn get_fault_status(&self) -> Result<FaultStatus, Self::Error> {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
-        Ok(0)
+        Ok(self.fault)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C> EStopTarget for HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>
+where
+    E1: OutputPin<Error: HalCause>,
+    E2: OutputPin<Error: HalCause>,
+    P1: SetDutyCycle<Error: HalCause>,
+    P2: SetDutyCycle<Error: HalCause>,
+    Enc1: InputPin<Error: HalCause>,
+    Enc2: InputPin<Error: HalCause>,
+    D: OutputPin<Error: HalCause>,
+    F: InputPin<Error: HalCause>,
+    S: PowerSense,
+    C: Clock,
+{
+    fn attach_estop(&mut self, estop: EStop) {
+        self.estop = Some(estop);
     }
 }
 
 #[cfg(feature = "rppal")]
 pub mod rppal {
     use super::*;
+    use crate::encoder::EncoderChannel;
     use crate::wrapper::rppal::{GpioWrapper, PwmWrapper};
-    use ::rppal::gpio::{Gpio, InputPin as RppalInputPin, OutputPin as RppalOutputPin};
+    use ::rppal::gpio::{Gpio, InputPin as RppalInputPin, OutputPin as RppalOutputPin, Trigger};
     use ::rppal::pwm::{Channel, Pwm, Polarity};
 
-    pub type RppalMotorDriverBuilder = HBridgeMotorDriverBuilder<
+    pub type RppalMotorDriverBuilder<D = NoPin, F = NoEncoder, S = NoSense, C = DefaultClock> = HBridgeMotorDriverBuilder<
         GpioWrapper<RppalOutputPin>,
         GpioWrapper<RppalOutputPin>,
         PwmWrapper,
         PwmWrapper,
         GpioWrapper<RppalInputPin>,
-        GpioWrapper<RppalInputPin>
+        GpioWrapper<RppalInputPin>,
+        D,
+        F,
+        S,
+        C
     >;
 
     impl RppalMotorDriverBuilder {
@@ -897,7 +2319,9 @@ pub mod rppal {
         pub fn new_rppal() -> Self {
             HBridgeMotorDriverBuilder::new()
         }
+    }
 
+    impl<D, F, S, C> RppalMotorDriverBuilder<D, F, S, C> {
         pub fn with_gpio_enable(mut self, gpio: &Gpio, pin: u8) -> Result<Self, ::rppal::gpio::Error> {
             self.enable1 = Some(GpioWrapper::new(gpio.get(pin)?.into_output()));
             Ok(self)
@@ -958,6 +2382,22 @@ pub mod rppal {
             Ok(self)
         }
 
+        /// Configure the active-low nFAULT input with the internal pull-up enabled.
+        /// 
+        /// # Arguments
+        /// 
+        /// * `gpio` - Raspberry Pi GPIO interface
+        /// * `pin` - nFAULT pin number (0-27)
+        /// 
+        /// # Example
+        /// 
+        /// ```rust,ignore
+        /// builder.with_gpio_fault(&gpio, 17)?
+        /// ```
+        pub fn with_gpio_fault(self, gpio: &Gpio, pin: u8) -> Result<RppalMotorDriverBuilder<D, GpioWrapper<RppalInputPin>, S, C>, ::rppal::gpio::Error> {
+            Ok(self.with_fault_pin(GpioWrapper::new(gpio.get(pin)?.into_input_pullup())))
+        }
+
         /// Configure quadrature encoder pins for position feedback.
         /// 
         /// # Arguments
@@ -976,24 +2416,104 @@ pub mod rppal {
             self.encoder2 = Some(GpioWrapper::new(gpio.get(pin_b)?.into_input_pullup()));
             Ok(self)
         }
+
+        /// Configure quadrature encoder pins counted by GPIO interrupts.
+        /// 
+        /// Both pins trigger an asynchronous interrupt on every edge, which
+        /// updates a shared `QuadratureCounter`. `read_encoder()` does not need
+        /// to be polled and `get_pulse_count()` reads the counter lock-free.
+        /// 
+        /// # Arguments
+        /// 
+        /// * `gpio` - Raspberry Pi GPIO interface
+        /// * `pin_a` - Encoder A phase pin number (0-27)
+        /// * `pin_b` - Encoder B phase pin number (0-27)
+        /// 
+        /// # Example
+        /// 
+        /// ```rust,ignore
+        /// builder.with_encoder_interrupts(&gpio, 25, 8)?
+        /// ```
+        pub fn with_encoder_interrupts(mut self, gpio: &Gpio, pin_a: u8, pin_b: u8) -> Result<Self, ::rppal::gpio::Error> {
+            let mut enc_a = gpio.get(pin_a)?.into_input_pullup();
+            let mut enc_b = gpio.get(pin_b)?.into_input_pullup();
+            let counter = Arc::new(QuadratureCounter::with_levels(enc_a.is_high(), enc_b.is_high()));
+
+            let counter_a = counter.clone();
+            enc_a.set_async_interrupt(Trigger::Both, None, move |event| {
+                counter_a.on_edge(EncoderChannel::A, event.trigger == Trigger::RisingEdge);
+            })?;
+            let counter_b = counter.clone();
+            enc_b.set_async_interrupt(Trigger::Both, None, move |event| {
+                counter_b.on_edge(EncoderChannel::B, event.trigger == Trigger::RisingEdge);
+            })?;
+
+            self.encoder1 = Some(GpioWrapper::new(enc_a));
+            self.encoder2 = Some(GpioWrapper::new(enc_b));
+            self.edge_counter = Some(counter);
+            Ok(self)
+        }
     }
 }
 
 #[cfg(feature = "linux-embedded-hal")]
 pub mod linux {
     use super::*;
+    use crate::encoder::EncoderChannel;
     use crate::wrapper::linux::{GpioWrapper, PwmWrapper};
-    use linux_embedded_hal::{gpio_cdev::Chip, CdevPin};
+    use linux_embedded_hal::gpio_cdev::{Chip, EventRequestFlags, EventType, Line, LineEventHandle, LineRequestFlags};
+    use linux_embedded_hal::CdevPin;
+    use std::sync::Weak;
+    use std::thread;
 
-    pub type LinuxMotorDriverBuilder = HBridgeMotorDriverBuilder<
+    /// `GPIOHANDLE_REQUEST_BIAS_PULL_UP`, available since Linux 5.5 but not exposed by gpio-cdev.
+    const BIAS_PULL_UP: u32 = 1 << 5;
+
+    pub type LinuxMotorDriverBuilder<D = NoPin, F = NoEncoder, S = NoSense, C = DefaultClock> = HBridgeMotorDriverBuilder<
         GpioWrapper,
         GpioWrapper,
         PwmWrapper,
         PwmWrapper,
-        NoEncoder,
-        NoEncoder
+        GpioWrapper,
+        GpioWrapper,
+        D,
+        F,
+        S,
+        C
     >;
 
+    fn pull_up_input() -> LineRequestFlags {
+        LineRequestFlags::INPUT | LineRequestFlags::from_bits_retain(BIAS_PULL_UP)
+    }
+
+    /// Makes `request` with a pulled-up input, retrying as a plain input when it fails.
+    fn with_bias_fallback<T, E>(mut request: impl FnMut(LineRequestFlags) -> Result<T, E>) -> Result<T, E> {
+        // Kernels without bias support reject the flag, fall back to a plain input
+        request(pull_up_input()).or_else(|_| request(LineRequestFlags::INPUT))
+    }
+
+    fn request_encoder_input(chip: &mut Chip, pin: u32, consumer: &str) -> Result<CdevPin, linux_embedded_hal::gpio_cdev::errors::Error> {
+        let line = chip.get_line(pin)?;
+        let handle = with_bias_fallback(|flags| line.request(flags, 0, consumer))?;
+        CdevPin::new(handle)
+    }
+
+    fn request_encoder_events(line: &Line, consumer: &str) -> Result<LineEventHandle, linux_embedded_hal::gpio_cdev::errors::Error> {
+        with_bias_fallback(|flags| line.events(flags, EventRequestFlags::BOTH_EDGES, consumer))
+    }
+
+    /// Forwards line events to the counter until the line fails or the counter is dropped.
+    fn spawn_edge_thread(events: LineEventHandle, channel: EncoderChannel, counter: Weak<QuadratureCounter>) {
+        thread::spawn(move || {
+            for event in events {
+                let (Ok(event), Some(counter)) = (event, counter.upgrade()) else {
+                    break;
+                };
+                counter.on_edge(channel, event.event_type() == EventType::RisingEdge);
+            }
+        });
+    }
+
     impl LinuxMotorDriverBuilder {
         /// Create a new Linux motor driver builder.
         /// 
@@ -1006,16 +2526,18 @@ pub mod linux {
         /// ```rust
         /// let motor = LinuxMotorDriverBuilder::new_linux()
         ///     .with_dual_gpio_enable(&mut chip, 23, 24)?
-        ///     .with_dual_pwm_channels(0, 0, 1, 1000)
+        ///     .with_dual_pwm_channels(0, 0, 1, 1000.0, 1000)?
         ///     .build_and_init()?;
         /// ```
         pub fn new_linux() -> Self {
             HBridgeMotorDriverBuilder::new()
         }
+    }
 
+    impl<D, F, S, C> LinuxMotorDriverBuilder<D, F, S, C> {
         pub fn with_gpio_enable(mut self, chip: &mut Chip, pin: u32) -> Result<Self, linux_embedded_hal::gpio_cdev::errors::Error> {
             let handle = chip.get_line(pin)?.request(
-                linux_embedded_hal::gpio_cdev::LineRequestFlags::OUTPUT,
+                LineRequestFlags::OUTPUT,
                 0,
                 "enable"
             )?;
@@ -1025,12 +2547,12 @@ pub mod linux {
 
         pub fn with_dual_gpio_enable(mut self, chip: &mut Chip, pin1: u32, pin2: u32) -> Result<Self, linux_embedded_hal::gpio_cdev::errors::Error> {
             let handle1 = chip.get_line(pin1)?.request(
-                linux_embedded_hal::gpio_cdev::LineRequestFlags::OUTPUT,
+                LineRequestFlags::OUTPUT,
                 0,
                 "enable1"
             )?;
             let handle2 = chip.get_line(pin2)?.request(
-                linux_embedded_hal::gpio_cdev::LineRequestFlags::OUTPUT,
+                LineRequestFlags::OUTPUT,
                 0,
                 "enable2"
             )?;
@@ -1039,18 +2561,671 @@ pub mod linux {
             Ok(self)
         }
 
-        pub fn with_pwm_channel(mut self, chip: u32, channel: u32, max_duty: u16) -> Self {
-            self.pwm1 = Some(PwmWrapper::new(chip, channel, max_duty));
+        /// Configure a single sysfs PWM channel for motor speed control.
+        /// 
+        /// # Arguments
+        /// 
+        /// * `chip` - PWM chip number (`/sys/class/pwm/pwmchipN`)
+        /// * `channel` - PWM channel number on the chip
+        /// * `frequency` - PWM frequency in Hz (e.g., 1000.0)
+        /// * `max_duty` - Maximum duty cycle value (e.g., 1000)
+        /// 
+        /// # Example
+        /// 
+        /// ```rust
+        /// builder.with_pwm_channel(0, 0, 1000.0, 1000)?
+        /// ```
+        pub fn with_pwm_channel(mut self, chip: u32, channel: u32, frequency: f64, max_duty: u16) -> Result<Self, std::io::Error> {
+            self.pwm1 = Some(PwmWrapper::new(chip, channel, frequency, max_duty)?);
             self.max_duty = Some(max_duty);
-            self
+            Ok(self)
         }
 
-        pub fn with_dual_pwm_channels(mut self, chip: u32, channel1: u32, channel2: u32, max_duty: u16) -> Self {
-            self.pwm1 = Some(PwmWrapper::new(chip, channel1, max_duty));
-            self.pwm2 = Some(PwmWrapper::new(chip, channel2, max_duty));
+        /// Configure dual sysfs PWM channels on the same chip.
+        /// 
+        /// # Arguments
+        /// 
+        /// * `chip` - PWM chip number (`/sys/class/pwm/pwmchipN`)
+        /// * `channel1` - First PWM channel number
+        /// * `channel2` - Second PWM channel number
+        /// * `frequency` - PWM frequency in Hz (e.g., 1000.0)
+        /// * `max_duty` - Maximum duty cycle value (e.g., 1000)
+        /// 
+        /// # Example
+        /// 
+        /// ```rust
+        /// builder.with_dual_pwm_channels(0, 0, 1, 1000.0, 1000)?
+        /// ```
+        pub fn with_dual_pwm_channels(
+            mut self,
+            chip: u32,
+            channel1: u32,
+            channel2: u32,
+            frequency: f64,
+            max_duty: u16
+        ) -> Result<Self, std::io::Error> {
+            self.pwm1 = Some(PwmWrapper::new(chip, channel1, frequency, max_duty)?);
+            self.pwm2 = Some(PwmWrapper::new(chip, channel2, frequency, max_duty)?);
             self.max_duty = Some(max_duty);
-            self
+            Ok(self)
+        }
+
+        /// Configure the active-low nFAULT input.
+        /// 
+        /// The line is requested with the internal pull-up enabled when the
+        /// kernel supports line bias, since nFAULT is an open-drain output.
+        /// 
+        /// # Arguments
+        /// 
+        /// * `chip` - GPIO character device chip
+        /// * `pin` - nFAULT line offset
+        /// 
+        /// # Example
+        /// 
+        /// ```rust,ignore
+        /// builder.with_gpio_fault(&mut chip, 17)?
+        /// ```
+        pub fn with_gpio_fault(self, chip: &mut Chip, pin: u32) -> Result<LinuxMotorDriverBuilder<D, GpioWrapper, S, C>, linux_embedded_hal::gpio_cdev::errors::Error> {
+            Ok(self.with_fault_pin(GpioWrapper::new(request_encoder_input(chip, pin, "nfault")?)))
+        }
+
+        /// Configure quadrature encoder pins for position feedback.
+        /// 
+        /// The lines are requested as inputs with the internal pull-up enabled
+        /// when the kernel supports line bias (Linux 5.5+), and as plain inputs otherwise.
+        /// 
+        /// # Arguments
+        /// 
+        /// * `chip` - GPIO character device chip
+        /// * `pin_a` - Encoder A phase line offset
+        /// * `pin_b` - Encoder B phase line offset
+        /// 
+        /// # Example
+        /// 
+        /// ```rust,ignore
+        /// builder.with_encoder_pins(&mut chip, 25, 8)?
+        /// ```
+        pub fn with_encoder_pins(mut self, chip: &mut Chip, pin_a: u32, pin_b: u32) -> Result<Self, linux_embedded_hal::gpio_cdev::errors::Error> {
+            self.encoder1 = Some(GpioWrapper::new(request_encoder_input(chip, pin_a, "encoder_a")?));
+            self.encoder2 = Some(GpioWrapper::new(request_encoder_input(chip, pin_b, "encoder_b")?));
+            Ok(self)
+        }
+
+        /// Configure quadrature encoder lines counted from GPIO line events.
+        /// 
+        /// Both lines are requested for rising and falling edge events, and a
+        /// background thread per line feeds each edge into a shared
+        /// `QuadratureCounter`. `read_encoder()` does not need to be polled and
+        /// `get_pulse_count()` reads the counter lock-free. The threads exit on
+        /// the first edge after the driver and all counter handles are dropped.
+        /// 
+        /// # Arguments
+        /// 
+        /// * `chip` - GPIO character device chip
+        /// * `pin_a` - Encoder A phase line offset
+        /// * `pin_b` - Encoder B phase line offset
+        /// 
+        /// # Example
+        /// 
+        /// ```rust,ignore
+        /// builder.with_encoder_events(&mut chip, 25, 8)?
+        /// ```
+        pub fn with_encoder_events(mut self, chip: &mut Chip, pin_a: u32, pin_b: u32) -> Result<Self, linux_embedded_hal::gpio_cdev::errors::Error> {
+            let events_a = request_encoder_events(&chip.get_line(pin_a)?, "encoder_a")?;
+            let events_b = request_encoder_events(&chip.get_line(pin_b)?, "encoder_b")?;
+            let counter = Arc::new(QuadratureCounter::with_levels(
+                events_a.get_value()? != 0,
+                events_b.get_value()? != 0,
+            ));
+
+            spawn_edge_thread(events_a, EncoderChannel::A, Arc::downgrade(&counter));
+            spawn_edge_thread(events_b, EncoderChannel::B, Arc::downgrade(&counter));
+
+            self.edge_counter = Some(counter);
+            Ok(self)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::vec::Vec;
+
+        #[test]
+        fn bias_fallback_retries_as_a_plain_input() {
+            let mut requested = Vec::new();
+            let result: Result<u32, ()> = with_bias_fallback(|flags| {
+                requested.push(flags.bits());
+                if flags.bits() & BIAS_PULL_UP != 0 { Err(()) } else { Ok(flags.bits()) }
+            });
+
+            assert_eq!(result, Ok(LineRequestFlags::INPUT.bits()));
+            assert_eq!(requested, [pull_up_input().bits(), LineRequestFlags::INPUT.bits()]);
+        }
+
+        #[test]
+        fn bias_fallback_keeps_the_pull_up_when_supported() {
+            let mut calls = 0;
+            let result: Result<u32, ()> = with_bias_fallback(|flags| {
+                calls += 1;
+                Ok(flags.bits())
+            });
+
+            assert_eq!(result, Ok(pull_up_input().bits()));
+            assert_eq!(calls, 1);
+        }
+
+        #[test]
+        fn bias_fallback_reports_the_plain_input_error() {
+            let result: Result<(), &str> = with_bias_fallback(|flags| {
+                Err(if flags.bits() & BIAS_PULL_UP == 0 { "plain" } else { "bias" })
+            });
+
+            assert_eq!(result, Err("plain"));
         }
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::current::{CurrentScaling, CurrentSense};
+    use crate::mock::{Event, Log, MockAdc, MockEncoder, MockPin, MockPwm};
+
+    type Driver<'a> = HBridgeMotorDriver<MockPin, MockPin, MockPwm, MockPwm, NoEncoder, NoEncoder, MockPin, NoEncoder, NoSense, &'a MockClock>;
+    type EncoderDriver<'a> = HBridgeMotorDriver<MockPin, MockPin, MockPwm, MockPwm, MockPin, MockPin, NoPin, NoEncoder, NoSense, &'a MockClock>;
+
+    fn in1_in2_driver<'a>(log: &Log, clock: &'a MockClock, dead_time_us: u32) -> Driver<'a> {
+        let mut motor = HBridgeMotorDriverBuilder::new()
+            .with_enable(log.pin("en"))
+            .with_pwm(log.pwm("pwm"))
+            .with_direction_pins(log.pin("in1"), log.pin("in2"))
+            .with_dead_time(dead_time_us)
+            .with_clock(clock)
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        motor
+    }
+
+    #[test]
+    fn reversal_zeroes_pwm_before_flipping_direction_pins() {
+        let (log, clock) = (Log::new(), MockClock::new());
+        let mut motor = in1_in2_driver(&log, &clock, 500);
+        motor.set_speed(600).unwrap();
+        log.take();
+
+        motor.set_speed(-600).unwrap();
+        let events = log.take();
+        let zeroed = events.iter().position(|e| *e == Event::Duty("pwm", 0)).unwrap();
+        let flipped = events.iter().position(|e| *e == Event::Pin("in1", false)).unwrap();
+        assert!(zeroed < flipped, "{events:?}");
+
+        clock.advance(499);
+        motor.update().unwrap();
+        assert_eq!(log.duty("pwm"), Some(0));
+        clock.advance(1);
+        motor.update().unwrap();
+        assert_eq!(log.duty("pwm"), Some(600));
+    }
+
+    #[test]
+    fn reversal_through_brake_still_waits_out_dead_time() {
+        let (log, clock) = (Log::new(), MockClock::new());
+        let mut motor = in1_in2_driver(&log, &clock, 500);
+        motor.set_speed(600).unwrap();
+        motor.brake().unwrap();
+        clock.advance(10_000);
+        log.take();
+
+        motor.set_speed(-600).unwrap();
+        assert_eq!(log.duty("pwm"), Some(0));
+        assert_eq!((log.level("in1"), log.level("in2")), (Some(false), Some(true)));
+        clock.advance(500);
+        motor.update().unwrap();
+        assert_eq!(log.duty("pwm"), Some(600));
+
+        // Releasing the brake in the same direction needs no dead time
+        motor.brake().unwrap();
+        log.take();
+        motor.set_speed(-300).unwrap();
+        assert_eq!(log.duty("pwm"), Some(300));
+    }
+
+    #[test]
+    fn stall_is_only_detected_while_running() {
+        let (log, clock) = (Log::new(), MockClock::new());
+        let mut motor = HBridgeMotorDriverBuilder::<_, MockPin, _, MockPwm, _, _>::new()
+            .with_enable(log.pin("en"))
+            .with_pwm(log.pwm("pwm"))
+            .with_encoder(log.pin("a"), log.pin("b"))
+            .with_stall_detection(StallDetector::new(200, 100).unwrap())
+            .with_clock(&clock)
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        motor.set_speed(500).unwrap();
+        motor.update().unwrap();
+
+        // A disabled bridge cannot turn the motor, so it is not a stall
+        motor.disable().unwrap();
+        clock.advance(200_000);
+        assert_eq!(motor.update(), Ok(500));
+        assert!(!motor.is_stalled());
+
+        // The timer restarts once the motor is driven again
+        motor.enable().unwrap();
+        motor.set_speed(500).unwrap();
+        assert_eq!(motor.update(), Ok(500));
+        clock.advance(99_000);
+        assert_eq!(motor.update(), Ok(500));
+        clock.advance(1_000);
+        assert_eq!(motor.update(), Err(MotorDriverError::Stalled));
+        assert_eq!(motor.state(), DriverState::Faulted);
+        assert!(motor.is_stalled());
+    }
+
+    fn stall_driver<'a>(log: &Log, encoder: &MockEncoder, clock: &'a MockClock, stall: StallDetector) -> EncoderDriver<'a> {
+        let (a, b) = encoder.pins();
+        let mut motor = HBridgeMotorDriverBuilder::new()
+            .with_enable(log.pin("en"))
+            .with_pwm(log.pwm("pwm"))
+            .with_encoder(a, b)
+            .with_stall_detection(stall)
+            .with_clock(clock)
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        motor
+    }
+
+    #[test]
+    fn stall_needs_the_threshold_duty() {
+        let (log, clock) = (Log::new(), MockClock::new());
+        let mut motor = stall_driver(&log, &MockEncoder::new(&log), &clock, StallDetector::new(200, 100).unwrap());
+
+        motor.set_speed(199).unwrap();
+        for _ in 0..5 {
+            clock.advance(100_000);
+            assert_eq!(motor.update(), Ok(199));
+        }
+
+        // The window starts when the duty reaches the threshold
+        motor.set_speed(-200).unwrap();
+        clock.advance(99_999);
+        assert_eq!(motor.update(), Ok(-200));
+        clock.advance(1);
+        assert_eq!(motor.update(), Err(MotorDriverError::Stalled));
+    }
+
+    #[test]
+    fn stall_window_restarts_when_the_encoder_moves() {
+        let (log, clock) = (Log::new(), MockClock::new());
+        let mut encoder = MockEncoder::new(&log);
+        let mut motor = stall_driver(&log, &encoder, &clock, StallDetector::new(200, 100).unwrap());
+        motor.set_speed(500).unwrap();
+        motor.update().unwrap();
+
+        clock.advance(90_000);
+        encoder.turn(1, || motor.read_encoder().unwrap());
+        assert_eq!(motor.update(), Ok(500));
+        clock.advance(90_000);
+        assert_eq!(motor.update(), Ok(500));
+        clock.advance(10_000);
+        assert_eq!(motor.update(), Err(MotorDriverError::Stalled));
+        assert_eq!(motor.get_fault_status().unwrap(), FaultStatus::STALL);
+
+        // Clearing restarts detection from scratch
+        assert_eq!(motor.clear_fault(), Ok(FaultStatus::empty()));
+        assert!(!motor.is_stalled());
+        motor.enable().unwrap();
+        motor.set_speed(500).unwrap();
+        clock.advance(99_000);
+        assert_eq!(motor.update(), Ok(500));
+    }
+
+    #[test]
+    fn stall_stops_the_motor_and_cuts_power_on_request() {
+        for cut_power in [false, true] {
+            let (log, clock) = (Log::new(), MockClock::new());
+            let stall = StallDetector::new(200, 100).unwrap().with_cut_power(cut_power);
+            let mut motor = stall_driver(&log, &MockEncoder::new(&log), &clock, stall);
+            motor.set_speed(500).unwrap();
+            motor.update().unwrap();
+
+            clock.advance(100_000);
+            assert_eq!(motor.update(), Err(MotorDriverError::Stalled));
+            assert_eq!(motor.state(), DriverState::Faulted);
+            assert_eq!((log.duty("pwm"), motor.get_speed()), (Some(0), Ok(0)));
+            assert_eq!(log.level("en"), Some(!cut_power));
+        }
+    }
+
+    #[test]
+    fn stall_detection_requires_an_encoder() {
+        let log = Log::new();
+        let result = HBridgeMotorDriver::<_, MockPin, _, MockPwm, NoEncoder, NoEncoder>::builder()
+            .with_enable(log.pin("en"))
+            .with_pwm(log.pwm("pwm"))
+            .with_stall_detection(StallDetector::new(200, 100).unwrap())
+            .try_build();
+        assert_eq!(result.err(), Some(ConfigError::StallWithoutEncoder));
+    }
+
+    #[test]
+    fn build_and_init_leaves_validation_to_try_build() {
+        let log = Log::new();
+        let builder = || {
+            HBridgeMotorDriver::<_, MockPin, _, MockPwm, NoEncoder, NoEncoder>::builder()
+                .with_enable(log.pin("en"))
+                .with_pwm(log.pwm("pwm"))
+                .with_ppr(1000)
+        };
+        assert!(builder().build_and_init().is_ok());
+        assert_eq!(builder().try_build().err(), Some(ConfigError::PprWithoutEncoder));
+    }
+
+    #[test]
+    fn watchdog_stops_and_disables_when_commands_stop() {
+        let (log, clock) = (Log::new(), MockClock::new());
+        let mut motor = HBridgeMotorDriverBuilder::<_, MockPin, _, MockPwm, NoEncoder, NoEncoder>::new()
+            .with_enable(log.pin("en"))
+            .with_pwm(log.pwm("pwm"))
+            .with_watchdog(Watchdog::new(100).unwrap())
+            .with_clock(&clock)
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        motor.set_speed(400).unwrap();
+
+        clock.advance(60_000);
+        motor.set_speed(300).unwrap();
+        clock.advance(60_000);
+        assert_eq!(motor.update(), Ok(300));
+        clock.advance(40_000);
+        assert_eq!(motor.update(), Err(MotorDriverError::WatchdogTimeout));
+        assert_eq!(motor.state(), DriverState::Disabled);
+        assert_eq!((log.duty("pwm"), log.level("en")), (Some(0), Some(false)));
+        assert_eq!(motor.set_speed(300), Err(MotorDriverError::InvalidState));
+    }
+
+    #[cfg(feature = "std")]
+    fn estop_driver<'a>(log: &Log, clock: &'a MockClock, estop: &EStop) -> Driver<'a> {
+        let mut motor = HBridgeMotorDriverBuilder::new()
+            .with_enable(log.pin("en"))
+            .with_pwm(log.pwm("pwm"))
+            .with_direction_pins(log.pin("in1"), log.pin("in2"))
+            .with_estop(estop.clone())
+            .with_clock(clock)
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        motor.set_speed(400).unwrap();
+        motor
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn update_polls_the_estop_input() {
+        let (log, clock) = (Log::new(), MockClock::new());
+        let button = log.pin("estop");
+        let estop = EStop::new().with_input(button.clone(), false);
+        let mut motor = estop_driver(&log, &clock, &estop);
+
+        button.drive(true);
+        assert_eq!(motor.update(), Err(MotorDriverError::EmergencyStop));
+        assert!(estop.is_triggered());
+        assert_eq!(motor.state(), DriverState::EStopped);
+        assert_eq!(log.level("en"), Some(false));
+        assert_eq!(motor.enable(), Err(MotorDriverError::EmergencyStop));
+
+        button.drive(false);
+        estop.reset().unwrap();
+        motor.enable().unwrap();
+        assert_eq!(motor.state(), DriverState::Enabled);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn estop_disables_even_when_braking_fails() {
+        let (log, clock, estop) = (Log::new(), MockClock::new(), EStop::new());
+        let mut motor = estop_driver(&log, &clock, &estop);
+
+        log.fail_pwm(true);
+        estop.trigger();
+        assert!(matches!(motor.update(), Err(MotorDriverError::PwmError(_))));
+        assert_eq!(motor.state(), DriverState::EStopped);
+        assert_eq!(log.level("en"), Some(false));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn polling_a_registered_driver_does_not_deadlock() {
+        use std::sync::{Arc, Mutex};
+
+        let log = Log::new();
+        let button = log.pin("estop");
+        let estop = EStop::new().with_input(button.clone(), false);
+        let motor = Arc::new(Mutex::new(
+            HBridgeMotorDriverBuilder::<_, MockPin, _, MockPwm, NoEncoder, NoEncoder>::new()
+                .with_enable(log.pin("en"))
+                .with_pwm(log.pwm("pwm"))
+                .with_estop(estop.clone())
+                .build_and_init()
+                .unwrap(),
+        ));
+        estop.register(motor.clone());
+        motor.lock().unwrap().enable().unwrap();
+
+        button.drive(true);
+        assert_eq!(motor.lock().unwrap().update(), Err(MotorDriverError::EmergencyStop));
+        assert_eq!(motor.lock().unwrap().state(), DriverState::EStopped);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn registering_attaches_the_estop() {
+        use std::sync::{Arc, Mutex};
+
+        let log = Log::new();
+        let estop = EStop::new();
+        let motor = Arc::new(Mutex::new(
+            HBridgeMotorDriverBuilder::<_, MockPin, _, MockPwm, NoEncoder, NoEncoder>::new()
+                .with_enable(log.pin("en"))
+                .with_pwm(log.pwm("pwm"))
+                .build_and_init()
+                .unwrap(),
+        ));
+        estop.register(motor.clone());
+        motor.lock().unwrap().enable().unwrap();
+
+        estop.trigger();
+        assert_eq!(log.level("en"), Some(false));
+        let mut motor = motor.lock().unwrap();
+        assert_eq!(motor.enable(), Err(MotorDriverError::EmergencyStop));
+        assert_eq!(motor.state(), DriverState::EStopped);
+
+        estop.reset().unwrap();
+        motor.enable().unwrap();
+        motor.set_speed(300).unwrap();
+        assert_eq!(log.duty("pwm"), Some(300));
+    }
+
+    #[test]
+    fn set_direction_is_gated_on_state_and_ramped() {
+        let (log, clock) = (Log::new(), MockClock::new());
+        let mut motor = HBridgeMotorDriverBuilder::<_, MockPin, _, MockPwm, NoEncoder, NoEncoder>::new()
+            .with_enable(log.pin("en"))
+            .with_pwm(log.pwm("pwm"))
+            .with_ramp(RampLimiter::symmetric(10_000.0).unwrap())
+            .with_clock(&clock)
+            .build_and_init()
+            .unwrap();
+        log.take();
+        assert_eq!(motor.set_direction(false), Err(MotorDriverError::InvalidState));
+        assert!(log.take().is_empty());
+
+        motor.enable().unwrap();
+        motor.set_speed(500).unwrap();
+        clock.advance(50_000);
+        assert_eq!(motor.update(), Ok(500));
+
+        motor.set_direction(false).unwrap();
+        assert_eq!((motor.get_speed(), motor.target_speed()), (Ok(500), -500));
+        clock.advance(50_000);
+        assert_eq!(motor.update(), Ok(0));
+        clock.advance(50_000);
+        assert_eq!(motor.update(), Ok(-500));
+        assert_eq!(motor.get_direction(), Ok(false));
+        assert_eq!(motor.state(), DriverState::Running);
+    }
+
+    #[test]
+    fn slow_decay_holds_one_input_high_and_inverts_the_other() {
+        let log = Log::new();
+        let mut motor = HBridgeMotorDriverBuilder::<_, MockPin, _, _, NoEncoder, NoEncoder>::new()
+            .with_enable(log.pin("en"))
+            .with_dual_pwm(log.pwm("in1"), log.pwm("in2"))
+            .with_decay_mode(DecayMode::Slow)
+            .with_clock(MockClock::new())
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        log.take();
+
+        motor.set_speed(300).unwrap();
+        assert_eq!(log.take(), [Event::Duty("in1", 1000), Event::Duty("in2", 700)]);
+        motor.set_speed(-300).unwrap();
+        assert_eq!(log.take(), [Event::Duty("in1", 700), Event::Duty("in2", 1000)]);
+        motor.set_speed(-1000).unwrap();
+        assert_eq!(log.take(), [Event::Duty("in1", 0), Event::Duty("in2", 1000)]);
+
+        motor.set_decay_mode(DecayMode::Fast).unwrap();
+        log.take();
+        motor.set_speed(300).unwrap();
+        assert_eq!(log.take(), [Event::Duty("in1", 300), Event::Duty("in2", 0)]);
+    }
+
+    #[test]
+    fn locked_antiphase_maps_speed_around_half_duty() {
+        let log = Log::new();
+        let mut motor = HBridgeMotorDriverBuilder::<_, MockPin, _, _, NoEncoder, NoEncoder>::new()
+            .with_enable(log.pin("en"))
+            .with_dual_pwm(log.pwm("pwm1"), log.pwm("pwm2"))
+            .with_drive_mode(DriveMode::LockedAntiphase)
+            .with_clock(MockClock::new())
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        log.take();
+
+        for (speed, forward) in [(0, 500), (400, 700), (-400, 300), (1000, 1000), (-1000, 0)] {
+            motor.set_speed(speed).unwrap();
+            assert_eq!(log.take(), [Event::Duty("pwm1", forward), Event::Duty("pwm2", 1000 - forward)], "speed {speed}");
+        }
+
+        // Stopping coasts with both channels off rather than holding 50%
+        motor.stop().unwrap();
+        assert_eq!((log.duty("pwm1"), log.duty("pwm2")), (Some(0), Some(0)));
+    }
+
+    #[test]
+    fn sign_magnitude_drives_direction_pin_and_magnitude() {
+        let log = Log::new();
+        let mut motor = HBridgeMotorDriverBuilder::<_, MockPin, _, MockPwm, NoEncoder, NoEncoder>::new()
+            .with_enable(log.pin("en"))
+            .with_pwm(log.pwm("pwm"))
+            .with_direction_pin(log.pin("dir"))
+            .with_clock(MockClock::new())
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        log.take();
+
+        motor.set_speed(400).unwrap();
+        assert_eq!(log.take(), [Event::Pin("dir", true), Event::Duty("pwm", 400)]);
+        motor.set_speed(250).unwrap();
+        assert_eq!(log.take(), [Event::Duty("pwm", 250)]);
+        motor.set_speed(-400).unwrap();
+        assert_eq!(log.take(), [Event::Pin("dir", false), Event::Duty("pwm", 400)]);
+        motor.stop().unwrap();
+        assert_eq!(log.duty("pwm"), Some(0));
+    }
+
+    #[test]
+    fn nfault_latches_until_cleared() {
+        let log = Log::new();
+        let nfault = log.pin("nfault");
+        nfault.drive(true);
+        let mut motor = HBridgeMotorDriverBuilder::<_, MockPin, _, MockPwm, NoEncoder, NoEncoder>::new()
+            .with_enable(log.pin("en"))
+            .with_pwm(log.pwm("pwm"))
+            .with_fault_pin(nfault.clone())
+            .with_clock(MockClock::new())
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        motor.set_speed(300).unwrap();
+        assert_eq!(motor.check_fault(), Ok(FaultStatus::empty()));
+
+        // A short low pulse on the active-low pin stays latched after it goes high again
+        nfault.drive(false);
+        assert_eq!(motor.check_fault(), Ok(FaultStatus::GENERIC));
+        nfault.drive(true);
+        assert_eq!(motor.check_fault(), Ok(FaultStatus::GENERIC));
+
+        log.take();
+        assert_eq!(motor.set_speed(500), Err(MotorDriverError::HardwareFault));
+        assert_eq!(motor.state(), DriverState::Faulted);
+        assert_eq!(log.duty("pwm"), None);
+        assert_eq!(motor.update(), Err(MotorDriverError::HardwareFault));
+        assert_eq!(motor.enable(), Err(MotorDriverError::HardwareFault));
+
+        // Clearing while the pin is still low keeps the fault
+        nfault.drive(false);
+        assert_eq!(motor.clear_fault(), Ok(FaultStatus::GENERIC));
+        assert_eq!(motor.state(), DriverState::Faulted);
+
+        nfault.drive(true);
+        assert_eq!(motor.clear_fault(), Ok(FaultStatus::empty()));
+        assert_eq!(motor.state(), DriverState::Disabled);
+        assert_eq!(log.duty("pwm"), Some(0));
+        motor.enable().unwrap();
+        motor.set_speed(500).unwrap();
+        assert_eq!(log.duty("pwm"), Some(500));
+    }
+
+    #[test]
+    fn supervision_disables_even_when_the_pwm_write_fails() {
+        let (log, clock, adc) = (Log::new(), MockClock::new(), MockAdc::default());
+        let mut motor = HBridgeMotorDriverBuilder::<_, MockPin, _, MockPwm, NoEncoder, NoEncoder>::new()
+            .with_enable(log.pin("en"))
+            .with_pwm(log.pwm("pwm"))
+            .with_watchdog(Watchdog::new(100).unwrap())
+            .with_current_sense(CurrentSense::new(adc.clone(), CurrentScaling::Shunt { ohms: 1.0, gain: 1.0 }).unwrap())
+            .with_current_limit(CurrentLimit::new(2.0, 5.0).unwrap())
+            .with_clock(&clock)
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        motor.set_speed(400).unwrap();
+
+        log.fail_pwm(true);
+        clock.advance(100_000);
+        assert!(matches!(motor.update(), Err(MotorDriverError::PwmError(_))));
+        assert_eq!(motor.state(), DriverState::Disabled);
+        assert_eq!(log.level("en"), Some(false));
+
+        log.fail_pwm(false);
+        motor.feed();
+        motor.enable().unwrap();
+        motor.set_speed(400).unwrap();
+        log.fail_pwm(true);
+        adc.set(6.0);
+        assert!(matches!(motor.update(), Err(MotorDriverError::PwmError(_))));
+        assert_eq!(motor.state(), DriverState::Faulted);
+        assert_eq!(log.level("en"), Some(false));
+    }
+}
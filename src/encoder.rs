@@ -0,0 +1,171 @@
+use crate::driver::QEM;
+use core::sync::atomic::{AtomicI32, AtomicU8, Ordering};
+
+/// Quadrature encoder channel that produced an edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncoderChannel {
+    /// Encoder A phase
+    A,
+    /// Encoder B phase
+    B,
+}
+
+/// Lock-free quadrature pulse counter fed from edge events.
+///
+/// Instead of polling both encoder pins, interrupt handlers or event threads
+/// report each edge with `on_edge()`. The counter keeps the last A/B levels,
+/// decodes the transition with the same state table used by
+/// `HBridgeMotorDriver::read_encoder()` and accumulates the result in an
+/// atomic count that can be read from any thread without locking.
+///
+/// # Example
+///
+/// ```rust
+/// use motor_driver_hal::encoder::{EncoderChannel, QuadratureCounter};
+///
+/// let counter = QuadratureCounter::new();
+/// counter.on_edge(EncoderChannel::A, true);
+/// counter.on_edge(EncoderChannel::B, true);
+/// assert_eq!(counter.count(), 2);
+/// ```
+#[derive(Debug, Default)]
+pub struct QuadratureCounter {
+    state: AtomicU8,
+    count: AtomicI32,
+}
+
+impl QuadratureCounter {
+    /// Creates a counter assuming both encoder channels start low.
+    pub const fn new() -> Self {
+        Self::with_levels(false, false)
+    }
+
+    /// Creates a counter from the current levels of the A and B channels.
+    ///
+    /// # Arguments
+    ///
+    /// * `level_a` - `true` if encoder A is currently high
+    /// * `level_b` - `true` if encoder B is currently high
+    pub const fn with_levels(level_a: bool, level_b: bool) -> Self {
+        Self {
+            state: AtomicU8::new(((level_a as u8) << 1) | level_b as u8),
+            count: AtomicI32::new(0),
+        }
+    }
+
+    /// Records an edge on one encoder channel.
+    ///
+    /// Repeated reports of the level a channel already has are ignored, so
+    /// spurious or debounced events never change the count.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - Channel that changed
+    /// * `high` - New level of that channel (`true` for a rising edge)
+    pub fn on_edge(&self, channel: EncoderChannel, high: bool) {
+        let mask = match channel {
+            EncoderChannel::A => 0b10,
+            EncoderChannel::B => 0b01,
+        };
+
+        let mut last = self.state.load(Ordering::Acquire);
+        loop {
+            let next = if high { last | mask } else { last & !mask };
+            if next == last {
+                return;
+            }
+            match self.state.compare_exchange_weak(last, next, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => {
+                    let index = (last << 2) | next;
+                    self.count.fetch_add(QEM[index as usize] as i32, Ordering::AcqRel);
+                    return;
+                }
+                Err(current) => last = current,
+            }
+        }
+    }
+
+    /// Returns the accumulated pulse count.
+    pub fn count(&self) -> i32 {
+        self.count.load(Ordering::Acquire)
+    }
+
+    /// Resets the accumulated pulse count to zero.
+    pub fn reset(&self) {
+        self.count.store(0, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use EncoderChannel::{A, B};
+
+    /// One full quadrature cycle with A leading B, starting from both low.
+    const FORWARD: [(EncoderChannel, bool); 4] = [(A, true), (B, true), (A, false), (B, false)];
+    /// One full quadrature cycle with B leading A, starting from both low.
+    const REVERSE: [(EncoderChannel, bool); 4] = [(B, true), (A, true), (B, false), (A, false)];
+
+    fn feed(counter: &QuadratureCounter, edges: &[(EncoderChannel, bool)]) -> [i32; 4] {
+        let mut counts = [0; 4];
+        for (count, &(channel, high)) in counts.iter_mut().zip(edges) {
+            counter.on_edge(channel, high);
+            *count = counter.count();
+        }
+        counts
+    }
+
+    #[test]
+    fn counts_up_when_a_leads() {
+        let counter = QuadratureCounter::new();
+        assert_eq!(feed(&counter, &FORWARD), [1, 2, 3, 4]);
+        assert_eq!(feed(&counter, &FORWARD), [5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn counts_down_when_b_leads() {
+        let counter = QuadratureCounter::new();
+        assert_eq!(feed(&counter, &REVERSE), [-1, -2, -3, -4]);
+
+        counter.reset();
+        assert_eq!(counter.count(), 0);
+        assert_eq!(feed(&counter, &REVERSE), [-1, -2, -3, -4]);
+    }
+
+    #[test]
+    fn reversing_mid_cycle_counts_back() {
+        let counter = QuadratureCounter::new();
+        feed(&counter, &FORWARD[..2]);
+        assert_eq!(counter.count(), 2);
+
+        // Undo the last two edges in reverse order
+        counter.on_edge(B, false);
+        counter.on_edge(A, false);
+        assert_eq!(counter.count(), 0);
+    }
+
+    #[test]
+    fn repeated_levels_do_not_count() {
+        let counter = QuadratureCounter::new();
+        counter.on_edge(A, false);
+        counter.on_edge(B, false);
+        assert_eq!(counter.count(), 0);
+
+        counter.on_edge(A, true);
+        counter.on_edge(A, true);
+        counter.on_edge(A, true);
+        assert_eq!(counter.count(), 1);
+    }
+
+    #[test]
+    fn starts_from_the_given_levels() {
+        // Both high is the third state of the forward cycle
+        let counter = QuadratureCounter::with_levels(true, true);
+        counter.on_edge(A, true);
+        counter.on_edge(B, true);
+        assert_eq!(counter.count(), 0);
+
+        counter.on_edge(A, false);
+        assert_eq!(counter.count(), 1);
+    }
+}
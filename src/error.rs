@@ -1,3 +1,7 @@
+use embedded_hal::{digital, i2c, pwm};
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
 /// Error types that can occur during motor driver operations.
 /// 
 /// This enum represents all possible error conditions that can arise when
@@ -13,22 +17,25 @@
 ///     Ok(()) => println!("Speed set successfully"),
 ///     Err(MotorDriverError::InvalidSpeed) => println!("Speed value too high"),
 ///     Err(MotorDriverError::NotInitialized) => println!("Driver not initialized"),
+///     Err(MotorDriverError::GpioError(e)) => println!("{} failed on {:?}", e.operation(), e.signal()),
 ///     Err(e) => println!("Other error: {}", e),
 /// }
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum MotorDriverError {
     /// GPIO pin control operation failed.
     /// 
     /// This error occurs when setting GPIO pin states (high/low) fails,
     /// typically due to hardware issues or incorrect pin configuration.
-    GpioError,
+    /// The `HalError` names the pin and operation and keeps the cause.
+    GpioError(HalError),
     
     /// PWM channel operation failed.
     /// 
     /// This error occurs when PWM duty cycle setting fails, which can
     /// happen due to invalid duty cycle values or PWM hardware issues.
-    PwmError,
+    /// The `HalError` names the channel and keeps the cause.
+    PwmError(HalError),
     
     /// Invalid speed value provided.
     /// 
@@ -42,12 +49,25 @@ pub enum MotorDriverError {
     /// or contains conflicting settings that prevent proper operation.
     InvalidConfiguration,
     
+    /// A builder rejected its configuration.
+    /// 
+    /// Returned by `?` on a `try_build()` result and by the type-state
+    /// builder's `build_and_init()`; the `ConfigError` says which setting is
+    /// wrong.
+    Config(ConfigError),
+    
     /// Motor driver has not been initialized.
     /// 
     /// This error occurs when attempting to perform operations before
     /// calling the `initialize()` method.
     NotInitialized,
     
+    /// Operation is not allowed in the driver's current lifecycle state.
+    /// 
+    /// This error occurs, for example, when calling `set_speed()` on a
+    /// driver that has not been enabled.
+    InvalidState,
+    
     /// Hardware fault detected.
     /// 
     /// This error indicates a general hardware fault that prevents normal
@@ -78,30 +98,456 @@ pub enum MotorDriverError {
     /// exceeds the maximum safe operating voltage.
     OverVoltage,
     
+    /// Motor is driven but not turning.
+    /// 
+    /// This error occurs when stall detection sees no encoder movement
+    /// while the commanded duty cycle is above its threshold.
+    Stalled,
+    
+    /// No speed command arrived within the watchdog timeout.
+    /// 
+    /// The driver has stopped or braked the motor and stays in this state
+    /// until it receives a new command.
+    WatchdogTimeout,
+    
+    /// The emergency stop is latched.
+    /// 
+    /// The motor has been braked and disabled and cannot be driven until
+    /// the emergency stop is reset and the driver is enabled again.
+    EmergencyStop,
+    
     /// Communication with motor driver hardware failed.
     /// 
     /// This error occurs when communication protocols (I2C, SPI, UART)
-    /// fail to communicate with smart motor drivers.
-    CommunicationError,
+    /// fail to communicate with smart motor drivers or sensors.
+    /// The `HalError` names the bus operation and keeps the cause.
+    CommunicationError(HalError),
 }
 
 impl core::fmt::Display for MotorDriverError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
-            MotorDriverError::GpioError => write!(f, "GPIO control error"),
-            MotorDriverError::PwmError => write!(f, "PWM control error"),
+            MotorDriverError::GpioError(error) => write!(f, "GPIO control error: {}", error),
+            MotorDriverError::PwmError(error) => write!(f, "PWM control error: {}", error),
             MotorDriverError::InvalidSpeed => write!(f, "Invalid speed value"),
             MotorDriverError::InvalidConfiguration => write!(f, "Invalid configuration"),
+            MotorDriverError::Config(error) => write!(f, "Invalid configuration: {}", error),
             MotorDriverError::NotInitialized => write!(f, "Driver not initialized"),
+            MotorDriverError::InvalidState => write!(f, "Operation not allowed in current driver state"),
             MotorDriverError::HardwareFault => write!(f, "Hardware fault detected"),
             MotorDriverError::OverCurrent => write!(f, "Over current condition"),
             MotorDriverError::OverTemperature => write!(f, "Over temperature condition"),
             MotorDriverError::UnderVoltage => write!(f, "Under voltage condition"),
             MotorDriverError::OverVoltage => write!(f, "Over voltage condition"),
-            MotorDriverError::CommunicationError => write!(f, "Communication error"),
+            MotorDriverError::Stalled => write!(f, "Motor stalled"),
+            MotorDriverError::WatchdogTimeout => write!(f, "Command watchdog timeout"),
+            MotorDriverError::EmergencyStop => write!(f, "Emergency stop latched"),
+            MotorDriverError::CommunicationError(error) => write!(f, "Communication error: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MotorDriverError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MotorDriverError::GpioError(error)
+            | MotorDriverError::PwmError(error)
+            | MotorDriverError::CommunicationError(error) => Some(error),
+            MotorDriverError::Config(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl MotorDriverError {
+    /// Returns a `map_err` adapter for a failed GPIO access.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// enable.set_high().map_err(MotorDriverError::gpio(Signal::Enable, Operation::SetHigh))?;
+    /// ```
+    pub fn gpio<E: digital::Error + HalCause>(signal: Signal, operation: Operation) -> impl FnOnce(E) -> Self {
+        move |error| {
+            MotorDriverError::GpioError(HalError::new(signal, operation, HalErrorKind::Digital(error.kind())).with_cause(error))
+        }
+    }
+
+    /// Returns a `map_err` adapter for a failed PWM duty cycle update.
+    pub fn pwm<E: pwm::Error + HalCause>(signal: Signal) -> impl FnOnce(E) -> Self {
+        move |error| {
+            MotorDriverError::PwmError(HalError::new(signal, Operation::SetDutyCycle, HalErrorKind::Pwm(error.kind())).with_cause(error))
+        }
+    }
+
+    /// Returns a `map_err` adapter for a failed ADC conversion of a sensor input.
+    /// 
+    /// ADC errors only need `Debug`, so with `std` the cause keeps their
+    /// `Debug` output rather than the error itself.
+    pub fn adc<E: core::fmt::Debug>() -> impl FnOnce(E) -> Self {
+        move |error| {
+            let hal = HalError::new(Signal::Sensor, Operation::Read, HalErrorKind::Adc);
+            #[cfg(feature = "std")]
+            let hal = hal.with_cause(AdcError(format!("{:?}", error)));
+            #[cfg(not(feature = "std"))]
+            let _ = error;
+            MotorDriverError::CommunicationError(hal)
+        }
+    }
+
+    /// Returns a `map_err` adapter for a failed I2C transfer with a sensor.
+    pub fn i2c<E: i2c::Error + HalCause>(operation: Operation) -> impl FnOnce(E) -> Self {
+        move |error| {
+            MotorDriverError::CommunicationError(HalError::new(Signal::Sensor, operation, HalErrorKind::I2c(error.kind())).with_cause(error))
+        }
+    }
+}
+
+/// Role of the pin, channel or bus involved in a failed hardware access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signal {
+    /// First (or only) enable, sleep or standby pin.
+    Enable,
+    /// Second enable pin.
+    Enable2,
+    /// First (or only) PWM channel.
+    Pwm,
+    /// Second PWM channel.
+    Pwm2,
+    /// First (or only) direction pin, e.g. DIR or IN1.
+    Direction,
+    /// Second direction pin, e.g. IN2.
+    Direction2,
+    /// Encoder channel A.
+    EncoderA,
+    /// Encoder channel B.
+    EncoderB,
+    /// nFAULT input.
+    Fault,
+    /// Bus to a current, voltage or temperature sensor.
+    Sensor,
+}
+
+/// Hardware access that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// Driving an output pin high.
+    SetHigh,
+    /// Driving an output pin low.
+    SetLow,
+    /// Reading an input pin or a sensor register.
+    Read,
+    /// Writing a sensor register.
+    Write,
+    /// Updating a PWM duty cycle.
+    SetDutyCycle,
+}
+
+impl core::fmt::Display for Operation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Operation::SetHigh => write!(f, "set high"),
+            Operation::SetLow => write!(f, "set low"),
+            Operation::Read => write!(f, "read"),
+            Operation::Write => write!(f, "write"),
+            Operation::SetDutyCycle => write!(f, "set duty cycle"),
         }
     }
 }
 
+/// `embedded-hal` error kind reported by the failing peripheral.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HalErrorKind {
+    /// Error from a digital input or output pin.
+    Digital(digital::ErrorKind),
+    /// Error from a PWM channel.
+    Pwm(pwm::ErrorKind),
+    /// Error from an I2C bus.
+    I2c(i2c::ErrorKind),
+    /// Error from an ADC conversion; ADC traits carry no error kind.
+    Adc,
+}
+
+/// Original error a [`HalError`] can keep as its cause.
+/// 
+/// With `std` this is any `std::error::Error + Send + Sync + 'static`, so
+/// HAL error types must implement `std::error::Error`. Without `std` every
+/// type qualifies and only the error kind is kept.
 #[cfg(feature = "std")]
-impl std::error::Error for MotorDriverError {}
\ No newline at end of file
+pub trait HalCause: std::error::Error + Send + Sync + 'static {}
+
+#[cfg(feature = "std")]
+impl<T: std::error::Error + Send + Sync + 'static> HalCause for T {}
+
+/// Original error a [`HalError`] can keep as its cause.
+/// 
+/// With `std` this is any `std::error::Error + Send + Sync + 'static`, so
+/// HAL error types must implement `std::error::Error`. Without `std` every
+/// type qualifies and only the error kind is kept.
+#[cfg(not(feature = "std"))]
+pub trait HalCause {}
+
+#[cfg(not(feature = "std"))]
+impl<T: ?Sized> HalCause for T {}
+
+/// Failed hardware access with the pin and operation it happened on.
+/// 
+/// Without `std` only the `embedded-hal` error kind is kept. With `std` the
+/// original error, e.g. an `io::Error` or rppal error, is kept as well. It
+/// is returned by `source()` and included in the `Display` output.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use embedded_hal::digital::ErrorKind;
+/// use motor_driver_hal::{HalError, HalErrorKind, Operation, Signal};
+/// 
+/// let error = HalError::new(Signal::Enable, Operation::SetHigh, HalErrorKind::Digital(ErrorKind::Other));
+/// assert_eq!(error.signal(), Signal::Enable);
+/// assert_eq!(error.to_string(), "set high on Enable failed (Digital(Other))");
+/// ```
+#[derive(Debug, Clone)]
+pub struct HalError {
+    signal: Signal,
+    operation: Operation,
+    kind: HalErrorKind,
+    #[cfg(feature = "std")]
+    cause: Option<Arc<dyn std::error::Error + Send + Sync>>,
+}
+
+impl HalError {
+    /// Creates an error without a preserved cause.
+    pub fn new(signal: Signal, operation: Operation, kind: HalErrorKind) -> Self {
+        Self {
+            signal,
+            operation,
+            kind,
+            #[cfg(feature = "std")]
+            cause: None,
+        }
+    }
+
+    /// Keeps the original error (only with `std`).
+    pub fn with_cause<E: HalCause>(self, cause: E) -> Self {
+        #[cfg(feature = "std")]
+        {
+            Self {
+                cause: Some(Arc::new(cause)),
+                ..self
+            }
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            let _ = cause;
+            self
+        }
+    }
+
+    /// Returns the pin, channel or bus the access failed on.
+    pub fn signal(&self) -> Signal {
+        self.signal
+    }
+
+    /// Returns the operation that failed.
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// Returns the `embedded-hal` error kind.
+    pub fn kind(&self) -> HalErrorKind {
+        self.kind
+    }
+
+    /// Returns the original error, if it was kept.
+    ///
+    /// Unlike `source()` the result is `Send + Sync`. Downcast it to reach
+    /// the HAL's own error, e.g. `io::Error::raw_os_error()`.
+    #[cfg(feature = "std")]
+    pub fn cause(&self) -> Option<&(dyn std::error::Error + Send + Sync + 'static)> {
+        self.cause.as_deref()
+    }
+}
+
+/// Errors are equal if the same access failed the same way. With `std`
+/// the causes are compared by their `Display` output.
+impl PartialEq for HalError {
+    fn eq(&self, other: &Self) -> bool {
+        #[cfg(feature = "std")]
+        {
+            let message = |error: &Self| error.cause.as_ref().map(|cause| cause.to_string());
+            if message(self) != message(other) {
+                return false;
+            }
+        }
+        self.signal == other.signal && self.operation == other.operation && self.kind == other.kind
+    }
+}
+
+impl Eq for HalError {}
+
+impl core::fmt::Display for HalError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} on {:?} failed ({:?})", self.operation, self.signal, self.kind)?;
+        #[cfg(feature = "std")]
+        if let Some(ref cause) = self.cause {
+            write!(f, ": {}", cause)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.cause.as_deref().map(|cause| cause as &(dyn std::error::Error + 'static))
+    }
+}
+
+/// `Debug` output of an ADC error, kept as the cause of a failed conversion.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct AdcError(String);
+
+#[cfg(feature = "std")]
+impl core::fmt::Display for AdcError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AdcError {}
+
+/// Builder configuration problems reported by `try_build()`.
+/// 
+/// Converts into `MotorDriverError::Config`, so `?` works in functions
+/// returning `MotorDriverError` without losing the detail.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use motor_driver_hal::{ConfigError, MotorDriverError};
+/// 
+/// let error = ConfigError::MaxDutyExceedsChannel { max_duty: 1000, channel_max: 255 };
+/// assert_eq!(MotorDriverError::from(error), MotorDriverError::Config(error));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError {
+    /// No enable pin was supplied.
+    MissingEnable,
+    
+    /// No PWM channel was supplied.
+    MissingPwm,
+    
+    /// The configured maximum duty cycle is above what a PWM channel accepts.
+    MaxDutyExceedsChannel {
+        /// Maximum duty cycle configured on the builder (1000 if unset).
+        max_duty: u16,
+        /// `max_duty_cycle()` reported by the PWM channel.
+        channel_max: u16,
+    },
+    
+    /// The initial speed magnitude is above the maximum duty cycle.
+    InitialSpeedOutOfRange {
+        /// Configured initial speed.
+        speed: i16,
+        /// Maximum duty cycle it was checked against.
+        max_duty: u16,
+    },
+    
+    /// Pulses per revolution were set but there is no encoder to count them.
+    PprWithoutEncoder,
+    
+    /// Only one of the two quadrature encoder channels was supplied.
+    IncompleteEncoder,
+    
+    /// Stall detection was enabled but there is no encoder to watch.
+    StallWithoutEncoder,
+}
+
+impl core::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ConfigError::MissingEnable => write!(f, "Enable pin is required"),
+            ConfigError::MissingPwm => write!(f, "PWM channel is required"),
+            ConfigError::MaxDutyExceedsChannel { max_duty, channel_max } => {
+                write!(f, "Max duty {} exceeds PWM channel maximum {}", max_duty, channel_max)
+            }
+            ConfigError::InitialSpeedOutOfRange { speed, max_duty } => {
+                write!(f, "Initial speed {} exceeds max duty {}", speed, max_duty)
+            }
+            ConfigError::PprWithoutEncoder => write!(f, "PPR set without encoder"),
+            ConfigError::IncompleteEncoder => write!(f, "Both encoder channels are required"),
+            ConfigError::StallWithoutEncoder => write!(f, "Stall detection set without encoder"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConfigError {}
+
+impl From<ConfigError> for MotorDriverError {
+    fn from(error: ConfigError) -> Self {
+        MotorDriverError::Config(error)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    /// GPIO error wrapping the `io::Error` of a failed line access.
+    #[derive(Debug)]
+    struct PinError(std::io::Error);
+
+    impl core::fmt::Display for PinError {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            self.0.fmt(f)
+        }
+    }
+
+    impl Error for PinError {}
+
+    impl digital::Error for PinError {
+        fn kind(&self) -> digital::ErrorKind {
+            digital::ErrorKind::Other
+        }
+    }
+
+    #[test]
+    fn hal_errors_keep_their_cause_and_chain_as_source() {
+        let busy = PinError(std::io::Error::from_raw_os_error(16));
+        let message = busy.to_string();
+        let error = MotorDriverError::gpio(Signal::Enable2, Operation::SetLow)(busy);
+        let MotorDriverError::GpioError(ref hal) = error else {
+            panic!("unexpected {error:?}");
+        };
+        assert_eq!((hal.signal(), hal.operation()), (Signal::Enable2, Operation::SetLow));
+        let cause = hal.cause().and_then(|cause| cause.downcast_ref::<PinError>()).unwrap();
+        assert_eq!(cause.0.raw_os_error(), Some(16));
+
+        let source = error.source().unwrap();
+        assert_eq!(source.to_string(), format!("set low on Enable2 failed (Digital(Other)): {message}"));
+        assert!(source.source().unwrap().is::<PinError>());
+        assert_eq!(error.clone(), error);
+    }
+
+    #[test]
+    fn adc_errors_keep_their_debug_output() {
+        let MotorDriverError::CommunicationError(hal) = MotorDriverError::adc()("timeout") else {
+            panic!("expected a communication error");
+        };
+        assert_eq!(hal.kind(), HalErrorKind::Adc);
+        assert_eq!(hal.cause().unwrap().to_string(), "\"timeout\"");
+    }
+
+    #[test]
+    fn config_errors_chain_as_source() {
+        let error = MotorDriverError::from(ConfigError::MissingPwm);
+        assert_eq!(error.to_string(), "Invalid configuration: PWM channel is required");
+        assert_eq!(error.source().unwrap().to_string(), "PWM channel is required");
+        assert!(MotorDriverError::Stalled.source().is_none());
+    }
+}
@@ -0,0 +1,219 @@
+//! Latching emergency stop shared by any number of motors.
+//!
+//! An [`EStop`] is a cheap, cloneable handle. Motors registered with
+//! `register()` are braked and disabled from the thread that calls
+//! `trigger()`. Registered drivers and drivers built `with_estop()` refuse
+//! `set_speed()`, `enable()` and `update()` while it is latched. An optional hardware
+//! input is read by `poll()` and by those driver calls. The stop stays
+//! latched until `reset()`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use motor_driver_hal::estop::EStop;
+//! use motor_driver_hal::NoEncoder;
+//!
+//! let estop = EStop::new();
+//! let handler = estop.clone();
+//! // e.g. from a Ctrl+C handler or a watchdog thread
+//! handler.trigger();
+//!
+//! assert!(estop.is_triggered());
+//! estop.reset().unwrap();
+//! assert!(!estop.is_triggered());
+//!
+//! // An E-stop contact that reads high while pressed
+//! let estop = EStop::new().with_input(NoEncoder, false);
+//! assert!(!estop.poll());
+//! ```
+
+use crate::{MotorDriver, MotorDriverError};
+use core::sync::atomic::{AtomicBool, Ordering};
+use embedded_hal::digital::InputPin;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Motor driver that an [`EStop`] can be attached to.
+///
+/// Implemented by the drivers that support `with_estop()`, so that
+/// `EStop::register()` can attach itself and a registered motor cannot be
+/// enabled again before `reset()`.
+pub trait EStopTarget: MotorDriver {
+    /// Attaches `estop`, replacing any stop attached with `with_estop()`.
+    fn attach_estop(&mut self, estop: EStop);
+}
+
+/// Stop action applied to a registered motor, independent of its error type.
+trait Emergency: Send {
+    fn emergency_stop(&mut self);
+}
+
+impl<M: MotorDriver + Send> Emergency for M {
+    fn emergency_stop(&mut self) {
+        // Best effort: a failing motor must not keep the others running
+        let _ = self.brake();
+        let _ = self.disable();
+    }
+}
+
+type Input = Box<dyn FnMut() -> bool + Send>;
+
+struct Shared {
+    triggered: AtomicBool,
+    motors: Mutex<Vec<Arc<Mutex<dyn Emergency>>>>,
+    input: Mutex<Option<Input>>,
+}
+
+impl Shared {
+    fn stop_registered(&self) {
+        for motor in lock(&self.motors).iter() {
+            lock(motor).emergency_stop();
+        }
+    }
+}
+
+/// Handle to a latching emergency stop.
+///
+/// Clones share the same latch, registry and input.
+#[derive(Clone)]
+pub struct EStop {
+    shared: Arc<Shared>,
+}
+
+impl EStop {
+    /// Creates a released emergency stop without motors or input.
+    pub fn new() -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                triggered: AtomicBool::new(false),
+                motors: Mutex::new(Vec::new()),
+                input: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Attaches a hardware E-stop input read by `poll()` and `reset()`.
+    ///
+    /// A read error counts as pressed, so a faulty input stops the motors.
+    ///
+    /// # Arguments
+    ///
+    /// * `pin` - E-stop switch input
+    /// * `active_low` - `true` if the input reads low while pressed
+    pub fn with_input<P>(self, mut pin: P, active_low: bool) -> Self
+    where
+        P: InputPin + Send + 'static,
+    {
+        let pressed = move || match pin.is_high() {
+            Ok(high) => high != active_low,
+            Err(_) => true,
+        };
+        *lock(&self.shared.input) = Some(Box::new(pressed));
+        self
+    }
+
+    /// Registers a motor to be braked and disabled when the stop triggers.
+    ///
+    /// The stop is also attached to the driver, as with `with_estop()`, so
+    /// it refuses to run until `reset()`. If the stop is already latched the
+    /// motor is stopped immediately. `trigger()` locks every registered
+    /// motor, so do not call it while holding one of their locks.
+    pub fn register<M>(&self, motor: Arc<Mutex<M>>)
+    where
+        M: EStopTarget + Send + 'static,
+    {
+        {
+            let mut driver = lock(&motor);
+            driver.attach_estop(self.clone());
+            if self.is_triggered() {
+                driver.emergency_stop();
+            }
+        }
+        lock(&self.shared.motors).push(motor);
+    }
+
+    /// Latches the stop and brakes and disables every registered motor.
+    pub fn trigger(&self) {
+        self.shared.triggered.store(true, Ordering::SeqCst);
+        self.shared.stop_registered();
+    }
+
+    /// Returns `true` while the stop is latched.
+    pub fn is_triggered(&self) -> bool {
+        self.shared.triggered.load(Ordering::SeqCst)
+    }
+
+    /// Reads the hardware input and triggers the stop if it is pressed.
+    ///
+    /// Call it periodically from a control loop or a dedicated thread.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the stop is latched
+    pub fn poll(&self) -> bool {
+        if !self.is_triggered() && self.input_pressed() {
+            self.trigger();
+        }
+        self.is_triggered()
+    }
+
+    /// `poll()` for a driver that may itself be registered and locked by the
+    /// calling thread.
+    ///
+    /// Locking the registered motors here could deadlock on the caller, so
+    /// they are stopped from a helper thread instead.
+    pub(crate) fn poll_from_driver(&self) -> bool {
+        // Only the caller that latches the stop spawns the helper thread
+        if !self.is_triggered()
+            && self.input_pressed()
+            && self
+                .shared
+                .triggered
+                .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok()
+        {
+            let shared = self.shared.clone();
+            std::thread::spawn(move || shared.stop_registered());
+        }
+        self.is_triggered()
+    }
+
+    /// Releases the latch. Motors stay disabled until they are enabled again.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EmergencyStop` if the hardware input is still pressed.
+    pub fn reset(&self) -> Result<(), MotorDriverError> {
+        if self.input_pressed() {
+            return Err(MotorDriverError::EmergencyStop);
+        }
+        self.shared.triggered.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn input_pressed(&self) -> bool {
+        match lock(&self.shared.input).as_mut() {
+            Some(pressed) => pressed(),
+            None => false,
+        }
+    }
+}
+
+impl Default for EStop {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Debug for EStop {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EStop")
+            .field("triggered", &self.is_triggered())
+            .field("motors", &lock(&self.shared.motors).len())
+            .finish()
+    }
+}
+
+/// An emergency stop must keep working after a panic in another thread.
+fn lock<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
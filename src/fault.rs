@@ -0,0 +1,66 @@
+//! Fault status reported by motor drivers.
+
+use crate::MotorDriverError;
+
+bitflags::bitflags! {
+    /// Fault conditions latched by a motor driver.
+    ///
+    /// Drivers with a plain nFAULT input cannot tell faults apart and report
+    /// `GENERIC`. Smart drivers that read a status register set the specific
+    /// flags instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use motor_driver_hal::FaultStatus;
+    ///
+    /// let status = FaultStatus::OVERCURRENT | FaultStatus::THERMAL;
+    /// assert!(status.contains(FaultStatus::THERMAL));
+    /// assert_eq!(FaultStatus::empty().bits(), 0);
+    /// ```
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct FaultStatus: u8 {
+        /// Output current exceeded the overcurrent protection threshold.
+        const OVERCURRENT = 1 << 0;
+        /// Die temperature exceeded the thermal shutdown threshold.
+        const THERMAL = 1 << 1;
+        /// Supply voltage dropped below the undervoltage lockout threshold.
+        const UNDERVOLTAGE = 1 << 2;
+        /// A fault was signalled without further detail, e.g. by an nFAULT pin.
+        const GENERIC = 1 << 3;
+        /// Supply voltage rose above the overvoltage threshold.
+        const OVERVOLTAGE = 1 << 4;
+        /// Motor was driven but the encoder did not move.
+        const STALL = 1 << 5;
+    }
+}
+
+impl FaultStatus {
+    /// Returns the error matching the most specific latched fault, or `None` if clear.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use motor_driver_hal::{FaultStatus, MotorDriverError};
+    ///
+    /// assert_eq!(FaultStatus::THERMAL.error(), Some(MotorDriverError::OverTemperature));
+    /// assert_eq!(FaultStatus::empty().error(), None);
+    /// ```
+    pub fn error(self) -> Option<MotorDriverError> {
+        if self.contains(FaultStatus::OVERCURRENT) {
+            Some(MotorDriverError::OverCurrent)
+        } else if self.contains(FaultStatus::THERMAL) {
+            Some(MotorDriverError::OverTemperature)
+        } else if self.contains(FaultStatus::UNDERVOLTAGE) {
+            Some(MotorDriverError::UnderVoltage)
+        } else if self.contains(FaultStatus::OVERVOLTAGE) {
+            Some(MotorDriverError::OverVoltage)
+        } else if self.contains(FaultStatus::STALL) {
+            Some(MotorDriverError::Stalled)
+        } else if self.is_empty() {
+            None
+        } else {
+            Some(MotorDriverError::HardwareFault)
+        }
+    }
+}
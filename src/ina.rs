@@ -0,0 +1,537 @@
+//! TI INA219/INA226 power monitors as current and voltage sensors.
+//!
+//! [`Ina2xx`] talks to the monitor over any `embedded_hal::i2c::I2c` bus,
+//! programs the calibration register from the shunt resistance and the
+//! largest expected current, and implements [`PowerSense`] so it can be
+//! attached to a driver with `with_current_sense()`. `get_current()` then
+//! reports the shunt current and `get_voltage()` the bus voltage, and
+//! optional limits latch overcurrent, undervoltage and overvoltage faults.
+//!
+//! # Example
+//!
+//! ```rust
+//! use embedded_hal::i2c::{ErrorType, I2c, Operation};
+//! use motor_driver_hal::ina::Ina2xx;
+//!
+//! /// Register file of an INA226 reading 12.0 V on the bus and 1.0 A.
+//! struct MockBus {
+//!     registers: [u16; 6],
+//!     pointer: usize,
+//! }
+//!
+//! impl ErrorType for MockBus {
+//!     type Error = core::convert::Infallible;
+//! }
+//!
+//! impl I2c for MockBus {
+//!     fn transaction(&mut self, _address: u8, operations: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+//!         for operation in operations {
+//!             match operation {
+//!                 Operation::Write(bytes) => {
+//!                     self.pointer = bytes[0] as usize;
+//!                     if let [_, msb, lsb] = bytes[..] {
+//!                         self.registers[self.pointer] = u16::from_be_bytes([msb, lsb]);
+//!                     }
+//!                 }
+//!                 Operation::Read(buffer) => {
+//!                     buffer.copy_from_slice(&self.registers[self.pointer].to_be_bytes());
+//!                 }
+//!             }
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let mut registers = [0; 6];
+//! registers[0x02] = 9600; // 12.0 V at 1.25 mV/bit
+//! registers[0x04] = 10_000; // 1.0 A at 100 uA/bit
+//! let bus = MockBus { registers, pointer: 0 };
+//!
+//! // 10 mOhm shunt, up to 3.2768 A: 100 uA current LSB
+//! let mut ina = Ina2xx::ina226(bus, 0x40, 0.01, 3.2768).unwrap();
+//! ina.init().unwrap();
+//! assert_eq!(ina.calibration(), 5120);
+//! assert!((ina.read_bus_voltage().unwrap() - 12.0).abs() < 1e-4);
+//! assert!((ina.read_current().unwrap() - 1.0).abs() < 1e-4);
+//!
+//! let bus = ina.release();
+//! assert_eq!(bus.registers[0x05], 5120);
+//! ```
+
+use crate::current::PowerSense;
+use crate::{FaultStatus, HalCause, MotorDriverError, Operation};
+use embedded_hal::i2c::I2c;
+
+const REG_CONFIG: u8 = 0x00;
+const REG_SHUNT_VOLTAGE: u8 = 0x01;
+const REG_BUS_VOLTAGE: u8 = 0x02;
+const REG_CURRENT: u8 = 0x04;
+const REG_CALIBRATION: u8 = 0x05;
+
+/// Supported INA power monitor models.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ina2xxModel {
+    /// INA219: 26 V bus, 12-bit ADC, 4 mV bus LSB and 10 uV shunt LSB.
+    Ina219,
+    /// INA226: 36 V bus, 16-bit ADC, 1.25 mV bus LSB and 2.5 uV shunt LSB.
+    Ina226,
+}
+
+impl Ina2xxModel {
+    /// Power-on configuration: continuous shunt and bus conversions.
+    fn config(self) -> u16 {
+        match self {
+            // 32 V range, +-320 mV PGA, 12-bit conversions
+            Ina2xxModel::Ina219 => 0x399F,
+            // 1 sample averaging, 1.1 ms conversions
+            Ina2xxModel::Ina226 => 0x4127,
+        }
+    }
+
+    /// Constant in the calibration equation `cal = k / (current_lsb * r_shunt)`.
+    fn calibration_constant(self) -> f32 {
+        match self {
+            Ina2xxModel::Ina219 => 0.04096,
+            Ina2xxModel::Ina226 => 0.00512,
+        }
+    }
+
+    fn bus_voltage(self, raw: u16) -> f32 {
+        match self {
+            Ina2xxModel::Ina219 => (raw >> 3) as f32 * 0.004,
+            Ina2xxModel::Ina226 => raw as f32 * 0.001_25,
+        }
+    }
+
+    fn shunt_voltage(self, raw: i16) -> f32 {
+        match self {
+            Ina2xxModel::Ina219 => raw as f32 * 10e-6,
+            Ina2xxModel::Ina226 => raw as f32 * 2.5e-6,
+        }
+    }
+}
+
+/// INA219 or INA226 power monitor on an I2C bus.
+///
+/// Construction only computes the calibration; `init()` writes the
+/// configuration and calibration registers and is called by the driver's
+/// `initialize()` when the monitor is attached to one.
+#[derive(Debug)]
+pub struct Ina2xx<I2C> {
+    i2c: I2C,
+    address: u8,
+    model: Ina2xxModel,
+    current_lsb: f32,
+    calibration: u16,
+    current_limit: Option<f32>,
+    voltage_limits: Option<(f32, f32)>,
+    current: Option<f32>,
+    voltage: Option<f32>,
+}
+
+impl<I2C: I2c<Error: HalCause>> Ina2xx<I2C> {
+    /// Creates an INA219 driver.
+    ///
+    /// # Arguments
+    ///
+    /// * `i2c` - Bus the monitor is connected to
+    /// * `address` - 7-bit I2C address, 0x40 with A0 and A1 grounded
+    /// * `shunt_ohms` - Shunt resistance in ohms
+    /// * `max_current` - Largest expected current in amps, sets the current resolution
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if the values are not positive or give a
+    /// calibration that does not fit the register.
+    pub fn ina219(i2c: I2C, address: u8, shunt_ohms: f32, max_current: f32) -> Result<Self, MotorDriverError> {
+        Self::new(i2c, address, Ina2xxModel::Ina219, shunt_ohms, max_current)
+    }
+
+    /// Creates an INA226 driver.
+    ///
+    /// # Arguments
+    ///
+    /// * `i2c` - Bus the monitor is connected to
+    /// * `address` - 7-bit I2C address, 0x40 with A0 and A1 grounded
+    /// * `shunt_ohms` - Shunt resistance in ohms
+    /// * `max_current` - Largest expected current in amps, sets the current resolution
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if the values are not positive or give a
+    /// calibration that does not fit the register.
+    pub fn ina226(i2c: I2C, address: u8, shunt_ohms: f32, max_current: f32) -> Result<Self, MotorDriverError> {
+        Self::new(i2c, address, Ina2xxModel::Ina226, shunt_ohms, max_current)
+    }
+
+    /// Creates a driver for either model.
+    ///
+    /// The current LSB is `max_current / 32768` and the calibration register
+    /// follows the datasheet equation for the model.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if the values are not positive or give a
+    /// calibration that does not fit the register.
+    pub fn new(i2c: I2C, address: u8, model: Ina2xxModel, shunt_ohms: f32, max_current: f32) -> Result<Self, MotorDriverError> {
+        if !(shunt_ohms > 0.0 && max_current > 0.0) {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+
+        let current_lsb = max_current / 32768.0;
+        // Rounded rather than truncated so float error cannot drop a whole count
+        let calibration = (model.calibration_constant() / (current_lsb * shunt_ohms) + 0.5) as u32;
+        if calibration == 0 || calibration > u16::MAX as u32 {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+
+        Ok(Self {
+            i2c,
+            address,
+            model,
+            current_lsb,
+            calibration: calibration as u16,
+            current_limit: None,
+            voltage_limits: None,
+            current: None,
+            voltage: None,
+        })
+    }
+
+    /// Sets the current magnitude above which `sample()` reports an overcurrent fault.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if the limit is not positive.
+    pub fn with_current_limit(mut self, amps: f32) -> Result<Self, MotorDriverError> {
+        if !(amps > 0.0 && amps.is_finite()) {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+        self.current_limit = Some(amps);
+        Ok(self)
+    }
+
+    /// Sets the bus voltage window outside which `sample()` reports under- or overvoltage.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if `min` is negative or not below `max`.
+    pub fn with_voltage_limits(mut self, min: f32, max: f32) -> Result<Self, MotorDriverError> {
+        if !(min >= 0.0 && min < max) {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+        self.voltage_limits = Some((min, max));
+        Ok(self)
+    }
+
+    /// Returns the monitor model.
+    pub fn model(&self) -> Ina2xxModel {
+        self.model
+    }
+
+    /// Returns the value written to the calibration register.
+    pub fn calibration(&self) -> u16 {
+        self.calibration
+    }
+
+    /// Returns the current resolution in amps per bit.
+    pub fn current_lsb(&self) -> f32 {
+        self.current_lsb
+    }
+
+    /// Writes the configuration and calibration registers.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CommunicationError` if the bus transfer fails.
+    pub fn init(&mut self) -> Result<(), MotorDriverError> {
+        self.write_register(REG_CONFIG, self.model.config())?;
+        self.write_register(REG_CALIBRATION, self.calibration)
+    }
+
+    /// Reads the shunt current in amps.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CommunicationError` if the bus transfer fails.
+    pub fn read_current(&mut self) -> Result<f32, MotorDriverError> {
+        let raw = self.read_register(REG_CURRENT)? as i16;
+        Ok(raw as f32 * self.current_lsb)
+    }
+
+    /// Reads the bus voltage in volts.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CommunicationError` if the bus transfer fails.
+    pub fn read_bus_voltage(&mut self) -> Result<f32, MotorDriverError> {
+        let raw = self.read_register(REG_BUS_VOLTAGE)?;
+        Ok(self.model.bus_voltage(raw))
+    }
+
+    /// Reads the voltage across the shunt in volts.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CommunicationError` if the bus transfer fails.
+    pub fn read_shunt_voltage(&mut self) -> Result<f32, MotorDriverError> {
+        let raw = self.read_register(REG_SHUNT_VOLTAGE)? as i16;
+        Ok(self.model.shunt_voltage(raw))
+    }
+
+    /// Releases the I2C bus.
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+
+    fn write_register(&mut self, register: u8, value: u16) -> Result<(), MotorDriverError> {
+        let [msb, lsb] = value.to_be_bytes();
+        self.i2c
+            .write(self.address, &[register, msb, lsb])
+            .map_err(MotorDriverError::i2c(Operation::Write))
+    }
+
+    fn read_register(&mut self, register: u8) -> Result<u16, MotorDriverError> {
+        let mut buffer = [0; 2];
+        self.i2c
+            .write_read(self.address, &[register], &mut buffer)
+            .map_err(MotorDriverError::i2c(Operation::Read))?;
+        Ok(u16::from_be_bytes(buffer))
+    }
+}
+
+impl<I2C: I2c<Error: HalCause>> PowerSense for Ina2xx<I2C> {
+    fn init(&mut self) -> Result<(), MotorDriverError> {
+        Ina2xx::init(self)
+    }
+
+    fn sample(&mut self) -> Result<FaultStatus, MotorDriverError> {
+        let current = self.read_current()?;
+        let voltage = self.read_bus_voltage()?;
+        self.current = Some(current);
+        self.voltage = Some(voltage);
+
+        let mut faults = FaultStatus::empty();
+        if let Some(limit) = self.current_limit {
+            if current.abs() > limit {
+                faults |= FaultStatus::OVERCURRENT;
+            }
+        }
+        if let Some((min, max)) = self.voltage_limits {
+            if voltage < min {
+                faults |= FaultStatus::UNDERVOLTAGE;
+            } else if voltage > max {
+                faults |= FaultStatus::OVERVOLTAGE;
+            }
+        }
+        Ok(faults)
+    }
+
+    fn current(&self) -> Option<f32> {
+        self.current
+    }
+
+    fn voltage(&self) -> Option<f32> {
+        self.voltage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::driver::HBridgeMotorDriverBuilder;
+    use crate::mock::{Log, MockPin, MockPwm};
+    use crate::{MotorDriver, NoEncoder, Signal};
+    use core::cell::RefCell;
+    use embedded_hal::i2c::{ErrorKind, ErrorType, Operation as I2cOperation};
+
+    /// Register file of a monitor, shared with the test through a `RefCell`.
+    #[derive(Default)]
+    struct Registers {
+        values: [u16; 6],
+        pointer: usize,
+        address: u8,
+        fail: Option<ErrorKind>,
+    }
+
+    struct Bus<'a>(&'a RefCell<Registers>);
+
+    /// Bus failure reporting the `embedded-hal` kind it was set up with.
+    #[derive(Debug, Clone, Copy)]
+    struct BusError(ErrorKind);
+
+    impl core::fmt::Display for BusError {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            self.0.fmt(f)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for BusError {}
+
+    impl embedded_hal::i2c::Error for BusError {
+        fn kind(&self) -> ErrorKind {
+            self.0
+        }
+    }
+
+    impl ErrorType for Bus<'_> {
+        type Error = BusError;
+    }
+
+    impl I2c for Bus<'_> {
+        fn transaction(&mut self, address: u8, operations: &mut [I2cOperation<'_>]) -> Result<(), Self::Error> {
+            let mut registers = self.0.borrow_mut();
+            if let Some(kind) = registers.fail {
+                return Err(BusError(kind));
+            }
+            registers.address = address;
+            for operation in operations {
+                match operation {
+                    I2cOperation::Write(bytes) => {
+                        registers.pointer = bytes[0] as usize;
+                        if let [_, msb, lsb] = bytes[..] {
+                            let pointer = registers.pointer;
+                            registers.values[pointer] = u16::from_be_bytes([msb, lsb]);
+                        }
+                    }
+                    I2cOperation::Read(buffer) => {
+                        buffer.copy_from_slice(&registers.values[registers.pointer].to_be_bytes());
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn set(registers: &RefCell<Registers>, register: u8, value: u16) {
+        registers.borrow_mut().values[register as usize] = value;
+    }
+
+    #[test]
+    fn init_writes_config_and_calibration() {
+        let registers = RefCell::new(Registers::default());
+        // 100 mOhm shunt and 100 uA per bit
+        let mut ina = Ina2xx::ina219(Bus(&registers), 0x41, 0.1, 3.2768).unwrap();
+        ina.init().unwrap();
+        assert_eq!(ina.calibration(), 4096);
+        assert_eq!(registers.borrow().values[REG_CONFIG as usize], 0x399F);
+        assert_eq!(registers.borrow().values[REG_CALIBRATION as usize], 4096);
+        assert_eq!(registers.borrow().address, 0x41);
+
+        let registers = RefCell::new(Registers::default());
+        let mut ina = Ina2xx::ina226(Bus(&registers), 0x40, 0.01, 3.2768).unwrap();
+        ina.init().unwrap();
+        assert_eq!(registers.borrow().values[REG_CONFIG as usize], 0x4127);
+        assert_eq!(registers.borrow().values[REG_CALIBRATION as usize], 5120);
+    }
+
+    #[test]
+    fn rejects_calibrations_that_do_not_fit() {
+        let registers = RefCell::new(Registers::default());
+        assert!(matches!(Ina2xx::ina226(Bus(&registers), 0x40, 0.0, 1.0), Err(MotorDriverError::InvalidConfiguration)));
+        assert!(matches!(Ina2xx::ina226(Bus(&registers), 0x40, 1e-4, 1e-3), Err(MotorDriverError::InvalidConfiguration)));
+    }
+
+    #[test]
+    fn ina219_bus_voltage_skips_the_status_bits() {
+        let registers = RefCell::new(Registers::default());
+        let mut ina = Ina2xx::ina219(Bus(&registers), 0x40, 0.1, 3.2768).unwrap();
+
+        // 12 V is 3000 counts of 4 mV above the CNVR and OVF flags
+        set(&registers, REG_BUS_VOLTAGE, (3000 << 3) | 0b011);
+        assert!((ina.read_bus_voltage().unwrap() - 12.0).abs() < 1e-4);
+
+        set(&registers, REG_SHUNT_VOLTAGE, (-1000i16) as u16);
+        assert!((ina.read_shunt_voltage().unwrap() + 0.01).abs() < 1e-6);
+    }
+
+    #[test]
+    fn reads_negative_current() {
+        let registers = RefCell::new(Registers::default());
+        let mut ina = Ina2xx::ina226(Bus(&registers), 0x40, 0.01, 3.2768).unwrap();
+
+        set(&registers, REG_CURRENT, (-5000i16) as u16);
+        assert!((ina.read_current().unwrap() + 0.5).abs() < 1e-4);
+        set(&registers, REG_CURRENT, 0x8000);
+        assert!((ina.read_current().unwrap() + 3.2768).abs() < 1e-4);
+    }
+
+    #[test]
+    fn sample_reports_limit_faults() {
+        let registers = RefCell::new(Registers::default());
+        let mut ina = Ina2xx::ina226(Bus(&registers), 0x40, 0.01, 3.2768)
+            .unwrap()
+            .with_current_limit(2.0)
+            .unwrap()
+            .with_voltage_limits(10.0, 14.0)
+            .unwrap();
+
+        set(&registers, REG_CURRENT, 10_000);
+        set(&registers, REG_BUS_VOLTAGE, 9600);
+        assert_eq!(ina.sample(), Ok(FaultStatus::empty()));
+        assert_eq!((ina.current(), ina.voltage()), (Some(1.0), Some(12.0)));
+
+        set(&registers, REG_CURRENT, (-25_000i16) as u16);
+        assert_eq!(ina.sample(), Ok(FaultStatus::OVERCURRENT));
+
+        set(&registers, REG_CURRENT, 0);
+        set(&registers, REG_BUS_VOLTAGE, 7000);
+        assert_eq!(ina.sample(), Ok(FaultStatus::UNDERVOLTAGE));
+        set(&registers, REG_BUS_VOLTAGE, 12_000);
+        assert_eq!(ina.sample(), Ok(FaultStatus::OVERVOLTAGE));
+    }
+
+    #[test]
+    fn driver_latches_sampled_faults() {
+        let registers = RefCell::new(Registers::default());
+        set(&registers, REG_BUS_VOLTAGE, 9600);
+        let ina = Ina2xx::ina226(Bus(&registers), 0x40, 0.01, 3.2768)
+            .unwrap()
+            .with_current_limit(2.0)
+            .unwrap()
+            .with_voltage_limits(10.0, 14.0)
+            .unwrap();
+        let log = Log::new();
+        let mut motor = HBridgeMotorDriverBuilder::<_, MockPin, _, MockPwm, NoEncoder, NoEncoder>::new()
+            .with_enable(log.pin("en"))
+            .with_pwm(log.pwm("pwm"))
+            .with_current_sense(ina)
+            .with_clock(MockClock::new())
+            .build_and_init()
+            .unwrap();
+        assert_eq!(registers.borrow().values[REG_CALIBRATION as usize], 5120);
+        motor.enable().unwrap();
+
+        set(&registers, REG_CURRENT, 30_000);
+        set(&registers, REG_BUS_VOLTAGE, 7000);
+        assert_eq!(motor.check_fault(), Ok(FaultStatus::OVERCURRENT | FaultStatus::UNDERVOLTAGE));
+
+        // Back within limits, the faults stay latched until cleared
+        set(&registers, REG_CURRENT, 0);
+        set(&registers, REG_BUS_VOLTAGE, 12_000);
+        assert_eq!(motor.check_fault(), Ok(FaultStatus::OVERCURRENT | FaultStatus::UNDERVOLTAGE | FaultStatus::OVERVOLTAGE));
+        set(&registers, REG_BUS_VOLTAGE, 9600);
+        assert_eq!(motor.clear_fault(), Ok(FaultStatus::empty()));
+    }
+
+    #[test]
+    fn bus_errors_report_the_operation() {
+        let registers = RefCell::new(Registers::default());
+        let mut ina = Ina2xx::ina219(Bus(&registers), 0x40, 0.1, 3.2768).unwrap();
+        registers.borrow_mut().fail = Some(ErrorKind::Bus);
+
+        let Err(MotorDriverError::CommunicationError(error)) = ina.init() else {
+            panic!("init should fail");
+        };
+        assert_eq!((error.signal(), error.operation()), (Signal::Sensor, Operation::Write));
+        assert_eq!(error.kind(), crate::HalErrorKind::I2c(ErrorKind::Bus));
+
+        let Err(MotorDriverError::CommunicationError(error)) = ina.read_current() else {
+            panic!("read should fail");
+        };
+        assert_eq!(error.operation(), Operation::Read);
+        assert!(matches!(ina.sample(), Err(MotorDriverError::CommunicationError(_))));
+    }
+}
@@ -9,8 +9,28 @@
 //!
 //! - H-bridge motor control with single or dual PWM channels
 //! - Single or dual enable pin configurations
+//! - Fast or slow current decay for dual-PWM bridges
+//! - Sign-magnitude (PWM + direction pin) and locked-antiphase drive modes
+//! - DIR or IN1/IN2 direction outputs for single-PWM bridges, with optional dead time
+//! - Typed drivers with datasheet truth tables for DRV8833, DRV8871, TB6612FNG, L298N and BTS7960
+//! - Active-low nFAULT input with latched, structured fault status
+//! - Shunt or sense-pin current measurement with filtering and overcurrent detection
+//! - Current fold-back and peak-current trip evaluated in `update()`
+//! - INA219/INA226 I2C power monitors for current and supply voltage (`ina2xx` feature)
 //! - Encoder support for position and speed feedback
+//! - Encoder velocity estimation in pulses/s, RPM and rad/s
+//! - Stall detection from encoder feedback, with optional power cut
+//! - Command watchdog that stops or brakes the motor when commands stop arriving
+//! - Latching emergency stop shared by any number of motors, with optional hardware input
+//! - Closed-loop velocity and position control with a PID controller
+//! - Trapezoidal and S-curve motion profiles for smooth point-to-point moves
+//! - Acceleration and deceleration limiting of commanded duty cycles
 //! - Cross-platform support (Raspberry Pi, Linux embedded systems)
+//! - Simulated motor backend for hardware-free testing (`sim` feature)
+//! - Pluggable monotonic clock, with a mock clock for deterministic tests
+//! - Explicit driver lifecycle states with checked transitions
+//! - Type-state builder and driver handles that reject misuse at compile time
+//! - Hardware errors that name the failing pin and operation and keep the cause
 //! - Builder pattern for easy configuration
 //! - No-std compatible (when std feature is disabled)
 //!
@@ -34,12 +54,49 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod chip;
+pub mod clock;
+pub mod control;
+pub mod current;
 pub mod driver;
+#[cfg(all(target_has_atomic = "8", target_has_atomic = "32"))]
+pub mod encoder;
 pub mod error;
+#[cfg(feature = "std")]
+pub mod estop;
+pub mod fault;
+#[cfg(feature = "ina2xx")]
+pub mod ina;
+#[cfg(test)]
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+mod mock;
+pub mod profile;
+pub mod ramp;
+#[cfg(feature = "sim")]
+pub mod sim;
+pub mod stall;
+pub mod state;
+pub mod typestate;
+pub mod velocity;
+pub mod watchdog;
 pub mod wrapper;
 
-pub use driver::{HBridgeMotorDriver, NoEncoder};
-pub use error::MotorDriverError;
+pub use clock::{Clock, MockClock, NoClock};
+#[cfg(feature = "std")]
+pub use clock::StdClock;
+pub use current::{AdcChannel, CurrentLimit, CurrentScaling, CurrentSense, NoSense, PowerSense};
+pub use chip::{Bts7960, ChipBridge, ChipDriver, Drv8833, Drv8871, L298n, Tb6612fng};
+pub use driver::{DecayMode, DriveMode, HBridgeMotorDriver, NoEncoder, NoPin};
+pub use error::{ConfigError, HalCause, HalError, HalErrorKind, MotorDriverError, Operation, Signal};
+#[cfg(feature = "std")]
+pub use estop::{EStop, EStopTarget};
+pub use fault::FaultStatus;
+pub use ramp::RampLimiter;
+pub use stall::StallDetector;
+pub use state::DriverState;
+pub use typestate::{Enabled, Initialized, TypedBuilder};
+pub use velocity::{Velocity, VelocityEstimation};
+pub use watchdog::{Watchdog, WatchdogAction};
 pub use wrapper::{MotorDriverWrapper, MotorDriverBuilder, EnablePins, PwmChannels, MotorDirection};
 
 #[cfg(feature = "rppal")]
@@ -103,7 +160,8 @@ pub trait MotorDriver {
     /// 
     /// # Errors
     /// 
-    /// Returns error if driver is not initialized or speed exceeds maximum duty cycle.
+    /// Returns error if driver is not initialized or not enabled, speed exceeds
+    /// maximum duty cycle or a fault is latched.
     /// 
     /// # Example
     /// 
@@ -126,7 +184,8 @@ pub trait MotorDriver {
     /// 
     /// # Errors
     /// 
-    /// Returns error if driver is not initialized.
+    /// Returns error if driver is not initialized, or if it is not enabled
+    /// (`MotorDriverError::InvalidState` for drivers with a `DriverState`).
     /// 
     /// # Example
     /// 
@@ -219,25 +278,25 @@ pub trait MotorDriver {
     /// ```
     fn disable(&mut self) -> Result<(), Self::Error>;
     
-    /// Checks if the encoder pulse count matches the target position.
+    /// Checks that encoder feedback is configured and readable.
     /// 
-    /// This method validates that the motor has reached the desired position
-    /// based on encoder feedback and configured pulses per revolution (PPR).
+    /// This method validates that pulses per revolution (PPR) are configured
+    /// and updates the encoder state once. Position targets are tracked by
+    /// `PositionController` instead.
     /// 
     /// # Returns
     /// 
-    /// * `Ok(())` if position matches target
-    /// * `Err(Self::Error)` if position doesn't match or encoder error occurs
+    /// * `Ok(())` if PPR is configured and the encoder was read successfully
+    /// * `Err(Self::Error)` if PPR is not configured or encoder reading fails
     /// 
     /// # Errors
     /// 
-    /// Returns error if PPR is not configured or encoder reading fails.
+    /// Returns error if PPR is not configured, no encoder is present or encoder reading fails.
     /// 
     /// # Example
     /// 
     /// ```rust
-    /// motor.set_target_pulse(1000);
-    /// motor.check_ppr()?; // Verify position
+    /// motor.check_ppr()?; // Verify encoder feedback before closing a position loop
     /// ```
     fn check_ppr(&mut self) -> Result<(), Self::Error>;
     
@@ -329,20 +388,20 @@ pub trait MotorDriver {
     /// May return `HardwareFault` if temperature sensing is not implemented.
     fn get_temperature(&self) -> Result<f32, Self::Error>;
     
-    /// Gets the current fault status of the motor driver.
+    /// Gets the latched fault status of the motor driver.
     /// 
     /// # Returns
     /// 
-    /// * `Ok(status)` - Fault status byte (0 = no faults)
+    /// * `Ok(status)` - Latched faults (empty = no faults)
     /// * `Err(Self::Error)` if driver is not initialized or hardware error
     /// 
     /// # Example
     /// 
     /// ```rust
     /// let fault_status = motor.get_fault_status()?;
-    /// if fault_status != 0 {
-    ///     println!("Motor fault detected: {}", fault_status);
+    /// if !fault_status.is_empty() {
+    ///     println!("Motor fault detected: {:?}", fault_status);
     /// }
     /// ```
-    fn get_fault_status(&self) -> Result<u8, Self::Error>;
+    fn get_fault_status(&self) -> Result<FaultStatus, Self::Error>;
 }
\ No newline at end of file
@@ -0,0 +1,210 @@
+//! Recording pin and PWM doubles shared by the unit tests.
+
+extern crate std;
+
+use core::convert::Infallible;
+use crate::current::AdcChannel;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use embedded_hal::digital::{ErrorType as DigitalErrorType, InputPin, OutputPin};
+use embedded_hal::pwm::{Error as PwmError, ErrorKind as PwmErrorKind, ErrorType as PwmErrorType, SetDutyCycle};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::vec::Vec;
+
+/// Duty range of every [`MockPwm`].
+pub const MAX_DUTY: u16 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Pin(&'static str, bool),
+    Duty(&'static str, u16),
+}
+
+/// Ordered record of every output written through the doubles it created.
+#[derive(Clone, Default)]
+pub struct Log {
+    events: Arc<Mutex<Vec<Event>>>,
+    pwm_fails: Arc<AtomicBool>,
+}
+
+impl Log {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pin(&self, name: &'static str) -> MockPin {
+        MockPin { name, log: self.clone(), level: Arc::new(AtomicBool::new(false)) }
+    }
+
+    pub fn pwm(&self, name: &'static str) -> MockPwm {
+        MockPwm { name, log: self.clone() }
+    }
+
+    /// Makes every PWM write fail, without recording it, until called with `false`.
+    pub fn fail_pwm(&self, fail: bool) {
+        self.pwm_fails.store(fail, Ordering::SeqCst);
+    }
+
+    /// Returns and clears the events recorded so far.
+    pub fn take(&self) -> Vec<Event> {
+        self.events().drain(..).collect()
+    }
+
+    /// Last duty cycle written to the named channel.
+    pub fn duty(&self, name: &'static str) -> Option<u16> {
+        self.events().iter().rev().find_map(|event| match *event {
+            Event::Duty(channel, duty) if channel == name => Some(duty),
+            _ => None,
+        })
+    }
+
+    /// Last level written to the named pin.
+    pub fn level(&self, name: &'static str) -> Option<bool> {
+        self.events().iter().rev().find_map(|event| match *event {
+            Event::Pin(pin, high) if pin == name => Some(high),
+            _ => None,
+        })
+    }
+
+    fn push(&self, event: Event) {
+        self.events().push(event);
+    }
+
+    fn events(&self) -> MutexGuard<'_, Vec<Event>> {
+        self.events.lock().unwrap()
+    }
+}
+
+/// Output and input pin. Clones share the pin level, so a test can keep one to drive an input.
+#[derive(Clone)]
+pub struct MockPin {
+    name: &'static str,
+    log: Log,
+    level: Arc<AtomicBool>,
+}
+
+impl MockPin {
+    /// Sets the level read back through `InputPin` without recording an event.
+    pub fn drive(&self, high: bool) {
+        self.level.store(high, Ordering::SeqCst);
+    }
+}
+
+impl DigitalErrorType for MockPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for MockPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.level.store(false, Ordering::SeqCst);
+        self.log.push(Event::Pin(self.name, false));
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.level.store(true, Ordering::SeqCst);
+        self.log.push(Event::Pin(self.name, true));
+        Ok(())
+    }
+}
+
+impl InputPin for MockPin {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.level.load(Ordering::SeqCst))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.level.load(Ordering::SeqCst))
+    }
+}
+
+/// Quadrature levels of (A, B) in the order they change when turning forward.
+const QUADRATURE: [(bool, bool); 4] = [(false, false), (true, false), (true, true), (false, true)];
+
+/// Quadrature encoder a test turns by hand through a pair of input pins.
+pub struct MockEncoder {
+    a: MockPin,
+    b: MockPin,
+    phase: usize,
+}
+
+impl MockEncoder {
+    pub fn new(log: &Log) -> Self {
+        Self { a: log.pin("enc_a"), b: log.pin("enc_b"), phase: 0 }
+    }
+
+    /// Input pins to hand to the driver, starting with both channels low.
+    pub fn pins(&self) -> (MockPin, MockPin) {
+        (self.a.clone(), self.b.clone())
+    }
+
+    /// Moves by `pulses` counts, calling `read` after every edge so the driver can decode it.
+    pub fn turn(&mut self, pulses: i32, mut read: impl FnMut()) {
+        for _ in 0..pulses.unsigned_abs() {
+            self.phase = if pulses > 0 { (self.phase + 1) % 4 } else { (self.phase + 3) % 4 };
+            let (a, b) = QUADRATURE[self.phase];
+            self.a.drive(a);
+            self.b.drive(b);
+            read();
+        }
+    }
+}
+
+pub struct MockPwm {
+    name: &'static str,
+    log: Log,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockPwmError;
+
+impl core::fmt::Display for MockPwmError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("mock PWM write failed")
+    }
+}
+
+impl std::error::Error for MockPwmError {}
+
+impl PwmError for MockPwmError {
+    fn kind(&self) -> PwmErrorKind {
+        PwmErrorKind::Other
+    }
+}
+
+impl PwmErrorType for MockPwm {
+    type Error = MockPwmError;
+}
+
+impl SetDutyCycle for MockPwm {
+    fn max_duty_cycle(&self) -> u16 {
+        MAX_DUTY
+    }
+
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        if self.log.pwm_fails.load(Ordering::SeqCst) {
+            return Err(MockPwmError);
+        }
+        self.log.push(Event::Duty(self.name, duty));
+        Ok(())
+    }
+}
+
+/// ADC channel reading the voltage last set by the test. Clones share the reading.
+#[derive(Clone, Default)]
+pub struct MockAdc {
+    volts: Arc<AtomicU32>,
+}
+
+impl MockAdc {
+    pub fn set(&self, volts: f32) {
+        self.volts.store(volts.to_bits(), Ordering::SeqCst);
+    }
+}
+
+impl AdcChannel for MockAdc {
+    type Error = Infallible;
+
+    fn read_volts(&mut self) -> Result<f32, Self::Error> {
+        Ok(f32::from_bits(self.volts.load(Ordering::SeqCst)))
+    }
+}
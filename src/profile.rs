@@ -0,0 +1,309 @@
+//! Point-to-point motion profile generation.
+//!
+//! A [`MotionProfile`] turns a travel distance and velocity, acceleration and
+//! optional jerk limits into a time-parameterized setpoint stream. Units are
+//! up to the caller; the controllers in [`crate::control`] interpret them as
+//! encoder pulses, pulses/s, pulses/s² and pulses/s³.
+
+use crate::MotorDriverError;
+
+/// Number of bisection steps used to find the reachable peak velocity.
+const PEAK_VELOCITY_ITERATIONS: usize = 48;
+
+/// A single sample of a motion profile.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Setpoint {
+    /// Distance travelled since the start of the profile.
+    pub position: f32,
+    /// Velocity at this point of the profile.
+    pub velocity: f32,
+    /// Acceleration at this point of the profile.
+    pub acceleration: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Phase {
+    duration: f32,
+    acceleration: f32,
+    jerk: f32,
+}
+
+/// Time-parameterized point-to-point motion profile.
+///
+/// Trapezoidal profiles ramp the velocity linearly between rest and the
+/// cruise velocity. S-curve profiles additionally limit jerk, so the
+/// acceleration itself ramps in and out and gearboxes see no torque steps.
+/// Short moves that cannot reach the velocity (or acceleration) limit are
+/// shortened automatically. Sampling at or after [`MotionProfile::duration`]
+/// returns exactly the requested distance.
+///
+/// # Example
+///
+/// ```rust
+/// use motor_driver_hal::profile::MotionProfile;
+///
+/// let profile = MotionProfile::s_curve(4000.0, 2000.0, 8000.0, 40000.0).unwrap();
+/// let mid = profile.sample(profile.duration() / 2.0);
+/// assert!(mid.velocity > 0.0);
+/// assert_eq!(profile.sample(profile.duration()).position, 4000.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotionProfile {
+    distance: f32,
+    phases: [Phase; 7],
+    duration: f32,
+}
+
+impl MotionProfile {
+    /// Creates a trapezoidal profile with unlimited jerk.
+    ///
+    /// # Arguments
+    ///
+    /// * `distance` - Signed travel distance
+    /// * `max_velocity` - Velocity limit (must be positive)
+    /// * `max_acceleration` - Acceleration and deceleration limit (must be positive)
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if a limit is not positive or the distance is not finite.
+    pub fn trapezoidal(distance: f32, max_velocity: f32, max_acceleration: f32) -> Result<Self, MotorDriverError> {
+        Self::new(distance, max_velocity, max_acceleration, f32::INFINITY)
+    }
+
+    /// Creates a jerk-limited S-curve profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `distance` - Signed travel distance
+    /// * `max_velocity` - Velocity limit (must be positive)
+    /// * `max_acceleration` - Acceleration and deceleration limit (must be positive)
+    /// * `max_jerk` - Jerk limit (must be positive)
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if a limit is not positive or the distance is not finite.
+    pub fn s_curve(distance: f32, max_velocity: f32, max_acceleration: f32, max_jerk: f32) -> Result<Self, MotorDriverError> {
+        Self::new(distance, max_velocity, max_acceleration, max_jerk)
+    }
+
+    fn new(distance: f32, max_velocity: f32, max_acceleration: f32, max_jerk: f32) -> Result<Self, MotorDriverError> {
+        let limits_valid = max_velocity > 0.0 && max_acceleration > 0.0 && max_jerk > 0.0;
+        if !limits_valid || !distance.is_finite() || !max_velocity.is_finite() || !max_acceleration.is_finite() {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+
+        let length = distance.abs();
+        if length == 0.0 {
+            return Ok(Self {
+                distance,
+                phases: [Phase::default(); 7],
+                duration: 0.0,
+            });
+        }
+
+        // Accelerating from rest to `v` and back covers `v * ta`, which grows with `v`
+        let mut velocity = max_velocity;
+        if velocity * accel_times(velocity, max_acceleration, max_jerk).1 > length {
+            let (mut low, mut high) = (0.0, max_velocity);
+            for _ in 0..PEAK_VELOCITY_ITERATIONS {
+                let mid = (low + high) / 2.0;
+                if mid * accel_times(mid, max_acceleration, max_jerk).1 > length {
+                    high = mid;
+                } else {
+                    low = mid;
+                }
+            }
+            velocity = if low > 0.0 { low } else { high };
+        }
+
+        let (tj, ta) = accel_times(velocity, max_acceleration, max_jerk);
+        let peak_acceleration = velocity / (ta - tj);
+        let jerk = if tj > 0.0 { peak_acceleration / tj } else { 0.0 };
+        let cruise = ((length - velocity * ta) / velocity).max(0.0);
+
+        let phases = [
+            Phase { duration: tj, acceleration: 0.0, jerk },
+            Phase { duration: ta - 2.0 * tj, acceleration: peak_acceleration, jerk: 0.0 },
+            Phase { duration: tj, acceleration: peak_acceleration, jerk: -jerk },
+            Phase { duration: cruise, acceleration: 0.0, jerk: 0.0 },
+            Phase { duration: tj, acceleration: 0.0, jerk: -jerk },
+            Phase { duration: ta - 2.0 * tj, acceleration: -peak_acceleration, jerk: 0.0 },
+            Phase { duration: tj, acceleration: -peak_acceleration, jerk },
+        ];
+
+        Ok(Self {
+            distance,
+            phases,
+            duration: phases.iter().map(|phase| phase.duration).sum(),
+        })
+    }
+
+    /// Returns the signed travel distance.
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    /// Returns the total duration of the profile in seconds.
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// Returns `true` once `time` has reached the end of the profile.
+    pub fn is_finished(&self, time: f32) -> bool {
+        time >= self.duration
+    }
+
+    /// Samples the profile `time` seconds after its start.
+    ///
+    /// Times before the start are clamped to the start and times at or after
+    /// the end return the final position at rest.
+    pub fn sample(&self, time: f32) -> Setpoint {
+        if time >= self.duration {
+            return Setpoint {
+                position: self.distance,
+                velocity: 0.0,
+                acceleration: 0.0,
+            };
+        }
+
+        let sign = if self.distance < 0.0 { -1.0 } else { 1.0 };
+        let mut remaining = time.max(0.0);
+        let (mut position, mut velocity) = (0.0, 0.0);
+        for phase in &self.phases {
+            let t = remaining.min(phase.duration);
+            let (a, j) = (phase.acceleration, phase.jerk);
+            if remaining <= phase.duration {
+                return Setpoint {
+                    position: sign * (position + velocity * t + a * t * t / 2.0 + j * t * t * t / 6.0),
+                    velocity: sign * (velocity + a * t + j * t * t / 2.0),
+                    acceleration: sign * (a + j * t),
+                };
+            }
+            position += velocity * t + a * t * t / 2.0 + j * t * t * t / 6.0;
+            velocity += a * t + j * t * t / 2.0;
+            remaining -= t;
+        }
+
+        self.sample(self.duration)
+    }
+}
+
+/// Returns the jerk phase and total acceleration phase durations to reach `velocity` from rest.
+fn accel_times(velocity: f32, max_acceleration: f32, max_jerk: f32) -> (f32, f32) {
+    if max_jerk == f32::INFINITY {
+        (0.0, velocity / max_acceleration)
+    } else if velocity * max_jerk >= max_acceleration * max_acceleration {
+        let tj = max_acceleration / max_jerk;
+        (tj, tj + velocity / max_acceleration)
+    } else {
+        let tj = sqrt(velocity / max_jerk);
+        (tj, 2.0 * tj)
+    }
+}
+
+/// Square root by Newton iteration, `core` has no float `sqrt`.
+fn sqrt(value: f32) -> f32 {
+    if value <= 0.0 {
+        return 0.0;
+    }
+    let mut root = if value > 1.0 { value } else { 1.0 };
+    for _ in 0..64 {
+        let next = (root + value / root) / 2.0;
+        if next >= root {
+            break;
+        }
+        root = next;
+    }
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Samples `profile` densely and checks it against its limits, returning the peak speed.
+    fn check_profile(profile: &MotionProfile, max_velocity: f32, max_acceleration: f32) -> f32 {
+        const STEPS: u32 = 2000;
+        let sign = profile.distance().signum();
+        let mut last = profile.sample(0.0);
+        let mut peak: f32 = 0.0;
+        assert_eq!(last.position, 0.0);
+
+        for step in 1..=STEPS {
+            let setpoint = profile.sample(profile.duration() * step as f32 / STEPS as f32);
+            assert!(setpoint.velocity.abs() <= max_velocity * 1.001, "{setpoint:?}");
+            assert!(setpoint.acceleration.abs() <= max_acceleration * 1.001, "{setpoint:?}");
+            assert!(sign * (setpoint.position - last.position) >= -1e-3, "{last:?} -> {setpoint:?}");
+            peak = peak.max(setpoint.velocity.abs());
+            last = setpoint;
+        }
+
+        assert_eq!(last.position, profile.distance());
+        assert_eq!(profile.sample(profile.duration() + 1.0).position, profile.distance());
+        assert!(profile.is_finished(profile.duration()));
+        peak
+    }
+
+    #[test]
+    fn trapezoidal_reaches_cruise_velocity() {
+        let profile = MotionProfile::trapezoidal(4000.0, 1000.0, 2000.0).unwrap();
+        let peak = check_profile(&profile, 1000.0, 2000.0);
+        assert!((peak - 1000.0).abs() < 1.0);
+        // 0.5 s ramps on both ends and 3.5 s at cruise
+        assert!((profile.duration() - 4.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn trapezoidal_short_move_never_cruises() {
+        let profile = MotionProfile::trapezoidal(100.0, 1000.0, 2000.0).unwrap();
+        let peak = check_profile(&profile, 1000.0, 2000.0);
+        // A triangle covering 100 with 2000/s² peaks at sqrt(100 * 2000)
+        assert!((peak - 447.2).abs() < 1.0, "peak {peak}");
+    }
+
+    #[test]
+    fn trapezoidal_runs_in_reverse() {
+        let profile = MotionProfile::trapezoidal(-2500.0, 800.0, 3000.0).unwrap();
+        check_profile(&profile, 800.0, 3000.0);
+        assert!(profile.sample(profile.duration() / 2.0).velocity < 0.0);
+    }
+
+    #[test]
+    fn s_curve_reaches_cruise_velocity() {
+        let profile = MotionProfile::s_curve(4000.0, 2000.0, 8000.0, 40000.0).unwrap();
+        let peak = check_profile(&profile, 2000.0, 8000.0);
+        assert!((peak - 2000.0).abs() < 1.0);
+
+        // Acceleration ramps in instead of stepping to its limit
+        let early = profile.sample(0.01);
+        assert!(early.acceleration > 0.0 && early.acceleration < 8000.0);
+    }
+
+    #[test]
+    fn s_curve_short_move_never_cruises() {
+        let profile = MotionProfile::s_curve(50.0, 2000.0, 8000.0, 40000.0).unwrap();
+        let peak = check_profile(&profile, 2000.0, 8000.0);
+        assert!(peak > 0.0 && peak < 1000.0, "peak {peak}");
+    }
+
+    #[test]
+    fn s_curve_runs_in_reverse() {
+        let profile = MotionProfile::s_curve(-3000.0, 1500.0, 6000.0, 30000.0).unwrap();
+        check_profile(&profile, 1500.0, 6000.0);
+    }
+
+    #[test]
+    fn zero_distance_is_finished_immediately() {
+        let profile = MotionProfile::s_curve(0.0, 100.0, 100.0, 100.0).unwrap();
+        assert_eq!(profile.duration(), 0.0);
+        assert_eq!(profile.sample(0.0), Setpoint::default());
+    }
+
+    #[test]
+    fn limits_must_be_positive() {
+        assert_eq!(MotionProfile::trapezoidal(10.0, 0.0, 1.0), Err(MotorDriverError::InvalidConfiguration));
+        assert_eq!(MotionProfile::trapezoidal(10.0, 1.0, -1.0), Err(MotorDriverError::InvalidConfiguration));
+        assert_eq!(MotionProfile::s_curve(10.0, 1.0, 1.0, 0.0), Err(MotorDriverError::InvalidConfiguration));
+        assert_eq!(MotionProfile::trapezoidal(f32::NAN, 1.0, 1.0), Err(MotorDriverError::InvalidConfiguration));
+    }
+}
@@ -0,0 +1,252 @@
+//! Acceleration limiting for commanded duty cycles.
+
+use crate::control::round_duty;
+use crate::MotorDriverError;
+
+/// Slew-rate limiter that moves a duty output towards its target.
+///
+/// Rates are in duty units per second. Moving away from zero is limited by
+/// the acceleration rate and moving towards zero by the deceleration rate,
+/// so a full reversal first decelerates to zero and then accelerates the
+/// other way. Time is supplied by the caller in microseconds; a limiter that
+/// has reached its target does not accumulate idle time.
+///
+/// # Example
+///
+/// ```rust
+/// use motor_driver_hal::ramp::RampLimiter;
+///
+/// let mut ramp = RampLimiter::new(1000.0, 4000.0).unwrap();
+/// ramp.set_target(500, 0);
+/// assert_eq!(ramp.update(100_000), 100);
+/// assert_eq!(ramp.update(600_000), 500);
+///
+/// ramp.set_target(-500, 600_000);
+/// assert_eq!(ramp.update(725_000), 0);
+/// assert_eq!(ramp.update(825_000), -100);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RampLimiter {
+    acceleration: f32,
+    deceleration: f32,
+    output: f32,
+    target: i16,
+    last_update: Option<u64>,
+}
+
+impl RampLimiter {
+    /// Creates a limiter with separate acceleration and deceleration rates.
+    ///
+    /// # Arguments
+    ///
+    /// * `acceleration` - Maximum rate of increase of `|duty|`, in duty units per second
+    /// * `deceleration` - Maximum rate of decrease of `|duty|`, in duty units per second
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if a rate is not positive.
+    pub fn new(acceleration: f32, deceleration: f32) -> Result<Self, MotorDriverError> {
+        if !(acceleration > 0.0 && deceleration > 0.0) {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+
+        Ok(Self {
+            acceleration,
+            deceleration,
+            output: 0.0,
+            target: 0,
+            last_update: None,
+        })
+    }
+
+    /// Creates a limiter using the same rate for acceleration and deceleration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if the rate is not positive.
+    pub fn symmetric(rate: f32) -> Result<Self, MotorDriverError> {
+        Self::new(rate, rate)
+    }
+
+    /// Returns the acceleration limit in duty units per second.
+    pub fn acceleration(&self) -> f32 {
+        self.acceleration
+    }
+
+    /// Returns the deceleration limit in duty units per second.
+    pub fn deceleration(&self) -> f32 {
+        self.deceleration
+    }
+
+    /// Returns the duty the output is moving towards.
+    pub fn target(&self) -> i16 {
+        self.target
+    }
+
+    /// Returns the current rate-limited duty.
+    pub fn output(&self) -> i16 {
+        round_duty(self.output)
+    }
+
+    /// Returns `true` once the output has reached the target.
+    pub fn is_settled(&self) -> bool {
+        self.output == self.target as f32
+    }
+
+    /// Advances the output to `now_us` and then changes the target.
+    ///
+    /// # Returns
+    ///
+    /// The rate-limited duty at `now_us`
+    pub fn set_target(&mut self, target: i16, now_us: u64) -> i16 {
+        self.update(now_us);
+        self.target = target;
+        self.output()
+    }
+
+    /// Advances the output towards the target by the time elapsed since the last call.
+    ///
+    /// # Returns
+    ///
+    /// The rate-limited duty at `now_us`
+    pub fn update(&mut self, now_us: u64) -> i16 {
+        let elapsed = match self.last_update {
+            Some(last) if !self.is_settled() => now_us.saturating_sub(last),
+            _ => 0,
+        };
+        self.last_update = Some(now_us);
+
+        let target = self.target as f32;
+        let mut remaining = elapsed as f32 / 1e6;
+        while remaining > 0.0 && !self.is_settled() {
+            let decelerating = self.output != 0.0
+                && (target == 0.0 || target.signum() != self.output.signum() || target.abs() < self.output.abs());
+            let (goal, rate) = if !decelerating {
+                (target, self.acceleration)
+            } else if target != 0.0 && target.signum() == self.output.signum() {
+                (target, self.deceleration)
+            } else {
+                (0.0, self.deceleration)
+            };
+
+            let distance = (goal - self.output).abs();
+            let step = rate * remaining;
+            if step >= distance {
+                self.output = goal;
+                remaining -= distance / rate;
+            } else {
+                self.output += step.copysign(goal - self.output);
+                remaining = 0.0;
+            }
+        }
+
+        self.output()
+    }
+
+    /// Jumps the output and target to `duty` without ramping, e.g. after a stop or brake.
+    pub fn reset(&mut self, duty: i16) {
+        self.output = duty as f32;
+        self.target = duty;
+        self.last_update = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, MockClock};
+    use crate::driver::HBridgeMotorDriverBuilder;
+    use crate::mock::{Log, MockPin};
+    use crate::{MotorDriver, NoEncoder};
+
+    #[test]
+    fn rates_must_be_positive() {
+        assert_eq!(RampLimiter::new(0.0, 1.0), Err(MotorDriverError::InvalidConfiguration));
+        assert_eq!(RampLimiter::new(1.0, -1.0), Err(MotorDriverError::InvalidConfiguration));
+        assert_eq!(RampLimiter::symmetric(f32::NAN), Err(MotorDriverError::InvalidConfiguration));
+    }
+
+    #[test]
+    fn accelerates_and_decelerates_at_separate_rates() {
+        let clock = MockClock::new();
+        let mut ramp = RampLimiter::new(1000.0, 4000.0).unwrap();
+        assert_eq!(ramp.set_target(800, clock.now_micros()), 0);
+
+        clock.advance(400_000);
+        assert_eq!(ramp.update(clock.now_micros()), 400);
+        clock.advance(400_000);
+        assert_eq!(ramp.update(clock.now_micros()), 800);
+        assert!(ramp.is_settled());
+
+        // Slowing down uses the deceleration rate, also towards a non-zero target
+        assert_eq!(ramp.set_target(200, clock.now_micros()), 800);
+        clock.advance(100_000);
+        assert_eq!(ramp.update(clock.now_micros()), 400);
+        clock.advance(100_000);
+        assert_eq!(ramp.update(clock.now_micros()), 200);
+        assert!(ramp.is_settled());
+    }
+
+    #[test]
+    fn crossing_zero_decelerates_then_accelerates() {
+        let clock = MockClock::new();
+        let mut ramp = RampLimiter::new(1000.0, 4000.0).unwrap();
+        ramp.reset(400);
+        ramp.set_target(-600, clock.now_micros());
+
+        // 100 ms to brake from 400 to zero, then 50 ms accelerating in reverse
+        clock.advance(150_000);
+        assert_eq!(ramp.update(clock.now_micros()), -50);
+        clock.advance(100_000);
+        assert_eq!(ramp.update(clock.now_micros()), -150);
+        clock.advance(450_000);
+        assert_eq!(ramp.update(clock.now_micros()), -600);
+        assert!(ramp.is_settled());
+    }
+
+    #[test]
+    fn settled_ramp_ignores_elapsed_time() {
+        let clock = MockClock::new();
+        let mut ramp = RampLimiter::symmetric(1000.0).unwrap();
+        ramp.update(clock.now_micros());
+
+        // Time spent at rest must not be spent on the next move
+        clock.advance(1_000_000);
+        assert_eq!(ramp.set_target(500, clock.now_micros()), 0);
+        clock.advance(100_000);
+        assert_eq!(ramp.update(clock.now_micros()), 100);
+    }
+
+    #[test]
+    fn driver_ramps_instead_of_writing_the_target() {
+        let log = Log::new();
+        let clock = MockClock::new();
+        let mut motor = HBridgeMotorDriverBuilder::<_, MockPin, _, _, NoEncoder, NoEncoder>::new()
+            .with_enable(log.pin("en"))
+            .with_dual_pwm(log.pwm("fwd"), log.pwm("rev"))
+            .with_ramp(RampLimiter::new(1000.0, 4000.0).unwrap())
+            .with_clock(&clock)
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        log.take();
+
+        motor.set_speed(800).unwrap();
+        assert_ne!(log.duty("fwd"), Some(800));
+        assert_eq!(motor.get_speed().unwrap(), 0);
+        assert_eq!(motor.target_speed(), 800);
+
+        clock.advance(250_000);
+        assert_eq!(motor.update().unwrap(), 250);
+        assert_eq!(log.duty("fwd"), Some(250));
+
+        clock.advance(750_000);
+        assert_eq!(motor.update().unwrap(), 800);
+        assert_eq!(log.duty("fwd"), Some(800));
+
+        motor.set_speed(0).unwrap();
+        clock.advance(100_000);
+        assert_eq!(motor.update().unwrap(), 400);
+        assert_eq!(log.duty("fwd"), Some(400));
+    }
+}
@@ -0,0 +1,475 @@
+//! Simulated motor backend for hardware-free testing.
+//!
+//! [`SimMotor`] models a brushed DC motor behind an H-bridge and hands out
+//! virtual enable pins, PWM channels and quadrature encoder pins that plug
+//! into [`HBridgeMotorDriver`](crate::HBridgeMotorDriver) or
+//! [`MotorDriverWrapper`](crate::MotorDriverWrapper) like real peripherals.
+//! Time only advances when [`SimMotor::step`] is called, which keeps runs
+//! fully deterministic. Advance a [`MockClock`](crate::clock::MockClock)
+//! alongside it to make velocity estimation and other time-dependent
+//! features deterministic as well.
+//!
+//! # Example
+//!
+//! ```rust
+//! use motor_driver_hal::sim::{DcMotorParams, SimMotor};
+//! use motor_driver_hal::{HBridgeMotorDriver, MotorDriver};
+//!
+//! let sim = SimMotor::new(DcMotorParams::default());
+//! let mut motor = HBridgeMotorDriver::builder_with_encoder()
+//!     .with_dual_enable(sim.enable_pin(), sim.enable_pin())
+//!     .with_dual_pwm(sim.pwm_forward(1000), sim.pwm_reverse(1000))
+//!     .with_encoder(sim.encoder_a(), sim.encoder_b())
+//!     .with_ppr(1000)
+//!     .build_and_init()
+//!     .unwrap();
+//!
+//! motor.enable().unwrap();
+//! motor.set_speed(500).unwrap();
+//! for _ in 0..1000 {
+//!     sim.step(0.0001);
+//!     motor.read_encoder().unwrap();
+//! }
+//! assert!(motor.get_pulse_count() > 0);
+//! ```
+
+use core::convert::Infallible;
+use embedded_hal::digital::{ErrorType as DigitalErrorType, InputPin, OutputPin};
+use embedded_hal::pwm::{ErrorType as PwmErrorType, SetDutyCycle};
+use std::f32::consts::PI;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Longest integration step used internally by [`SimMotor::step`], in seconds.
+const MAX_SUBSTEP: f32 = 1e-4;
+
+/// Physical parameters of the simulated DC motor.
+///
+/// The electrical side is modelled as a resistor in series with the back-EMF
+/// source (winding inductance is neglected), the mechanical side as a single
+/// inertia with viscous and Coulomb friction. Constants are referred to the
+/// output shaft, so a gearmotor is described by its geared-down values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DcMotorParams {
+    /// Bridge supply voltage in volts.
+    pub supply_voltage: f32,
+    /// Winding resistance in ohms.
+    pub resistance: f32,
+    /// Back-EMF constant in V·s/rad, equal to the torque constant in N·m/A.
+    pub back_emf_constant: f32,
+    /// Rotor and load inertia in kg·m².
+    pub inertia: f32,
+    /// Viscous friction coefficient in N·m·s/rad.
+    pub viscous_friction: f32,
+    /// Coulomb (dry) friction torque in N·m.
+    pub coulomb_friction: f32,
+    /// Quadrature counts per output revolution, as seen by `get_pulse_count()`.
+    pub counts_per_rev: u32,
+}
+
+impl Default for DcMotorParams {
+    /// A 12V gearmotor with roughly 230 RPM no-load speed and a 1000 count encoder.
+    fn default() -> Self {
+        Self {
+            supply_voltage: 12.0,
+            resistance: 2.0,
+            back_emf_constant: 0.5,
+            inertia: 1e-3,
+            viscous_friction: 1e-4,
+            coulomb_friction: 0.02,
+            counts_per_rev: 1000,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SimState {
+    params: DcMotorParams,
+    enables: Vec<bool>,
+    duty_forward: f32,
+    duty_reverse: f32,
+    load_torque: f32,
+    current: f32,
+    velocity: f32,
+    position: f64,
+    time: f64,
+}
+
+impl SimState {
+    fn is_enabled(&self) -> bool {
+        self.enables.iter().all(|&enabled| enabled)
+    }
+
+    /// Averaged bridge output voltage, `None` while the bridge floats.
+    fn bridge_voltage(&self) -> Option<f32> {
+        if !self.is_enabled() || (self.duty_forward == 0.0 && self.duty_reverse == 0.0) {
+            None
+        } else {
+            Some(self.params.supply_voltage * (self.duty_forward - self.duty_reverse))
+        }
+    }
+
+    fn integrate(&mut self, dt: f32) {
+        let p = self.params;
+        self.current = match self.bridge_voltage() {
+            Some(voltage) => (voltage - p.back_emf_constant * self.velocity) / p.resistance,
+            None => 0.0,
+        };
+
+        let drive = p.back_emf_constant * self.current - self.load_torque;
+        let mut velocity = self.velocity + (drive - p.viscous_friction * self.velocity) / p.inertia * dt;
+
+        let friction_step = p.coulomb_friction / p.inertia * dt;
+        if velocity.abs() <= friction_step && drive.abs() <= p.coulomb_friction {
+            velocity = 0.0;
+        } else {
+            velocity -= friction_step.copysign(velocity);
+        }
+
+        self.velocity = velocity;
+        self.position += (velocity * dt) as f64;
+        self.time += dt as f64;
+    }
+
+    fn encoder_count(&self) -> i64 {
+        (self.position / core::f64::consts::TAU * self.params.counts_per_rev as f64).floor() as i64
+    }
+
+    /// Levels of the A and B channels for the current position.
+    fn encoder_levels(&self) -> (bool, bool) {
+        match self.encoder_count().rem_euclid(4) {
+            0 => (false, false),
+            1 => (true, false),
+            2 => (true, true),
+            _ => (false, true),
+        }
+    }
+}
+
+/// Shared handle to a simulated DC motor and its H-bridge.
+///
+/// Cloning the handle is cheap and every clone observes the same motor.
+/// Pins created from the handle feed the model: the bridge is enabled when
+/// every enable pin is high, the forward and reverse PWM channels set the
+/// averaged bridge voltage, and the encoder pins report quadrature levels
+/// derived from the rotor position. With both PWM duties at zero the bridge
+/// floats and the motor coasts; equal non-zero duties short the winding and brake it.
+#[derive(Debug, Clone)]
+pub struct SimMotor {
+    state: Arc<Mutex<SimState>>,
+}
+
+impl SimMotor {
+    /// Creates a motor at rest with the given physical parameters.
+    pub fn new(params: DcMotorParams) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SimState {
+                params,
+                enables: Vec::new(),
+                duty_forward: 0.0,
+                duty_reverse: 0.0,
+                load_torque: 0.0,
+                current: 0.0,
+                velocity: 0.0,
+                position: 0.0,
+                time: 0.0,
+            })),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SimState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Creates a new enable pin. The bridge conducts only while all enable pins are high.
+    pub fn enable_pin(&self) -> SimEnablePin {
+        let mut state = self.lock();
+        state.enables.push(false);
+        SimEnablePin {
+            motor: self.clone(),
+            index: state.enables.len() - 1,
+        }
+    }
+
+    /// Creates the PWM channel driving the motor forward.
+    pub fn pwm_forward(&self, max_duty: u16) -> SimPwm {
+        SimPwm {
+            motor: self.clone(),
+            reverse: false,
+            max_duty,
+        }
+    }
+
+    /// Creates the PWM channel driving the motor in reverse.
+    pub fn pwm_reverse(&self, max_duty: u16) -> SimPwm {
+        SimPwm {
+            motor: self.clone(),
+            reverse: true,
+            max_duty,
+        }
+    }
+
+    /// Creates the encoder A phase input.
+    pub fn encoder_a(&self) -> SimEncoderPin {
+        SimEncoderPin {
+            motor: self.clone(),
+            phase_b: false,
+        }
+    }
+
+    /// Creates the encoder B phase input.
+    pub fn encoder_b(&self) -> SimEncoderPin {
+        SimEncoderPin {
+            motor: self.clone(),
+            phase_b: true,
+        }
+    }
+
+    /// Advances the simulation by `dt` seconds.
+    ///
+    /// Long steps are split into sub-steps of at most 100µs. Encoder pins only
+    /// reflect the final position, so `read_encoder()` must be called often
+    /// enough that the count never moves by more than one between reads.
+    pub fn step(&self, dt: f32) {
+        let mut state = self.lock();
+        let mut remaining = dt;
+        while remaining > 0.0 {
+            let h = remaining.min(MAX_SUBSTEP);
+            state.integrate(h);
+            remaining -= h;
+        }
+    }
+
+    /// Sets an external load torque in N·m opposing forward rotation.
+    pub fn set_load_torque(&self, torque: f32) {
+        self.lock().load_torque = torque;
+    }
+
+    /// Returns the simulated time in seconds.
+    pub fn time(&self) -> f64 {
+        self.lock().time
+    }
+
+    /// Returns the rotor velocity in rad/s.
+    pub fn velocity(&self) -> f32 {
+        self.lock().velocity
+    }
+
+    /// Returns the rotor velocity in revolutions per minute.
+    pub fn rpm(&self) -> f32 {
+        self.velocity() * 60.0 / (2.0 * PI)
+    }
+
+    /// Returns the rotor position in radians.
+    pub fn position(&self) -> f64 {
+        self.lock().position
+    }
+
+    /// Returns the winding current in amperes.
+    pub fn current(&self) -> f32 {
+        self.lock().current
+    }
+
+    /// Returns the averaged bridge voltage in volts, zero while the bridge floats.
+    pub fn bridge_voltage(&self) -> f32 {
+        self.lock().bridge_voltage().unwrap_or(0.0)
+    }
+
+    /// Returns `true` when every enable pin is high.
+    pub fn is_enabled(&self) -> bool {
+        self.lock().is_enabled()
+    }
+
+    /// Returns the ideal encoder count for the current rotor position.
+    pub fn encoder_count(&self) -> i64 {
+        self.lock().encoder_count()
+    }
+}
+
+/// Virtual enable pin created by [`SimMotor::enable_pin`].
+#[derive(Debug)]
+pub struct SimEnablePin {
+    motor: SimMotor,
+    index: usize,
+}
+
+impl DigitalErrorType for SimEnablePin {
+    type Error = Infallible;
+}
+
+impl OutputPin for SimEnablePin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.motor.lock().enables[self.index] = false;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.motor.lock().enables[self.index] = true;
+        Ok(())
+    }
+}
+
+/// Virtual PWM channel created by [`SimMotor::pwm_forward`] or [`SimMotor::pwm_reverse`].
+#[derive(Debug)]
+pub struct SimPwm {
+    motor: SimMotor,
+    reverse: bool,
+    max_duty: u16,
+}
+
+impl PwmErrorType for SimPwm {
+    type Error = Infallible;
+}
+
+impl SetDutyCycle for SimPwm {
+    fn max_duty_cycle(&self) -> u16 {
+        self.max_duty
+    }
+
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        let fraction = if self.max_duty == 0 {
+            0.0
+        } else {
+            duty.min(self.max_duty) as f32 / self.max_duty as f32
+        };
+
+        let mut state = self.motor.lock();
+        if self.reverse {
+            state.duty_reverse = fraction;
+        } else {
+            state.duty_forward = fraction;
+        }
+        Ok(())
+    }
+}
+
+/// Virtual quadrature encoder input created by [`SimMotor::encoder_a`] or [`SimMotor::encoder_b`].
+#[derive(Debug)]
+pub struct SimEncoderPin {
+    motor: SimMotor,
+    phase_b: bool,
+}
+
+impl DigitalErrorType for SimEncoderPin {
+    type Error = Infallible;
+}
+
+impl InputPin for SimEncoderPin {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        let (level_a, level_b) = self.motor.lock().encoder_levels();
+        Ok(if self.phase_b { level_b } else { level_a })
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.is_high().map(|high| !high)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wrapper::MotorDriverBuilder;
+    use crate::{HBridgeMotorDriver, MotorDriver, NoPin};
+
+    /// Steps the motor for `seconds`, decoding the encoder after every sub-step.
+    fn run<F: FnMut()>(sim: &SimMotor, seconds: f32, mut each_step: F) {
+        for _ in 0..(seconds / MAX_SUBSTEP) as u32 {
+            sim.step(MAX_SUBSTEP);
+            each_step();
+        }
+    }
+
+    #[test]
+    fn hbridge_driver_follows_speed_and_direction() {
+        let sim = SimMotor::new(DcMotorParams::default());
+        let mut motor = HBridgeMotorDriver::builder_with_encoder()
+            .with_dual_enable(sim.enable_pin(), sim.enable_pin())
+            .with_dual_pwm(sim.pwm_forward(1000), sim.pwm_reverse(1000))
+            .with_encoder(sim.encoder_a(), sim.encoder_b())
+            .with_ppr(1000)
+            .build_and_init()
+            .unwrap();
+        assert!(!sim.is_enabled());
+
+        motor.enable().unwrap();
+        motor.set_speed(500).unwrap();
+        assert!(sim.is_enabled());
+        assert!((sim.bridge_voltage() - 6.0).abs() < 1e-4);
+
+        run(&sim, 0.5, || motor.read_encoder().unwrap());
+        assert!(sim.velocity() > 0.0);
+        assert_eq!(motor.get_speed().unwrap(), 500);
+        assert!(motor.get_direction().unwrap());
+        let forward_count = motor.get_pulse_count();
+        assert!(forward_count > 100);
+        assert_eq!(forward_count as i64, sim.encoder_count());
+
+        motor.set_speed(-500).unwrap();
+        assert!((sim.bridge_voltage() + 6.0).abs() < 1e-4);
+        run(&sim, 1.0, || motor.read_encoder().unwrap());
+        assert!(sim.velocity() < 0.0);
+        assert!(!motor.get_direction().unwrap());
+        assert!(motor.get_pulse_count() < forward_count);
+        assert_eq!(motor.get_pulse_count() as i64, sim.encoder_count());
+
+        motor.disable().unwrap();
+        assert!(!sim.is_enabled());
+    }
+
+    #[test]
+    fn hbridge_driver_brakes_faster_than_it_coasts() {
+        let spin_down = |brake: bool| {
+            let sim = SimMotor::new(DcMotorParams::default());
+            let mut motor = HBridgeMotorDriver::<_, NoPin, _, _, _, _>::builder_with_encoder()
+                .with_enable(sim.enable_pin())
+                .with_dual_pwm(sim.pwm_forward(1000), sim.pwm_reverse(1000))
+                .with_encoder(sim.encoder_a(), sim.encoder_b())
+                .build_and_init()
+                .unwrap();
+            motor.enable().unwrap();
+            motor.set_speed(1000).unwrap();
+            run(&sim, 0.5, || ());
+            if brake {
+                motor.brake().unwrap();
+            } else {
+                motor.stop().unwrap();
+            }
+            run(&sim, 0.05, || ());
+            sim.velocity()
+        };
+
+        let braked = spin_down(true);
+        let coasted = spin_down(false);
+        assert!(braked >= 0.0);
+        assert!(braked < coasted);
+    }
+
+    #[test]
+    fn wrapper_follows_speed_and_direction() {
+        let sim = SimMotor::new(DcMotorParams::default());
+        let mut motor = MotorDriverBuilder::<_, NoPin, _, _>::new()
+            .with_single_enable(sim.enable_pin())
+            .with_dual_pwm(sim.pwm_forward(1000), sim.pwm_reverse(1000))
+            .build_and_init()
+            .unwrap();
+
+        motor.enable().unwrap();
+        motor.set_speed(600).unwrap();
+        assert!((sim.bridge_voltage() - 7.2).abs() < 1e-4);
+        run(&sim, 0.5, || ());
+        assert!(sim.velocity() > 0.0);
+        assert_eq!(motor.get_speed().unwrap(), 600);
+        assert!(motor.get_direction().unwrap());
+        let forward_position = sim.position();
+
+        motor.set_speed(-600).unwrap();
+        run(&sim, 1.0, || ());
+        assert!(sim.velocity() < 0.0);
+        assert!(!motor.get_direction().unwrap());
+        assert!(sim.position() < forward_position);
+
+        motor.stop().unwrap();
+        assert_eq!(sim.bridge_voltage(), 0.0);
+        motor.disable().unwrap();
+        assert!(!sim.is_enabled());
+    }
+}
@@ -0,0 +1,95 @@
+//! Stall detection from encoder feedback.
+
+use crate::MotorDriverError;
+
+/// Detects a motor that is driven but does not turn.
+///
+/// A stall is reported once the commanded duty has stayed at or above a
+/// threshold while the encoder count has not changed for the timeout. Any
+/// encoder movement or a duty below the threshold restarts the timer, so
+/// slow but moving motors are not flagged.
+///
+/// # Example
+///
+/// ```rust
+/// use motor_driver_hal::stall::StallDetector;
+///
+/// let mut stall = StallDetector::new(200, 100).unwrap();
+/// assert!(!stall.update(500, 0, 0));
+/// assert!(!stall.update(500, 12, 80_000));
+/// assert!(!stall.update(500, 12, 150_000));
+/// assert!(stall.update(500, 12, 180_000));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StallDetector {
+    min_duty: u16,
+    timeout_us: u64,
+    cut_power: bool,
+    last_count: Option<i32>,
+    last_progress: u64,
+    stalled: bool,
+}
+
+impl StallDetector {
+    /// Creates a detector that stops the motor on a stall but leaves the bridge enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_duty` - Duty at or above which the motor is expected to turn
+    /// * `timeout_ms` - Time without encoder movement before a stall is reported
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if `min_duty` or `timeout_ms` is zero.
+    pub fn new(min_duty: u16, timeout_ms: u32) -> Result<Self, MotorDriverError> {
+        if min_duty == 0 || timeout_ms == 0 {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+
+        Ok(Self {
+            min_duty,
+            timeout_us: timeout_ms as u64 * 1000,
+            cut_power: false,
+            last_count: None,
+            last_progress: 0,
+            stalled: false,
+        })
+    }
+
+    /// Makes the driver also disable the bridge when a stall is detected.
+    pub fn with_cut_power(mut self, cut_power: bool) -> Self {
+        self.cut_power = cut_power;
+        self
+    }
+
+    /// Returns `true` if the driver should disable the bridge on a stall.
+    pub fn cuts_power(&self) -> bool {
+        self.cut_power
+    }
+
+    /// Returns `true` once a stall has been detected, until `reset()`.
+    pub fn is_stalled(&self) -> bool {
+        self.stalled
+    }
+
+    /// Evaluates the commanded duty and encoder count at `now_us`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the motor is stalled
+    pub fn update(&mut self, duty: u16, pulse_count: i32, now_us: u64) -> bool {
+        if duty < self.min_duty || self.last_count != Some(pulse_count) {
+            self.last_count = Some(pulse_count);
+            self.last_progress = now_us;
+        } else if now_us.saturating_sub(self.last_progress) >= self.timeout_us {
+            self.stalled = true;
+        }
+        self.stalled
+    }
+
+    /// Clears a detected stall and restarts the timer on the next update.
+    pub fn reset(&mut self) {
+        self.last_count = None;
+        self.stalled = false;
+    }
+}
@@ -0,0 +1,54 @@
+//! Driver lifecycle states.
+
+/// Lifecycle state of a motor driver.
+///
+/// ```text
+/// Uninitialized --initialize()--> Disabled --enable()--> Enabled <--> Running
+///                                    ^                      |            |
+///                                    +------disable()-------+------------+
+///
+/// any initialized state --latched fault--> Faulted --clear_fault()--> Disabled
+/// any initialized state --emergency stop--> EStopped --enable()--> Enabled
+/// ```
+///
+/// `set_speed()` is only accepted in `Enabled` and `Running`; in the other
+/// states it returns an error instead of writing the PWM. `stop()`,
+/// `brake()` and `disable()` are accepted in every initialized state.
+///
+/// # Example
+///
+/// ```rust
+/// use motor_driver_hal::DriverState;
+///
+/// assert!(DriverState::Running.can_drive());
+/// assert!(!DriverState::Disabled.can_drive());
+/// assert!(DriverState::Faulted.is_initialized());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DriverState {
+    /// `initialize()` has not been called yet.
+    #[default]
+    Uninitialized,
+    /// Initialized with the enable pins low.
+    Disabled,
+    /// Enable pins high with zero commanded speed.
+    Enabled,
+    /// Enable pins high with a non-zero commanded or ramping speed.
+    Running,
+    /// A fault is latched; `clear_fault()` returns the driver to `Disabled`.
+    Faulted,
+    /// The emergency stop tripped; `enable()` resumes once it is reset.
+    EStopped,
+}
+
+impl DriverState {
+    /// Returns `true` once `initialize()` has succeeded.
+    pub fn is_initialized(self) -> bool {
+        self != DriverState::Uninitialized
+    }
+
+    /// Returns `true` if the driver accepts speed commands.
+    pub fn can_drive(self) -> bool {
+        matches!(self, DriverState::Enabled | DriverState::Running)
+    }
+}
@@ -0,0 +1,440 @@
+//! Type-state API that checks driver setup and enabling at compile time.
+//!
+//! [`TypedBuilder`] tracks in its type whether the enable pin and PWM channel
+//! have been supplied, so `build()` and `build_and_init()` only exist once
+//! both are set and can never panic. Both still run `try_build()` on the
+//! remaining settings and return its error. `build_and_init()` returns an
+//! [`Initialized`] driver that offers read-only access; speed commands are
+//! only reachable through the [`Enabled`] guard returned by `enable()`,
+//! which stops and disables the motor when it is dropped.
+//!
+//! ```rust
+//! # use core::convert::Infallible;
+//! # use embedded_hal::pwm::{ErrorType, SetDutyCycle};
+//! # use motor_driver_hal::{HBridgeMotorDriver, MotorDriver, NoEncoder, NoPin};
+//! # struct Pwm;
+//! # impl ErrorType for Pwm { type Error = Infallible; }
+//! # impl SetDutyCycle for Pwm {
+//! #     fn max_duty_cycle(&self) -> u16 { 1000 }
+//! #     fn set_duty_cycle(&mut self, _duty: u16) -> Result<(), Infallible> { Ok(()) }
+//! # }
+//! # let (enable_pin, pwm_channel) = (NoPin, Pwm);
+//! let mut motor = HBridgeMotorDriver::<_, NoPin, _, Pwm, NoEncoder, NoEncoder>::typed_builder()
+//!     .with_enable(enable_pin)
+//!     .with_pwm(pwm_channel)
+//!     .configure(|builder| builder.with_max_duty(500))
+//!     .build_and_init()?;
+//!
+//! let mut running = motor.enable()?;
+//! running.set_speed(300)?;
+//! running.update()?;
+//! drop(running); // the motor is stopped and disabled
+//! # Ok::<(), motor_driver_hal::MotorDriverError>(())
+//! ```
+//!
+//! Commanding a driver that is not enabled does not compile:
+//!
+//! ```rust,compile_fail,E0599
+//! # use core::convert::Infallible;
+//! # use embedded_hal::pwm::{ErrorType, SetDutyCycle};
+//! # use motor_driver_hal::{HBridgeMotorDriver, MotorDriver, NoEncoder, NoPin};
+//! # struct Pwm;
+//! # impl ErrorType for Pwm { type Error = Infallible; }
+//! # impl SetDutyCycle for Pwm {
+//! #     fn max_duty_cycle(&self) -> u16 { 1000 }
+//! #     fn set_duty_cycle(&mut self, _duty: u16) -> Result<(), Infallible> { Ok(()) }
+//! # }
+//! # let (enable_pin, pwm_channel) = (NoPin, Pwm);
+//! let mut motor = HBridgeMotorDriver::<_, NoPin, _, Pwm, NoEncoder, NoEncoder>::typed_builder()
+//!     .with_enable(enable_pin)
+//!     .with_pwm(pwm_channel)
+//!     .build_and_init()?;
+//! motor.set_speed(300)?; // error: no method `set_speed` on `Initialized<_>`
+//! # Ok::<(), motor_driver_hal::MotorDriverError>(())
+//! ```
+//!
+//! Neither does leaving the type state through the guard, e.g. disabling
+//! the driver while the guard still claims it is enabled:
+//!
+//! ```rust,compile_fail,E0599
+//! # use core::convert::Infallible;
+//! # use embedded_hal::pwm::{ErrorType, SetDutyCycle};
+//! # use motor_driver_hal::{HBridgeMotorDriver, MotorDriver, NoEncoder, NoPin};
+//! # struct Pwm;
+//! # impl ErrorType for Pwm { type Error = Infallible; }
+//! # impl SetDutyCycle for Pwm {
+//! #     fn max_duty_cycle(&self) -> u16 { 1000 }
+//! #     fn set_duty_cycle(&mut self, _duty: u16) -> Result<(), Infallible> { Ok(()) }
+//! # }
+//! # let (enable_pin, pwm_channel) = (NoPin, Pwm);
+//! let mut motor = HBridgeMotorDriver::<_, NoPin, _, Pwm, NoEncoder, NoEncoder>::typed_builder()
+//!     .with_enable(enable_pin)
+//!     .with_pwm(pwm_channel)
+//!     .build_and_init()?;
+//! let mut running = motor.enable()?;
+//! running.initialize()?; // error: no method `initialize` on `Enabled<_>`
+//! # Ok::<(), motor_driver_hal::MotorDriverError>(())
+//! ```
+
+use crate::clock::Clock;
+use crate::current::PowerSense;
+use crate::driver::{HBridgeMotorDriver, HBridgeMotorDriverBuilder};
+use crate::velocity::Velocity;
+use crate::{ConfigError, FaultStatus, HalCause, MotorDriver, MotorDriverError};
+use core::marker::PhantomData;
+use core::ops::Deref;
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal::pwm::SetDutyCycle;
+
+/// Marker for a required builder input that has not been supplied.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Missing;
+
+/// Marker for a required builder input that has been supplied.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Provided;
+
+/// Builder that only builds once the enable pin and PWM channel are supplied.
+///
+/// `EN` and `PW` are [`Missing`] or [`Provided`]. Options other than the
+/// required pins are set on the wrapped builder with `configure()`.
+pub struct TypedBuilder<B, EN = Missing, PW = Missing> {
+    builder: B,
+    state: PhantomData<(EN, PW)>,
+}
+
+impl<B> TypedBuilder<B> {
+    /// Wraps a builder whose required pins are still to be supplied.
+    pub fn new(builder: B) -> Self {
+        Self {
+            builder,
+            state: PhantomData,
+        }
+    }
+}
+
+impl<B, EN, PW> TypedBuilder<B, EN, PW> {
+    /// Applies optional settings to the wrapped builder.
+    ///
+    /// The closure may change the builder type, e.g. with `with_clock()`.
+    pub fn configure<B2>(self, f: impl FnOnce(B) -> B2) -> TypedBuilder<B2, EN, PW> {
+        TypedBuilder {
+            builder: f(self.builder),
+            state: PhantomData,
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+impl<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C, EN, PW>
+    TypedBuilder<HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>, EN, PW>
+{
+    /// Sets the enable pin.
+    pub fn with_enable(
+        self,
+        enable: E1,
+    ) -> TypedBuilder<HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>, Provided, PW>
+    {
+        TypedBuilder {
+            builder: self.builder.with_enable(enable),
+            state: PhantomData,
+        }
+    }
+
+    /// Sets both enable pins.
+    pub fn with_dual_enable(
+        self,
+        enable1: E1,
+        enable2: E2,
+    ) -> TypedBuilder<HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>, Provided, PW>
+    {
+        TypedBuilder {
+            builder: self.builder.with_dual_enable(enable1, enable2),
+            state: PhantomData,
+        }
+    }
+
+    /// Sets the PWM channel.
+    pub fn with_pwm(
+        self,
+        pwm: P1,
+    ) -> TypedBuilder<HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>, EN, Provided>
+    {
+        TypedBuilder {
+            builder: self.builder.with_pwm(pwm),
+            state: PhantomData,
+        }
+    }
+
+    /// Sets both PWM channels.
+    pub fn with_dual_pwm(
+        self,
+        pwm1: P1,
+        pwm2: P2,
+    ) -> TypedBuilder<HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>, EN, Provided>
+    {
+        TypedBuilder {
+            builder: self.builder.with_dual_pwm(pwm1, pwm2),
+            state: PhantomData,
+        }
+    }
+}
+
+impl<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>
+    TypedBuilder<
+        HBridgeMotorDriverBuilder<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>,
+        Provided,
+        Provided,
+    >
+{
+    /// Builds the driver without initializing it.
+    ///
+    /// # Errors
+    ///
+    /// The type state guarantees the enable pin and PWM channel, but the
+    /// remaining settings are still checked by `try_build()`, whose
+    /// `ConfigError` is returned.
+    #[allow(clippy::type_complexity)]
+    pub fn build(self) -> Result<HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>, ConfigError>
+    where
+        P1: SetDutyCycle<Error: HalCause>,
+        P2: SetDutyCycle<Error: HalCause>,
+    {
+        self.builder.try_build()
+    }
+
+    /// Builds and initializes the driver.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Config` with the `ConfigError` if the remaining settings fail
+    /// `try_build()`, or the hardware error if initialization fails.
+    #[allow(clippy::type_complexity)]
+    pub fn build_and_init(
+        self,
+    ) -> Result<
+        Initialized<HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>>,
+        MotorDriverError,
+    >
+    where
+        E1: OutputPin<Error: HalCause>,
+        E2: OutputPin<Error: HalCause>,
+        P1: SetDutyCycle<Error: HalCause>,
+        P2: SetDutyCycle<Error: HalCause>,
+        Enc1: InputPin<Error: HalCause>,
+        Enc2: InputPin<Error: HalCause>,
+        D: OutputPin<Error: HalCause>,
+        F: InputPin<Error: HalCause>,
+        S: PowerSense,
+        C: Clock,
+    {
+        Initialized::new(self.builder.try_build()?)
+    }
+}
+
+/// Initialized driver that cannot be commanded until it is enabled.
+///
+/// Dereferences to the driver for read-only queries such as `get_speed()`
+/// or `state()`.
+#[derive(Debug)]
+pub struct Initialized<M> {
+    driver: M,
+}
+
+impl<M: MotorDriver> Initialized<M> {
+    /// Initializes a driver.
+    ///
+    /// # Errors
+    ///
+    /// Returns the driver's error if `initialize()` fails.
+    pub fn new(mut driver: M) -> Result<Self, M::Error> {
+        driver.initialize()?;
+        Ok(Self { driver })
+    }
+
+    /// Enables the driver for as long as the returned guard lives.
+    ///
+    /// # Errors
+    ///
+    /// Returns the driver's error if `enable()` fails, e.g. while a fault or
+    /// emergency stop is latched.
+    pub fn enable(&mut self) -> Result<Enabled<'_, M>, M::Error> {
+        self.driver.enable()?;
+        Ok(Enabled {
+            driver: &mut self.driver,
+            active: true,
+        })
+    }
+
+    /// Returns the driver, leaving the type-state API.
+    pub fn into_inner(self) -> M {
+        self.driver
+    }
+}
+
+impl<M> Deref for Initialized<M> {
+    type Target = M;
+
+    fn deref(&self) -> &M {
+        &self.driver
+    }
+}
+
+/// Enabled driver accepting speed commands.
+///
+/// Dropping the guard stops and disables the motor. Dereferences to the
+/// driver for read-only queries; only the calls that keep the driver
+/// enabled, such as `update()` or `read_encoder()`, are forwarded mutably.
+#[derive(Debug)]
+pub struct Enabled<'a, M: MotorDriver> {
+    driver: &'a mut M,
+    active: bool,
+}
+
+impl<M: MotorDriver> Enabled<'_, M> {
+    /// Sets the motor speed, see [`MotorDriver::set_speed`].
+    pub fn set_speed(&mut self, speed: i16) -> Result<(), M::Error> {
+        self.driver.set_speed(speed)
+    }
+
+    /// Sets the motor direction, see [`MotorDriver::set_direction`].
+    pub fn set_direction(&mut self, forward: bool) -> Result<(), M::Error> {
+        self.driver.set_direction(forward)
+    }
+
+    /// Lets the motor coast to a stop and stays enabled.
+    pub fn stop(&mut self) -> Result<(), M::Error> {
+        self.driver.stop()
+    }
+
+    /// Brakes the motor and stays enabled.
+    pub fn brake(&mut self) -> Result<(), M::Error> {
+        self.driver.brake()
+    }
+
+    /// Stops and disables the motor.
+    ///
+    /// # Errors
+    ///
+    /// Returns the driver's error if stopping or disabling fails.
+    pub fn disable(mut self) -> Result<(), M::Error> {
+        self.driver.stop()?;
+        self.driver.disable()?;
+        self.active = false;
+        Ok(())
+    }
+}
+
+#[allow(clippy::type_complexity)]
+impl<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C> Enabled<'_, HBridgeMotorDriver<E1, E2, P1, P2, Enc1, Enc2, D, F, S, C>>
+where
+    E1: OutputPin<Error: HalCause>,
+    E2: OutputPin<Error: HalCause>,
+    P1: SetDutyCycle<Error: HalCause>,
+    P2: SetDutyCycle<Error: HalCause>,
+    Enc1: InputPin<Error: HalCause>,
+    Enc2: InputPin<Error: HalCause>,
+    D: OutputPin<Error: HalCause>,
+    F: InputPin<Error: HalCause>,
+    S: PowerSense,
+    C: Clock,
+{
+    /// Runs the periodic supervision, see [`HBridgeMotorDriver::update`].
+    pub fn update(&mut self) -> Result<i16, MotorDriverError> {
+        self.driver.update()
+    }
+
+    /// Restarts the command watchdog, see [`HBridgeMotorDriver::feed`].
+    pub fn feed(&mut self) {
+        self.driver.feed()
+    }
+
+    /// Samples the encoder pins, see [`HBridgeMotorDriver::read_encoder`].
+    pub fn read_encoder(&mut self) -> Result<(), MotorDriverError> {
+        self.driver.read_encoder()
+    }
+
+    /// Resets the encoder position to zero.
+    pub fn reset_encoder(&mut self) {
+        self.driver.reset_encoder()
+    }
+
+    /// Estimates the velocity, see [`HBridgeMotorDriver::get_velocity`].
+    pub fn get_velocity(&mut self) -> Result<Velocity, MotorDriverError> {
+        self.driver.get_velocity()
+    }
+
+    /// Sets the target pulse count for position control.
+    pub fn set_target_pulse(&mut self, target: i32) {
+        self.driver.set_target_pulse(target)
+    }
+
+    /// Samples the fault inputs, see [`HBridgeMotorDriver::check_fault`].
+    pub fn check_fault(&mut self) -> Result<FaultStatus, MotorDriverError> {
+        self.driver.check_fault()
+    }
+
+    /// Reads the current sense, see [`HBridgeMotorDriver::sample_current`].
+    pub fn sample_current(&mut self) -> Result<f32, MotorDriverError> {
+        self.driver.sample_current()
+    }
+}
+
+impl<M: MotorDriver> Deref for Enabled<'_, M> {
+    type Target = M;
+
+    fn deref(&self) -> &M {
+        self.driver
+    }
+}
+
+impl<M: MotorDriver> Drop for Enabled<'_, M> {
+    fn drop(&mut self) {
+        if self.active {
+            let _ = self.driver.stop();
+            let _ = self.driver.disable();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Log, MockPin, MockPwm, MAX_DUTY};
+    use crate::NoEncoder;
+
+    #[test]
+    fn build_checks_the_remaining_settings() {
+        let log = Log::new();
+        let result = HBridgeMotorDriver::<_, MockPin, _, MockPwm, NoEncoder, NoEncoder>::typed_builder()
+            .with_enable(log.pin("en"))
+            .with_pwm(log.pwm("pwm"))
+            .configure(|builder| builder.with_max_duty(MAX_DUTY + 1))
+            .build();
+        assert_eq!(
+            result.err(),
+            Some(ConfigError::MaxDutyExceedsChannel { max_duty: MAX_DUTY + 1, channel_max: MAX_DUTY })
+        );
+    }
+
+    #[test]
+    fn enabled_guard_forwards_update_and_disables_on_drop() {
+        let log = Log::new();
+        let mut motor = HBridgeMotorDriver::<_, MockPin, _, MockPwm, NoEncoder, NoEncoder>::typed_builder()
+            .with_enable(log.pin("en"))
+            .with_pwm(log.pwm("pwm"))
+            .build_and_init()
+            .unwrap();
+
+        let mut running = motor.enable().unwrap();
+        running.set_speed(300).unwrap();
+        running.feed();
+        assert_eq!(running.update(), Ok(300));
+        assert_eq!(log.level("en"), Some(true));
+
+        drop(running);
+        assert_eq!(log.level("en"), Some(false));
+        assert_eq!(log.duty("pwm"), Some(0));
+        assert_eq!(motor.state(), crate::DriverState::Disabled);
+    }
+}
@@ -0,0 +1,266 @@
+//! Encoder-based velocity estimation.
+
+use core::f32::consts::TAU;
+
+/// Motor velocity expressed in several units.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Velocity {
+    /// Encoder pulses per second.
+    pub pulses_per_sec: f32,
+    /// Revolutions per minute.
+    pub rpm: f32,
+    /// Radians per second.
+    pub rad_per_sec: f32,
+}
+
+impl Velocity {
+    /// Converts a pulse rate into all velocity units.
+    ///
+    /// # Arguments
+    ///
+    /// * `pulses_per_sec` - Signed encoder pulse rate
+    /// * `ppr` - Encoder pulses per revolution (must be non-zero)
+    pub fn from_pulses_per_sec(pulses_per_sec: f32, ppr: u16) -> Self {
+        let revolutions_per_sec = pulses_per_sec / ppr as f32;
+        Self {
+            pulses_per_sec,
+            rpm: revolutions_per_sec * 60.0,
+            rad_per_sec: revolutions_per_sec * TAU,
+        }
+    }
+}
+
+/// Method used to turn encoder counts into a velocity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VelocityEstimation {
+    /// Pulse count difference over a fixed time window.
+    ///
+    /// Accurate at medium and high speeds, but the resolution at low speed
+    /// is limited to one pulse per window.
+    Windowed {
+        /// Window length in microseconds
+        window_us: u32,
+    },
+    /// Time between successive encoder edges.
+    ///
+    /// Resolves very low speeds where few pulses arrive per window. While no
+    /// edge arrives the estimate decays as if the next edge were imminent,
+    /// and drops to zero after `timeout_us`.
+    EdgePeriod {
+        /// Time without edges after which the motor is considered stopped, in microseconds
+        timeout_us: u32,
+    },
+}
+
+impl Default for VelocityEstimation {
+    fn default() -> Self {
+        VelocityEstimation::Windowed { window_us: 10_000 }
+    }
+}
+
+/// Velocity estimator fed with timestamped encoder counts.
+///
+/// # Example
+///
+/// ```rust
+/// use motor_driver_hal::velocity::{VelocityEstimation, VelocityEstimator};
+///
+/// let mut estimator = VelocityEstimator::new(VelocityEstimation::Windowed { window_us: 10_000 });
+/// estimator.update(0, 0);
+/// estimator.update(50, 10_000);
+/// assert_eq!(estimator.pulses_per_sec(), 5000.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VelocityEstimator {
+    method: VelocityEstimation,
+    pulses_per_sec: f32,
+    reference: Option<(i32, u64)>,
+}
+
+impl VelocityEstimator {
+    /// Creates an estimator reporting zero velocity until enough samples arrive.
+    pub fn new(method: VelocityEstimation) -> Self {
+        Self {
+            method,
+            pulses_per_sec: 0.0,
+            reference: None,
+        }
+    }
+
+    /// Returns the configured estimation method.
+    pub fn method(&self) -> VelocityEstimation {
+        self.method
+    }
+
+    /// Feeds the current pulse count and timestamp into the estimator.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Current encoder pulse count
+    /// * `now_us` - Monotonic timestamp in microseconds
+    pub fn update(&mut self, count: i32, now_us: u64) {
+        let Some((ref_count, ref_time)) = self.reference else {
+            self.reference = Some((count, now_us));
+            return;
+        };
+        let elapsed = now_us.saturating_sub(ref_time);
+        let delta = count.wrapping_sub(ref_count);
+
+        match self.method {
+            VelocityEstimation::Windowed { window_us } => {
+                if elapsed > 0 && elapsed >= window_us as u64 {
+                    self.pulses_per_sec = delta as f32 * 1e6 / elapsed as f32;
+                    self.reference = Some((count, now_us));
+                }
+            }
+            VelocityEstimation::EdgePeriod { timeout_us } => {
+                if delta != 0 {
+                    if elapsed > 0 {
+                        self.pulses_per_sec = delta as f32 * 1e6 / elapsed as f32;
+                    }
+                    self.reference = Some((count, now_us));
+                } else if elapsed > timeout_us as u64 {
+                    self.pulses_per_sec = 0.0;
+                } else if elapsed > 0 {
+                    // The next edge cannot be closer than `elapsed`, so bound the estimate
+                    let bound = 1e6 / elapsed as f32;
+                    if self.pulses_per_sec.abs() > bound {
+                        self.pulses_per_sec = bound.copysign(self.pulses_per_sec);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the latest estimate in pulses per second.
+    pub fn pulses_per_sec(&self) -> f32 {
+        self.pulses_per_sec
+    }
+
+    /// Clears the estimate and sample history.
+    pub fn reset(&mut self) {
+        self.pulses_per_sec = 0.0;
+        self.reference = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, MockClock};
+    use crate::driver::HBridgeMotorDriverBuilder;
+    use crate::mock::{Log, MockEncoder, MockPin};
+    use crate::MotorDriver;
+
+    fn feed(estimator: &mut VelocityEstimator, clock: &MockClock, count: i32) -> f32 {
+        estimator.update(count, clock.now_micros());
+        estimator.pulses_per_sec()
+    }
+
+    #[test]
+    fn converts_pulse_rate_to_rpm_and_rad_per_sec() {
+        let velocity = Velocity::from_pulses_per_sec(500.0, 100);
+        assert_eq!(velocity.pulses_per_sec, 500.0);
+        assert_eq!(velocity.rpm, 300.0);
+        assert!((velocity.rad_per_sec - 10.0 * core::f32::consts::PI).abs() < 1e-4);
+
+        let reverse = Velocity::from_pulses_per_sec(-250.0, 1000);
+        assert_eq!(reverse.rpm, -15.0);
+        assert!((reverse.rad_per_sec + 0.5 * core::f32::consts::PI).abs() < 1e-5);
+    }
+
+    #[test]
+    fn windowed_estimate_updates_once_per_window() {
+        let clock = MockClock::new();
+        let mut estimator = VelocityEstimator::new(VelocityEstimation::Windowed { window_us: 10_000 });
+        assert_eq!(feed(&mut estimator, &clock, 0), 0.0);
+
+        // Samples inside the window keep the previous estimate
+        clock.advance(4_000);
+        assert_eq!(feed(&mut estimator, &clock, 30), 0.0);
+
+        clock.advance(6_000);
+        assert_eq!(feed(&mut estimator, &clock, 50), 5000.0);
+
+        // The next window is measured from the last completed one, also when it overruns
+        clock.advance(20_000);
+        assert_eq!(feed(&mut estimator, &clock, 10), -2000.0);
+
+        clock.advance(10_000);
+        assert_eq!(feed(&mut estimator, &clock, 10), 0.0);
+    }
+
+    #[test]
+    fn edge_period_estimate_decays_and_times_out() {
+        let clock = MockClock::new();
+        let mut estimator = VelocityEstimator::new(VelocityEstimation::EdgePeriod { timeout_us: 100_000 });
+        feed(&mut estimator, &clock, 0);
+
+        // One edge every 2 ms
+        clock.advance(2_000);
+        assert_eq!(feed(&mut estimator, &clock, 1), 500.0);
+        clock.advance(2_000);
+        assert_eq!(feed(&mut estimator, &clock, 2), 500.0);
+
+        // Without edges the estimate is bounded by one pulse over the time waited so far
+        clock.advance(1_000);
+        assert_eq!(feed(&mut estimator, &clock, 2), 500.0);
+        clock.advance(9_000);
+        assert_eq!(feed(&mut estimator, &clock, 2), 100.0);
+        clock.advance(90_000);
+        assert_eq!(feed(&mut estimator, &clock, 2), 10.0);
+
+        clock.advance(1);
+        assert_eq!(feed(&mut estimator, &clock, 2), 0.0);
+
+        // A slow reverse edge after the timeout measures the whole gap
+        clock.advance(99_999);
+        assert_eq!(feed(&mut estimator, &clock, 1), -5.0);
+    }
+
+    #[test]
+    fn reset_clears_estimate_and_reference() {
+        let clock = MockClock::new();
+        let mut estimator = VelocityEstimator::new(VelocityEstimation::Windowed { window_us: 1_000 });
+        feed(&mut estimator, &clock, 0);
+        clock.advance(1_000);
+        assert_eq!(feed(&mut estimator, &clock, 10), 10_000.0);
+
+        estimator.reset();
+        assert_eq!(estimator.pulses_per_sec(), 0.0);
+
+        // The first sample after a reset only sets the reference
+        clock.advance(1_000);
+        assert_eq!(feed(&mut estimator, &clock, 1_000), 0.0);
+        clock.advance(1_000);
+        assert_eq!(feed(&mut estimator, &clock, 1_001), 1_000.0);
+    }
+
+    #[test]
+    fn driver_reports_velocity_on_its_clock() {
+        let log = Log::new();
+        let clock = MockClock::new();
+        let mut encoder = MockEncoder::new(&log);
+        let (a, b) = encoder.pins();
+        let mut motor = HBridgeMotorDriverBuilder::<_, MockPin, _, _, _, _>::new()
+            .with_enable(log.pin("en"))
+            .with_dual_pwm(log.pwm("fwd"), log.pwm("rev"))
+            .with_encoder(a, b)
+            .with_ppr(100)
+            .with_velocity_estimation(VelocityEstimation::Windowed { window_us: 10_000 })
+            .with_clock(&clock)
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+
+        assert_eq!(motor.get_velocity().unwrap(), Velocity::default());
+        encoder.turn(20, || motor.read_encoder().unwrap());
+        clock.advance(10_000);
+        let velocity = motor.get_velocity().unwrap();
+        assert_eq!(velocity.pulses_per_sec, 2000.0);
+        assert_eq!(velocity.rpm, 1200.0);
+
+        motor.reset_encoder();
+        assert_eq!(motor.get_velocity().unwrap(), Velocity::default());
+    }
+}
@@ -0,0 +1,110 @@
+//! Command watchdog that stops a motor when commands stop arriving.
+
+use crate::MotorDriverError;
+
+/// What a driver does when its watchdog expires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WatchdogAction {
+    /// Coast to a stop and disable the bridge.
+    #[default]
+    Stop,
+    /// Short the motor windings to brake, then disable the bridge.
+    ///
+    /// Bridges that float their outputs when disabled release the brake
+    /// once the enable pins drop, so the brake only slows the motor briefly.
+    Brake,
+}
+
+/// Deadman timer fed by every speed command.
+///
+/// The timer is armed by the first `feed()` or `update()`. Once `timeout_ms`
+/// passes without a feed it expires, and stays expired until fed again.
+/// Nothing runs in the background: a driver only checks the timeout inside
+/// its `update()`, which must keep being called for the watchdog to act.
+///
+/// # Example
+///
+/// ```rust
+/// use motor_driver_hal::watchdog::Watchdog;
+///
+/// let mut watchdog = Watchdog::new(100).unwrap();
+/// watchdog.feed(0);
+/// assert!(!watchdog.update(60_000));
+/// watchdog.feed(60_000);
+/// assert!(!watchdog.update(150_000));
+/// assert!(watchdog.update(160_000));
+/// assert!(!watchdog.update(200_000));
+/// assert!(watchdog.is_expired());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchdog {
+    timeout_us: u64,
+    action: WatchdogAction,
+    last_feed: Option<u64>,
+    expired: bool,
+}
+
+impl Watchdog {
+    /// Creates a watchdog that stops and disables the motor on expiry.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout_ms` - Longest allowed gap between commands
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` if `timeout_ms` is zero.
+    pub fn new(timeout_ms: u32) -> Result<Self, MotorDriverError> {
+        if timeout_ms == 0 {
+            return Err(MotorDriverError::InvalidConfiguration);
+        }
+
+        Ok(Self {
+            timeout_us: timeout_ms as u64 * 1000,
+            action: WatchdogAction::Stop,
+            last_feed: None,
+            expired: false,
+        })
+    }
+
+    /// Sets what the driver does on expiry.
+    pub fn with_action(mut self, action: WatchdogAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Returns the action taken on expiry.
+    pub fn action(&self) -> WatchdogAction {
+        self.action
+    }
+
+    /// Returns the timeout in microseconds.
+    pub fn timeout_us(&self) -> u64 {
+        self.timeout_us
+    }
+
+    /// Returns `true` if the timeout elapsed since the last feed.
+    pub fn is_expired(&self) -> bool {
+        self.expired
+    }
+
+    /// Restarts the timer at `now_us` and clears an expiry.
+    pub fn feed(&mut self, now_us: u64) {
+        self.last_feed = Some(now_us);
+        self.expired = false;
+    }
+
+    /// Checks the timer at `now_us`.
+    ///
+    /// # Returns
+    ///
+    /// `true` only on the update that detects the expiry, so the driver acts once
+    pub fn update(&mut self, now_us: u64) -> bool {
+        let last_feed = *self.last_feed.get_or_insert(now_us);
+        if self.expired || now_us.saturating_sub(last_feed) < self.timeout_us {
+            return false;
+        }
+        self.expired = true;
+        true
+    }
+}
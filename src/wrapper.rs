@@ -1,5 +1,13 @@
-use crate::{MotorDriver, MotorDriverError};
-use embedded_hal::digital::OutputPin;
+use crate::clock::{Clock, DefaultClock};
+use crate::current::{CurrentLimit, NoSense, PowerSense};
+use crate::driver::{DecayMode, DriveMode, NoEncoder, NoPin};
+#[cfg(feature = "std")]
+use crate::estop::{EStop, EStopTarget};
+use crate::ramp::RampLimiter;
+use crate::state::DriverState;
+use crate::watchdog::{Watchdog, WatchdogAction};
+use crate::{ConfigError, FaultStatus, HalCause, MotorDriver, MotorDriverError, Operation, Signal};
+use embedded_hal::digital::{InputPin, OutputPin};
 use embedded_hal::pwm::SetDutyCycle;
 
 pub enum EnablePins<E1, E2> {
@@ -22,46 +30,304 @@ pub enum MotorDirection {
     Coast,
 }
 
-pub struct MotorDriverWrapper<E1, E2, P1, P2> {
+pub struct MotorDriverWrapper<E1, E2, P1, P2, D = NoPin, F = NoEncoder, S = NoSense, C = DefaultClock> {
     enable_pins: EnablePins<E1, E2>,
     pwm_channels: PwmChannels<P1, P2>,
     max_duty: u16,
     current_speed: i16,
-    current_pulse: i16,
     ppr: i16,
     direction: MotorDirection,
-    initialized: bool,
+    state: DriverState,
+    ramp: Option<RampLimiter>,
+    decay_mode: DecayMode,
+    direction_pin: Option<D>,
+    direction_pin2: Option<D>,
+    pin_direction: Option<MotorDirection>,
+    drive_direction: Option<MotorDirection>,
+    dead_time_us: u32,
+    dead_time_start: Option<u64>,
+    drive_mode: DriveMode,
+    fault_pin: Option<F>,
+    fault: FaultStatus,
+    current_sense: Option<S>,
+    current_limit: Option<CurrentLimit>,
+    watchdog: Option<Watchdog>,
+    #[cfg(feature = "std")]
+    estop: Option<EStop>,
+    clock: C,
 }
 
 impl<E1, E2, P1, P2> MotorDriverWrapper<E1, E2, P1, P2>
 where
-    E1: OutputPin,
-    E2: OutputPin,
-    P1: SetDutyCycle,
-    P2: SetDutyCycle,
+    E1: OutputPin<Error: HalCause>,
+    E2: OutputPin<Error: HalCause>,
+    P1: SetDutyCycle<Error: HalCause>,
+    P2: SetDutyCycle<Error: HalCause>,
 {
     pub fn builder() -> MotorDriverBuilder<E1, E2, P1, P2> {
         MotorDriverBuilder::new()
     }
+}
+
+impl<E1, E2, P1, P2, D, F, S, C> MotorDriverWrapper<E1, E2, P1, P2, D, F, S, C>
+where
+    E1: OutputPin<Error: HalCause>,
+    E2: OutputPin<Error: HalCause>,
+    P1: SetDutyCycle<Error: HalCause>,
+    P2: SetDutyCycle<Error: HalCause>,
+    D: OutputPin<Error: HalCause>,
+    F: InputPin<Error: HalCause>,
+    S: PowerSense,
+    C: Clock,
+{
+    /// Advance the ramp limiter and apply the resulting duty cycle.
+    /// 
+    /// Call this periodically while a ramp or dead time is configured so the
+    /// output keeps moving towards the speed last passed to `set_speed()` and
+    /// the PWM is re-enabled after a direction flip. With a current limit it
+    /// also folds back the duty or trips the driver, returning `OverCurrent`,
+    /// and with a watchdog it returns `WatchdogTimeout` once commands stop.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// motor.set_speed(-800)?;
+    /// loop {
+    ///     motor.update()?;
+    ///     delay.delay_ms(10);
+    /// }
+    /// ```
+    pub fn update(&mut self) -> Result<i16, MotorDriverError> {
+        if !self.state.is_initialized() {
+            return Err(MotorDriverError::NotInitialized);
+        }
+        self.check_estop()?;
+        self.check_faulted()?;
+        self.check_watchdog()?;
+        self.limit_current()?;
+
+        if let Some(ref mut ramp) = self.ramp {
+            let speed = ramp.update(self.clock.now_micros());
+            if speed != self.current_speed {
+                self.apply_speed(speed)?;
+            }
+        }
+        if self.dead_time_start.is_some() {
+            self.update_pwm()?;
+        }
+        Ok(self.current_speed)
+    }
+
+    /// Current decay mode used by dual-PWM configurations.
+    pub fn decay_mode(&self) -> DecayMode {
+        self.decay_mode
+    }
+
+    /// Change the current decay mode and re-apply the current speed.
+    pub fn set_decay_mode(&mut self, mode: DecayMode) -> Result<(), MotorDriverError> {
+        self.decay_mode = mode;
+        if self.state.is_initialized() {
+            self.update_pwm()?;
+        }
+        Ok(())
+    }
+
+    /// Sample the fault pin and current sense and return the latched fault status.
+    /// 
+    /// A low level on the fault pin latches `FaultStatus::GENERIC` and a
+    /// current above the sense limit latches `FaultStatus::OVERCURRENT`.
+    pub fn check_fault(&mut self) -> Result<FaultStatus, MotorDriverError> {
+        if let Some(ref mut pin) = self.fault_pin {
+            if pin.is_low().map_err(MotorDriverError::gpio(Signal::Fault, Operation::Read))? {
+                self.fault |= FaultStatus::GENERIC;
+            }
+        }
+        if let Some(ref mut sense) = self.current_sense {
+            self.fault |= sense.sample()?;
+        }
+        Ok(self.fault)
+    }
+
+    /// Clear the latched faults and return whatever is still asserted.
+    /// 
+    /// A `Faulted` driver with no remaining fault is stopped and returns to
+    /// `Disabled`; call `enable()` to drive it again.
+    pub fn clear_fault(&mut self) -> Result<FaultStatus, MotorDriverError> {
+        self.fault = FaultStatus::empty();
+        if let Some(ref mut sense) = self.current_sense {
+            sense.reset();
+        }
+        if let Some(ref mut limit) = self.current_limit {
+            limit.reset();
+        }
+
+        let status = self.check_fault()?;
+        if status.is_empty() && self.state == DriverState::Faulted {
+            self.stop()?;
+            self.disable()?;
+            self.state = DriverState::Disabled;
+        }
+        Ok(status)
+    }
+
+    /// Lifecycle state of the driver, `EStopped` as soon as an attached emergency stop latches.
+    pub fn state(&self) -> DriverState {
+        #[cfg(feature = "std")]
+        if self.state.is_initialized() && self.estop.as_ref().is_some_and(EStop::is_triggered) {
+            return DriverState::EStopped;
+        }
+        self.state
+    }
+
+    /// Sample the current sensor and return the motor current in amps.
+    /// 
+    /// Latches the faults reported by the sensor, such as `FaultStatus::OVERCURRENT`.
+    pub fn sample_current(&mut self) -> Result<f32, MotorDriverError> {
+        let sense = self.current_sense.as_mut().ok_or(MotorDriverError::HardwareFault)?;
+        self.fault |= sense.sample()?;
+        sense.current().ok_or(MotorDriverError::HardwareFault)
+    }
+
+    /// Restart the command watchdog without changing the speed.
+    /// 
+    /// `set_speed()` and `enable()` feed the watchdog too.
+    pub fn feed(&mut self) {
+        let now = self.clock.now_micros();
+        if let Some(ref mut watchdog) = self.watchdog {
+            watchdog.feed(now);
+        }
+    }
+
+    /// Whether the command watchdog has expired and not been fed since.
+    pub fn is_watchdog_expired(&self) -> bool {
+        self.watchdog.is_some_and(|watchdog| watchdog.is_expired())
+    }
+
+    /// Duty fold-back factor applied by the current limit, 1.0 without one.
+    pub fn current_limit_scale(&self) -> f32 {
+        match self.current_limit {
+            Some(ref limit) => limit.scale(),
+            None => 1.0,
+        }
+    }
+
+    /// Speed the motor is ramping towards, equal to the current speed without a ramp.
+    pub fn target_speed(&self) -> i16 {
+        match self.ramp {
+            Some(ref ramp) => ramp.target(),
+            None => self.current_speed,
+        }
+    }
+
+    fn apply_speed(&mut self, speed: i16) -> Result<(), MotorDriverError> {
+        self.current_speed = speed;
+        if speed < 0 {
+            self.direction = MotorDirection::Reverse;
+        } else if speed > 0 {
+            self.direction = MotorDirection::Forward;
+        }
+        
+        self.settle_state();
+        self.update_pwm()
+    }
+
+    /// Enter `Faulted` if sampling the fault inputs latched a fault.
+    fn check_faulted(&mut self) -> Result<(), MotorDriverError> {
+        if let Some(error) = self.check_fault()?.error() {
+            self.state = DriverState::Faulted;
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    /// Move between `Enabled` and `Running` to match the commanded speed.
+    fn settle_state(&mut self) {
+        if self.state.can_drive() {
+            self.state = if self.current_speed != 0 || self.target_speed() != 0 {
+                DriverState::Running
+            } else {
+                DriverState::Enabled
+            };
+        }
+    }
+
+    fn limit_current(&mut self) -> Result<(), MotorDriverError> {
+        let current = match (&self.current_limit, &self.current_sense) {
+            (Some(_), Some(sense)) => sense.current(),
+            _ => None,
+        };
+        let (Some(current), Some(limit)) = (current, self.current_limit.as_mut()) else {
+            return Ok(());
+        };
+
+        let previous = limit.scale();
+        match limit.update(current, self.clock.now_micros()) {
+            Ok(scale) if scale != previous => self.update_pwm(),
+            Ok(_) => Ok(()),
+            Err(error) => {
+                self.fault |= FaultStatus::OVERCURRENT;
+                self.state = DriverState::Faulted;
+                let stopped = self.stop();
+                let disabled = self.disable();
+                stopped.and(disabled)?;
+                Err(error)
+            }
+        }
+    }
+
+    fn check_estop(&mut self) -> Result<(), MotorDriverError> {
+        #[cfg(feature = "std")]
+        if self.estop.as_ref().is_some_and(EStop::poll_from_driver) {
+            // Try both so a failing PWM write still drops the enable pins
+            let braked = self.brake();
+            let disabled = self.disable();
+            self.state = DriverState::EStopped;
+            braked.and(disabled)?;
+            return Err(MotorDriverError::EmergencyStop);
+        }
+        Ok(())
+    }
+
+    fn check_watchdog(&mut self) -> Result<(), MotorDriverError> {
+        let now = self.clock.now_micros();
+        let Some(ref mut watchdog) = self.watchdog else {
+            return Ok(());
+        };
+        let action = watchdog.action();
+        if watchdog.update(now) {
+            let halted = match action {
+                WatchdogAction::Stop => self.stop(),
+                WatchdogAction::Brake => self.brake(),
+            };
+            // Try both so a failing PWM write still drops the enable pins
+            let disabled = self.disable();
+            halted.and(disabled)?;
+            return Err(MotorDriverError::WatchdogTimeout);
+        }
+        if watchdog.is_expired() {
+            return Err(MotorDriverError::WatchdogTimeout);
+        }
+        Ok(())
+    }
 
     fn control_enable(&mut self, enable: bool) -> Result<(), MotorDriverError> {
         match &mut self.enable_pins {
             EnablePins::None => Ok(()),
             EnablePins::Single(pin) => {
                 if enable {
-                    pin.set_high().map_err(|_| MotorDriverError::GpioError)?;
+                    pin.set_high().map_err(MotorDriverError::gpio(Signal::Enable, Operation::SetHigh))?;
                 } else {
-                    pin.set_low().map_err(|_| MotorDriverError::GpioError)?;
+                    pin.set_low().map_err(MotorDriverError::gpio(Signal::Enable, Operation::SetLow))?;
                 }
                 Ok(())
             }
             EnablePins::Dual(pin1, pin2) => {
                 if enable {
-                    pin1.set_high().map_err(|_| MotorDriverError::GpioError)?;
-                    pin2.set_high().map_err(|_| MotorDriverError::GpioError)?;
+                    pin1.set_high().map_err(MotorDriverError::gpio(Signal::Enable, Operation::SetHigh))?;
+                    pin2.set_high().map_err(MotorDriverError::gpio(Signal::Enable2, Operation::SetHigh))?;
                 } else {
-                    pin1.set_low().map_err(|_| MotorDriverError::GpioError)?;
-                    pin2.set_low().map_err(|_| MotorDriverError::GpioError)?;
+                    pin1.set_low().map_err(MotorDriverError::gpio(Signal::Enable, Operation::SetLow))?;
+                    pin2.set_low().map_err(MotorDriverError::gpio(Signal::Enable2, Operation::SetLow))?;
                 }
                 Ok(())
             }
@@ -70,172 +336,334 @@ where
 
     fn update_pwm(&mut self) -> Result<(), MotorDriverError> {
         let duty = self.current_speed.unsigned_abs().min(self.max_duty);
+        let duty = match self.current_limit {
+            Some(ref limit) => (duty as f32 * limit.scale()) as u16,
+            None => duty,
+        };
+
+        if self.drive_mode == DriveMode::LockedAntiphase {
+            return self.update_antiphase_pwm(duty);
+        }
+
+        if self.direction_pin.is_some() && self.pin_direction != Some(self.direction) {
+            let driving = matches!(self.direction, MotorDirection::Forward | MotorDirection::Reverse);
+            // Compare against the last driving direction so Forward -> Brake -> Reverse
+            // still waits out the dead time.
+            let flipped = driving && self.drive_direction.is_some_and(|last| last != self.direction);
+            if flipped && self.dead_time_us > 0 {
+                match self.pwm_channels {
+                    PwmChannels::None => {}
+                    PwmChannels::Single(ref mut pwm) => {
+                        pwm.set_duty_cycle(0).map_err(MotorDriverError::pwm(Signal::Pwm))?;
+                    }
+                    PwmChannels::Dual(ref mut pwm1, ref mut pwm2) => {
+                        pwm1.set_duty_cycle(0).map_err(MotorDriverError::pwm(Signal::Pwm))?;
+                        pwm2.set_duty_cycle(0).map_err(MotorDriverError::pwm(Signal::Pwm2))?;
+                    }
+                }
+                self.dead_time_start = Some(self.clock.now_micros());
+            }
+            self.write_direction_pins()?;
+            if driving {
+                self.drive_direction = Some(self.direction);
+            }
+        }
+
+        let duty = match self.dead_time_start {
+            Some(start) if self.clock.now_micros().saturating_sub(start) < self.dead_time_us as u64 => 0,
+            _ => {
+                self.dead_time_start = None;
+                duty
+            }
+        };
+        let duty = if self.direction == MotorDirection::Brake && self.direction_pin2.is_some() {
+            self.max_duty
+        } else {
+            duty
+        };
+
+        let (active, opposite) = match self.decay_mode {
+            DecayMode::Fast => (duty, 0),
+            DecayMode::Slow => (self.max_duty, self.max_duty - duty),
+        };
 
         match (&mut self.pwm_channels, self.direction) {
             (PwmChannels::None, _) => Ok(()),
             (PwmChannels::Single(pwm), _) => {
                 if self.direction == MotorDirection::Coast {
-                    pwm.set_duty_cycle(0).map_err(|_| MotorDriverError::PwmError)?;
+                    pwm.set_duty_cycle(0).map_err(MotorDriverError::pwm(Signal::Pwm))?;
                 } else {
-                    pwm.set_duty_cycle(duty).map_err(|_| MotorDriverError::PwmError)?;
+                    pwm.set_duty_cycle(duty).map_err(MotorDriverError::pwm(Signal::Pwm))?;
                 }
                 Ok(())
             }
             (PwmChannels::Dual(pwm1, pwm2), MotorDirection::Forward) => {
-                pwm1.set_duty_cycle(duty).map_err(|_| MotorDriverError::PwmError)?;
-                pwm2.set_duty_cycle(0).map_err(|_| MotorDriverError::PwmError)?;
+                pwm1.set_duty_cycle(active).map_err(MotorDriverError::pwm(Signal::Pwm))?;
+                pwm2.set_duty_cycle(opposite).map_err(MotorDriverError::pwm(Signal::Pwm2))?;
                 Ok(())
             }
             (PwmChannels::Dual(pwm1, pwm2), MotorDirection::Reverse) => {
-                pwm1.set_duty_cycle(0).map_err(|_| MotorDriverError::PwmError)?;
-                pwm2.set_duty_cycle(duty).map_err(|_| MotorDriverError::PwmError)?;
+                pwm1.set_duty_cycle(opposite).map_err(MotorDriverError::pwm(Signal::Pwm))?;
+                pwm2.set_duty_cycle(active).map_err(MotorDriverError::pwm(Signal::Pwm2))?;
                 Ok(())
             }
             (PwmChannels::Dual(pwm1, pwm2), MotorDirection::Brake) => {
-                pwm1.set_duty_cycle(self.max_duty).map_err(|_| MotorDriverError::PwmError)?;
-                pwm2.set_duty_cycle(self.max_duty).map_err(|_| MotorDriverError::PwmError)?;
+                pwm1.set_duty_cycle(self.max_duty).map_err(MotorDriverError::pwm(Signal::Pwm))?;
+                pwm2.set_duty_cycle(self.max_duty).map_err(MotorDriverError::pwm(Signal::Pwm2))?;
                 Ok(())
             }
             (PwmChannels::Dual(pwm1, pwm2), MotorDirection::Coast) => {
-                pwm1.set_duty_cycle(0).map_err(|_| MotorDriverError::PwmError)?;
-                pwm2.set_duty_cycle(0).map_err(|_| MotorDriverError::PwmError)?;
+                pwm1.set_duty_cycle(0).map_err(MotorDriverError::pwm(Signal::Pwm))?;
+                pwm2.set_duty_cycle(0).map_err(MotorDriverError::pwm(Signal::Pwm2))?;
                 Ok(())
             }
         }
     }
+
+    fn write_direction_pins(&mut self) -> Result<(), MotorDriverError> {
+        let (in1, in2) = match self.direction {
+            MotorDirection::Forward => (true, false),
+            MotorDirection::Reverse => (false, true),
+            MotorDirection::Brake => (true, true),
+            MotorDirection::Coast => (false, false),
+        };
+
+        if self.direction_pin2.is_none() && matches!(self.direction, MotorDirection::Brake | MotorDirection::Coast) {
+            return Ok(());
+        }
+        if let Some(ref mut pin) = self.direction_pin {
+            if in1 {
+                pin.set_high().map_err(MotorDriverError::gpio(Signal::Direction, Operation::SetHigh))?;
+            } else {
+                pin.set_low().map_err(MotorDriverError::gpio(Signal::Direction, Operation::SetLow))?;
+            }
+        }
+        if let Some(ref mut pin) = self.direction_pin2 {
+            if in2 {
+                pin.set_high().map_err(MotorDriverError::gpio(Signal::Direction2, Operation::SetHigh))?;
+            } else {
+                pin.set_low().map_err(MotorDriverError::gpio(Signal::Direction2, Operation::SetLow))?;
+            }
+        }
+        self.pin_direction = Some(self.direction);
+        Ok(())
+    }
+
+    fn update_antiphase_pwm(&mut self, duty: u16) -> Result<(), MotorDriverError> {
+        let half = self.max_duty / 2;
+        let forward = match self.direction {
+            MotorDirection::Forward => ((self.max_duty as u32 + duty as u32) / 2) as u16,
+            MotorDirection::Reverse => ((self.max_duty - duty) as u32 / 2) as u16,
+            MotorDirection::Brake | MotorDirection::Coast => half,
+        };
+
+        match (&mut self.pwm_channels, self.direction) {
+            (PwmChannels::None, _) => Ok(()),
+            (PwmChannels::Single(pwm), _) => {
+                pwm.set_duty_cycle(forward).map_err(MotorDriverError::pwm(Signal::Pwm))
+            }
+            (PwmChannels::Dual(pwm1, pwm2), MotorDirection::Brake) => {
+                pwm1.set_duty_cycle(self.max_duty).map_err(MotorDriverError::pwm(Signal::Pwm))?;
+                pwm2.set_duty_cycle(self.max_duty).map_err(MotorDriverError::pwm(Signal::Pwm2))
+            }
+            (PwmChannels::Dual(pwm1, pwm2), MotorDirection::Coast) => {
+                pwm1.set_duty_cycle(0).map_err(MotorDriverError::pwm(Signal::Pwm))?;
+                pwm2.set_duty_cycle(0).map_err(MotorDriverError::pwm(Signal::Pwm2))
+            }
+            (PwmChannels::Dual(pwm1, pwm2), _) => {
+                pwm1.set_duty_cycle(forward).map_err(MotorDriverError::pwm(Signal::Pwm))?;
+                pwm2.set_duty_cycle(self.max_duty - forward).map_err(MotorDriverError::pwm(Signal::Pwm2))
+            }
+        }
+    }
 }
 
-impl<E1, E2, P1, P2> MotorDriver for MotorDriverWrapper<E1, E2, P1, P2>
+impl<E1, E2, P1, P2, D, F, S, C> MotorDriver for MotorDriverWrapper<E1, E2, P1, P2, D, F, S, C>
 where
-    E1: OutputPin,
-    E2: OutputPin,
-    P1: SetDutyCycle,
-    P2: SetDutyCycle,
+    E1: OutputPin<Error: HalCause>,
+    E2: OutputPin<Error: HalCause>,
+    P1: SetDutyCycle<Error: HalCause>,
+    P2: SetDutyCycle<Error: HalCause>,
+    D: OutputPin<Error: HalCause>,
+    F: InputPin<Error: HalCause>,
+    S: PowerSense,
+    C: Clock,
 {
     type Error = MotorDriverError;
 
     fn initialize(&mut self) -> Result<(), Self::Error> {
         self.control_enable(false)?;
         
+        let neutral = if self.drive_mode == DriveMode::LockedAntiphase {
+            self.max_duty / 2
+        } else {
+            0
+        };
         match &mut self.pwm_channels {
             PwmChannels::None => {},
             PwmChannels::Single(pwm) => {
-                pwm.set_duty_cycle(0).map_err(|_| MotorDriverError::PwmError)?;
+                pwm.set_duty_cycle(neutral).map_err(MotorDriverError::pwm(Signal::Pwm))?;
             }
             PwmChannels::Dual(pwm1, pwm2) => {
-                pwm1.set_duty_cycle(0).map_err(|_| MotorDriverError::PwmError)?;
-                pwm2.set_duty_cycle(0).map_err(|_| MotorDriverError::PwmError)?;
+                pwm1.set_duty_cycle(0).map_err(MotorDriverError::pwm(Signal::Pwm))?;
+                pwm2.set_duty_cycle(0).map_err(MotorDriverError::pwm(Signal::Pwm2))?;
             }
         }
         
-        self.initialized = true;
+        if let Some(ref mut sense) = self.current_sense {
+            sense.init()?;
+        }
+        
+        self.state = DriverState::Disabled;
         Ok(())
     }
 
     fn set_speed(&mut self, speed: i16) -> Result<(), Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
         
         if speed.unsigned_abs() > self.max_duty {
             return Err(MotorDriverError::InvalidSpeed);
         }
-        
-        self.current_speed = speed;
-        if speed < 0 {
-            self.direction = MotorDirection::Reverse;
-        } else if speed > 0 {
-            self.direction = MotorDirection::Forward;
+        self.check_estop()?;
+        self.check_faulted()?;
+        if !self.state.can_drive() {
+            return Err(MotorDriverError::InvalidState);
         }
+        self.feed();
         
-        self.update_pwm()
+        let speed = match self.ramp {
+            Some(ref mut ramp) => ramp.set_target(speed, self.clock.now_micros()),
+            None => speed,
+        };
+        
+        self.apply_speed(speed)
     }
 
     fn set_direction(&mut self, forward: bool) -> Result<(), Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
-        
-        self.direction = if forward {
-            MotorDirection::Forward
-        } else {
-            MotorDirection::Reverse
+        self.check_estop()?;
+        self.check_faulted()?;
+        if !self.state.can_drive() {
+            return Err(MotorDriverError::InvalidState);
+        }
+
+        let magnitude = self.target_speed().saturating_abs();
+        if magnitude == 0 {
+            // Nothing is driven, so only the reported direction changes
+            self.direction = if forward {
+                MotorDirection::Forward
+            } else {
+                MotorDirection::Reverse
+            };
+            return Ok(());
+        }
+        let target = if forward { magnitude } else { -magnitude };
+        let speed = match self.ramp {
+            Some(ref mut ramp) => ramp.set_target(target, self.clock.now_micros()),
+            None => target,
         };
-        
-        self.update_pwm()
+        self.apply_speed(speed)
     }
 
     fn stop(&mut self) -> Result<(), Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
         
         self.current_speed = 0;
+        if let Some(ref mut ramp) = self.ramp {
+            ramp.reset(0);
+        }
         self.direction = MotorDirection::Coast;
+        self.settle_state();
         self.update_pwm()
     }
 
     fn brake(&mut self) -> Result<(), Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
         
         self.current_speed = 0;
+        if let Some(ref mut ramp) = self.ramp {
+            ramp.reset(0);
+        }
         self.direction = MotorDirection::Brake;
+        self.settle_state();
         self.update_pwm()
     }
 
     fn enable(&mut self) -> Result<(), Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
+        self.check_estop()?;
+        self.check_faulted()?;
         
-        self.control_enable(true)
+        self.control_enable(true)?;
+        self.state = DriverState::Enabled;
+        self.settle_state();
+        self.feed();
+        Ok(())
     }
 
     fn disable(&mut self) -> Result<(), Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
         
-        self.control_enable(false)
+        self.control_enable(false)?;
+        if self.state.can_drive() {
+            self.state = DriverState::Disabled;
+        }
+        Ok(())
     }
 
     fn get_speed(&self) -> Result<i16, Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
         Ok(self.current_speed)
     }
 
     fn get_direction(&self) -> Result<bool, Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
         Ok(self.direction == MotorDirection::Forward)
     }
 
     fn get_current(&self) -> Result<f32, Self::Error> {
-        Err(MotorDriverError::HardwareFault)
+        self.current_sense
+            .as_ref()
+            .and_then(|sense| sense.current())
+            .ok_or(MotorDriverError::HardwareFault)
     }
 
     fn get_voltage(&self) -> Result<f32, Self::Error> {
-        Err(MotorDriverError::HardwareFault)
+        self.current_sense
+            .as_ref()
+            .and_then(|sense| sense.voltage())
+            .ok_or(MotorDriverError::HardwareFault)
     }
 
     fn get_temperature(&self) -> Result<f32, Self::Error> {
         Err(MotorDriverError::HardwareFault)
     }
 
-    fn get_fault_status(&self) -> Result<u8, Self::Error> {
-        if !self.initialized {
+    fn get_fault_status(&self) -> Result<FaultStatus, Self::Error> {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
-        Ok(0)
+        Ok(self.fault)
     }
     
     fn set_ppr(&mut self, ppr: i16) -> Result<bool, Self::Error> {
-        if !self.initialized {
+        if !self.state.is_initialized() {
             return Err(MotorDriverError::NotInitialized);
         }
         self.ppr = ppr;
@@ -244,19 +672,49 @@ where
 
     fn check_ppr(&mut self) -> Result<(), Self::Error> {
         if self.ppr == 0 {
-            return Err(MotorDriverError::NotInitialized);
+            return Err(MotorDriverError::InvalidConfiguration);
         }
         Ok(())
     }
 }
 
-pub struct MotorDriverBuilder<E1, E2, P1, P2> {
+#[cfg(feature = "std")]
+impl<E1, E2, P1, P2, D, F, S, C> EStopTarget for MotorDriverWrapper<E1, E2, P1, P2, D, F, S, C>
+where
+    E1: OutputPin<Error: HalCause>,
+    E2: OutputPin<Error: HalCause>,
+    P1: SetDutyCycle<Error: HalCause>,
+    P2: SetDutyCycle<Error: HalCause>,
+    D: OutputPin<Error: HalCause>,
+    F: InputPin<Error: HalCause>,
+    S: PowerSense,
+    C: Clock,
+{
+    fn attach_estop(&mut self, estop: EStop) {
+        self.estop = Some(estop);
+    }
+}
+
+pub struct MotorDriverBuilder<E1, E2, P1, P2, D = NoPin, F = NoEncoder, S = NoSense, C = DefaultClock> {
     enable_pins: Option<EnablePins<E1, E2>>,
     pwm_channels: Option<PwmChannels<P1, P2>>,
     max_duty: Option<u16>,
     initial_speed: Option<i16>,
     initial_direction: Option<MotorDirection>,
     ppr: Option<i16>,
+    ramp: Option<RampLimiter>,
+    decay_mode: Option<DecayMode>,
+    direction_pin: Option<D>,
+    direction_pin2: Option<D>,
+    dead_time_us: Option<u32>,
+    drive_mode: Option<DriveMode>,
+    fault_pin: Option<F>,
+    current_sense: Option<S>,
+    current_limit: Option<CurrentLimit>,
+    watchdog: Option<Watchdog>,
+    #[cfg(feature = "std")]
+    estop: Option<EStop>,
+    clock: C,
 }
 
 impl<E1, E2, P1, P2> MotorDriverBuilder<E1, E2, P1, P2> {
@@ -277,9 +735,24 @@ impl<E1, E2, P1, P2> MotorDriverBuilder<E1, E2, P1, P2> {
             initial_speed: None,
             initial_direction: None,
             ppr: None,
+            ramp: None,
+            decay_mode: None,
+            direction_pin: None,
+            direction_pin2: None,
+            dead_time_us: None,
+            drive_mode: None,
+            fault_pin: None,
+            current_sense: None,
+            current_limit: None,
+            watchdog: None,
+            #[cfg(feature = "std")]
+            estop: None,
+            clock: DefaultClock::default(),
         }
     }
+}
 
+impl<E1, E2, P1, P2, D, F, S, C> MotorDriverBuilder<E1, E2, P1, P2, D, F, S, C> {
     pub fn with_single_enable(mut self, enable: E1) -> Self {
         self.enable_pins = Some(EnablePins::Single(enable));
         self
@@ -342,25 +815,371 @@ impl<E1, E2, P1, P2> MotorDriverBuilder<E1, E2, P1, P2> {
         self
     }
 
-    pub fn build(self) -> MotorDriverWrapper<E1, E2, P1, P2> {
+    /// Limit how fast the commanded duty cycle may change.
+    /// 
+    /// With a ramp configured, `set_speed()` only changes the target and the
+    /// output approaches it over successive `update()` calls. `stop()` and
+    /// `brake()` still act immediately.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// builder.with_ramp(RampLimiter::new(2000.0, 4000.0)?)
+    /// ```
+    pub fn with_ramp(mut self, ramp: RampLimiter) -> Self {
+        self.ramp = Some(ramp);
+        self
+    }
+
+    /// Enforce continuous and peak current limits from `update()`.
+    /// 
+    /// Needs a sensor from `with_current_sense()`. Above the continuous limit
+    /// the duty is folded back; above the peak limit the driver stops,
+    /// disables the bridge and returns `OverCurrent` until `clear_fault()`.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// builder.with_current_sense(sense).with_current_limit(CurrentLimit::new(1.5, 4.0)?)
+    /// ```
+    pub fn with_current_limit(mut self, limit: CurrentLimit) -> Self {
+        self.current_limit = Some(limit);
+        self
+    }
+
+    /// Stop the motor when no command arrives within the watchdog timeout.
+    /// 
+    /// `set_speed()`, `enable()` and `feed()` restart the timer. Once it
+    /// expires, `update()` stops or brakes the motor (see `WatchdogAction`),
+    /// disables it and returns `WatchdogTimeout` until fed again. The timeout
+    /// is only checked inside `update()`.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// builder.with_watchdog(Watchdog::new(200)?.with_action(WatchdogAction::Brake))
+    /// ```
+    pub fn with_watchdog(mut self, watchdog: Watchdog) -> Self {
+        self.watchdog = Some(watchdog);
+        self
+    }
+
+    /// Share an emergency stop with other motors.
+    /// 
+    /// While it is latched, `set_speed()`, `enable()` and `update()` brake
+    /// and disable the motor and return `EmergencyStop`. They also poll the
+    /// stop's hardware input.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// builder.with_estop(estop.clone())
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_estop(mut self, estop: EStop) -> Self {
+        self.estop = Some(estop);
+        self
+    }
+
+    /// Select fast or slow current decay for dual-PWM configurations.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// builder.with_decay_mode(DecayMode::Slow)
+    /// ```
+    pub fn with_decay_mode(mut self, mode: DecayMode) -> Self {
+        self.decay_mode = Some(mode);
+        self
+    }
+
+    /// Select how `set_speed` maps onto the PWM channels.
+    /// 
+    /// In locked-antiphase mode a single PWM channel at 50% duty means stop,
+    /// 0% full reverse and 100% full forward.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// builder.with_single_pwm(pwm).with_drive_mode(DriveMode::LockedAntiphase)
+    /// ```
+    pub fn with_drive_mode(mut self, mode: DriveMode) -> Self {
+        self.drive_mode = Some(mode);
+        self
+    }
+
+    /// Configure a direction output for PH/EN or DIR/PWM style drivers.
+    /// 
+    /// The pin is driven high for forward and low for reverse whenever the
+    /// speed or direction changes in sign-magnitude mode.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// builder.with_single_pwm(en_pwm).with_direction_pin(ph_pin)
+    /// ```
+    pub fn with_direction_pin<Dir: OutputPin>(self, direction: Dir) -> MotorDriverBuilder<E1, E2, P1, P2, Dir, F, S, C> {
+        MotorDriverBuilder {
+            enable_pins: self.enable_pins,
+            pwm_channels: self.pwm_channels,
+            max_duty: self.max_duty,
+            initial_speed: self.initial_speed,
+            initial_direction: self.initial_direction,
+            ppr: self.ppr,
+            ramp: self.ramp,
+            decay_mode: self.decay_mode,
+            direction_pin: Some(direction),
+            direction_pin2: None,
+            dead_time_us: self.dead_time_us,
+            drive_mode: self.drive_mode,
+            fault_pin: self.fault_pin,
+            current_sense: self.current_sense,
+            current_limit: self.current_limit,
+            watchdog: self.watchdog,
+            #[cfg(feature = "std")]
+            estop: self.estop,
+            clock: self.clock,
+        }
+    }
+
+    /// Configure IN1/IN2 direction inputs for single-PWM bridges such as the
+    /// L298N or TB6612FNG, where the PWM drives the enable input.
+    /// 
+    /// Forward drives IN1 high and IN2 low, reverse the opposite, brake both
+    /// high with full duty and coast both low.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// builder.with_single_pwm(ena_pwm).with_direction_pins(in1_pin, in2_pin)
+    /// ```
+    pub fn with_direction_pins<Dir: OutputPin>(self, in1: Dir, in2: Dir) -> MotorDriverBuilder<E1, E2, P1, P2, Dir, F, S, C> {
+        let mut builder = self.with_direction_pin(in1);
+        builder.direction_pin2 = Some(in2);
+        builder
+    }
+
+    /// Hold the PWM at zero for `micros` microseconds after the direction pins flip.
+    /// 
+    /// A reversal that passes through `brake()` still counts as a flip.
+    /// 
+    /// The PWM is re-enabled by `update()`, which must be called periodically
+    /// while a dead time is configured.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// builder.with_direction_pin(dir_pin).with_dead_time(500)
+    /// ```
+    pub fn with_dead_time(mut self, micros: u32) -> Self {
+        self.dead_time_us = Some(micros);
+        self
+    }
+
+    /// Attach an active-low fault input such as the nFAULT pin of DRV88xx or TB67H drivers.
+    /// 
+    /// A low level latches `FaultStatus::GENERIC` and makes `set_speed()`
+    /// fail until `clear_fault()` is called.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// builder.with_dual_pwm(in1_pwm, in2_pwm).with_fault_pin(nfault_pin)
+    /// ```
+    pub fn with_fault_pin<Flt: InputPin>(self, fault: Flt) -> MotorDriverBuilder<E1, E2, P1, P2, D, Flt, S, C> {
+        MotorDriverBuilder {
+            enable_pins: self.enable_pins,
+            pwm_channels: self.pwm_channels,
+            max_duty: self.max_duty,
+            initial_speed: self.initial_speed,
+            initial_direction: self.initial_direction,
+            ppr: self.ppr,
+            ramp: self.ramp,
+            decay_mode: self.decay_mode,
+            direction_pin: self.direction_pin,
+            direction_pin2: self.direction_pin2,
+            dead_time_us: self.dead_time_us,
+            drive_mode: self.drive_mode,
+            fault_pin: Some(fault),
+            current_sense: self.current_sense,
+            current_limit: self.current_limit,
+            watchdog: self.watchdog,
+            #[cfg(feature = "std")]
+            estop: self.estop,
+            clock: self.clock,
+        }
+    }
+
+    /// Attach a sensor so `get_current()` and `get_voltage()` report real values.
+    /// 
+    /// Faults reported by the sensor are latched; a `CurrentSense` above its
+    /// limit makes `set_speed()` fail with `OverCurrent`.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// let sense = CurrentSense::new(adc, CurrentScaling::Shunt { ohms: 0.01, gain: 50.0 })?;
+    /// builder.with_current_sense(sense.with_limit(2.5)?)
+    /// ```
+    pub fn with_current_sense<S2: PowerSense>(self, sense: S2) -> MotorDriverBuilder<E1, E2, P1, P2, D, F, S2, C> {
+        MotorDriverBuilder {
+            enable_pins: self.enable_pins,
+            pwm_channels: self.pwm_channels,
+            max_duty: self.max_duty,
+            initial_speed: self.initial_speed,
+            initial_direction: self.initial_direction,
+            ppr: self.ppr,
+            ramp: self.ramp,
+            decay_mode: self.decay_mode,
+            direction_pin: self.direction_pin,
+            direction_pin2: self.direction_pin2,
+            dead_time_us: self.dead_time_us,
+            drive_mode: self.drive_mode,
+            fault_pin: self.fault_pin,
+            current_sense: Some(sense),
+            current_limit: self.current_limit,
+            watchdog: self.watchdog,
+            #[cfg(feature = "std")]
+            estop: self.estop,
+            clock: self.clock,
+        }
+    }
+
+    /// Replace the time source used by the ramp limiter.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// let clock = MockClock::new();
+    /// builder.with_clock(&clock)
+    /// ```
+    pub fn with_clock<C2: Clock>(self, clock: C2) -> MotorDriverBuilder<E1, E2, P1, P2, D, F, S, C2> {
+        MotorDriverBuilder {
+            enable_pins: self.enable_pins,
+            pwm_channels: self.pwm_channels,
+            max_duty: self.max_duty,
+            initial_speed: self.initial_speed,
+            initial_direction: self.initial_direction,
+            ppr: self.ppr,
+            ramp: self.ramp,
+            decay_mode: self.decay_mode,
+            direction_pin: self.direction_pin,
+            direction_pin2: self.direction_pin2,
+            dead_time_us: self.dead_time_us,
+            drive_mode: self.drive_mode,
+            fault_pin: self.fault_pin,
+            current_sense: self.current_sense,
+            current_limit: self.current_limit,
+            watchdog: self.watchdog,
+            #[cfg(feature = "std")]
+            estop: self.estop,
+            clock,
+        }
+    }
+
+    pub fn build(self) -> MotorDriverWrapper<E1, E2, P1, P2, D, F, S, C> {
         MotorDriverWrapper {
             enable_pins: self.enable_pins.unwrap_or(EnablePins::None),
             pwm_channels: self.pwm_channels.unwrap_or(PwmChannels::None),
             max_duty: self.max_duty.unwrap_or(1000),
             current_speed: self.initial_speed.unwrap_or(0),
-            current_pulse: 0,
             ppr: self.ppr.unwrap_or(0),
             direction: self.initial_direction.unwrap_or(MotorDirection::Coast),
-            initialized: false,
+            state: DriverState::Uninitialized,
+            ramp: self.ramp.map(|mut ramp| {
+                ramp.reset(self.initial_speed.unwrap_or(0));
+                ramp
+            }),
+            decay_mode: self.decay_mode.unwrap_or_default(),
+            direction_pin: self.direction_pin,
+            direction_pin2: self.direction_pin2,
+            pin_direction: None,
+            drive_direction: None,
+            dead_time_us: self.dead_time_us.unwrap_or(0),
+            dead_time_start: None,
+            drive_mode: self.drive_mode.unwrap_or_default(),
+            fault_pin: self.fault_pin,
+            fault: FaultStatus::empty(),
+            current_sense: self.current_sense,
+            current_limit: self.current_limit,
+            watchdog: self.watchdog,
+            #[cfg(feature = "std")]
+            estop: self.estop,
+            clock: self.clock,
+        }
+    }
+
+    /// Build the driver after validating the configuration.
+    /// 
+    /// Unlike `build()`, missing enable pins and missing or `None` PWM
+    /// channels are reported instead of producing a driver that never moves.
+    /// An explicit `EnablePins::None` is accepted for bridges whose enable
+    /// input is tied high.
+    /// 
+    /// # Errors
+    /// 
+    /// * `MissingEnable` - Enable pins were never set
+    /// * `MissingPwm` - No PWM channels, or `PwmChannels::None`
+    /// * `MaxDutyExceedsChannel` - `max_duty` (1000 if unset) is above a PWM
+    ///   channel's `max_duty_cycle()`
+    /// * `InitialSpeedOutOfRange` - The initial speed magnitude exceeds `max_duty`
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,ignore
+    /// let motor = MotorDriverBuilder::new()
+    ///     .with_single_enable(enable_pin)
+    ///     .with_single_pwm(pwm_channel)
+    ///     .try_build()?;
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn try_build(self) -> Result<MotorDriverWrapper<E1, E2, P1, P2, D, F, S, C>, ConfigError>
+    where
+        P1: SetDutyCycle<Error: HalCause>,
+        P2: SetDutyCycle<Error: HalCause>,
+    {
+        if self.enable_pins.is_none() {
+            return Err(ConfigError::MissingEnable);
+        }
+
+        let max_duty = self.max_duty.unwrap_or(1000);
+        let channel_max = match self.pwm_channels.as_ref() {
+            Some(PwmChannels::Single(pwm)) => pwm.max_duty_cycle(),
+            Some(PwmChannels::Dual(pwm1, pwm2)) => pwm1.max_duty_cycle().min(pwm2.max_duty_cycle()),
+            None | Some(PwmChannels::None) => return Err(ConfigError::MissingPwm),
+        };
+        if max_duty > channel_max {
+            return Err(ConfigError::MaxDutyExceedsChannel { max_duty, channel_max });
+        }
+
+        if let Some(speed) = self.initial_speed {
+            if speed.unsigned_abs() > max_duty {
+                return Err(ConfigError::InitialSpeedOutOfRange { speed, max_duty });
+            }
         }
+
+        Ok(self.build())
     }
 
-    pub fn build_and_init(self) -> Result<MotorDriverWrapper<E1, E2, P1, P2>, MotorDriverError>
+    /// Build and initialize the driver in one step.
+    /// 
+    /// Like `build()`, this does not validate the configuration. Call
+    /// `try_build()?` and then `initialize()` to have it checked.
+    /// 
+    /// # Errors
+    /// 
+    /// Returns the hardware error if initialization fails.
+    #[allow(clippy::type_complexity)]
+    pub fn build_and_init(self) -> Result<MotorDriverWrapper<E1, E2, P1, P2, D, F, S, C>, MotorDriverError>
     where
-        E1: OutputPin,
-        E2: OutputPin,
-        P1: SetDutyCycle,
-        P2: SetDutyCycle,
+        E1: OutputPin<Error: HalCause>,
+        E2: OutputPin<Error: HalCause>,
+        P1: SetDutyCycle<Error: HalCause>,
+        P2: SetDutyCycle<Error: HalCause>,
+        D: OutputPin<Error: HalCause>,
+        F: InputPin<Error: HalCause>,
+        S: PowerSense,
+        C: Clock,
     {
         let mut driver = self.build();
         driver.initialize()?;
@@ -383,8 +1202,23 @@ pub mod rppal {
     use ::rppal::gpio::InputPin as RppalInputPin;
     use ::rppal::pwm::Pwm;
 
+    /// Error from an rppal PWM channel.
+    ///
+    /// rppal GPIO access cannot fail, so only `PwmWrapper` produces it.
     #[derive(Debug)]
-    pub struct RppalError;
+    pub struct RppalError(pub ::rppal::pwm::Error);
+
+    impl core::fmt::Display for RppalError {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            self.0.fmt(f)
+        }
+    }
+
+    impl std::error::Error for RppalError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
 
     impl embedded_hal::pwm::Error for RppalError {
         fn kind(&self) -> embedded_hal::pwm::ErrorKind {
@@ -456,16 +1290,20 @@ pub mod rppal {
 
         fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
             let duty_percent = duty as f64 / self.max_duty as f64;
-            self.pwm.set_duty_cycle(duty_percent).map_err(|_| RppalError)?;
+            self.pwm.set_duty_cycle(duty_percent).map_err(RppalError)?;
             Ok(())
         }
     }
 
-    pub type RppalMotorBuilder = MotorDriverBuilder<
+    pub type RppalMotorBuilder<D = NoPin, F = NoEncoder, S = NoSense, C = DefaultClock> = MotorDriverBuilder<
         GpioWrapper<RppalOutputPin>,
         GpioWrapper<RppalOutputPin>,
         PwmWrapper,
-        PwmWrapper
+        PwmWrapper,
+        D,
+        F,
+        S,
+        C
     >;
 
     impl RppalMotorBuilder {
@@ -486,7 +1324,9 @@ pub mod rppal {
         pub fn new_rppal() -> Self {
             MotorDriverBuilder::new()
         }
+    }
 
+    impl<D, F, S, C> RppalMotorBuilder<D, F, S, C> {
         pub fn with_gpio_enable(self, gpio: &::rppal::gpio::Gpio, pin: u8) -> Result<Self, ::rppal::gpio::Error> {
             Ok(self.with_single_enable(GpioWrapper::new(gpio.get(pin)?.into_output())))
         }
@@ -519,12 +1359,39 @@ pub mod rppal {
 
 #[cfg(feature = "linux-embedded-hal")]
 pub mod linux {
-    use embedded_hal::digital::OutputPin;
+    use embedded_hal::digital::{InputPin, OutputPin};
     use embedded_hal::pwm::SetDutyCycle;
-    use linux_embedded_hal::CdevPin;
+    use linux_embedded_hal::{CdevPin, CdevPinError};
+    use std::fs;
+    use std::io;
+    use std::path::{Path, PathBuf};
 
+    /// Error from a GPIO character device line or a sysfs PWM write.
     #[derive(Debug)]
-    pub struct LinuxError;
+    pub enum LinuxError {
+        /// GPIO line request or value access failed.
+        Gpio(CdevPinError),
+        /// Writing a sysfs PWM attribute failed.
+        Pwm(io::Error),
+    }
+
+    impl core::fmt::Display for LinuxError {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match self {
+                LinuxError::Gpio(error) => error.fmt(f),
+                LinuxError::Pwm(error) => error.fmt(f),
+            }
+        }
+    }
+
+    impl std::error::Error for LinuxError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match self {
+                LinuxError::Gpio(error) => Some(error),
+                LinuxError::Pwm(error) => Some(error),
+            }
+        }
+    }
 
     impl embedded_hal::pwm::Error for LinuxError {
         fn kind(&self) -> embedded_hal::pwm::ErrorKind {
@@ -554,23 +1421,118 @@ pub mod linux {
 
     impl OutputPin for GpioWrapper {
         fn set_low(&mut self) -> Result<(), Self::Error> {
-            self.pin.set_value(0).map_err(|_| LinuxError)
+            self.pin.set_value(0).map_err(|error| LinuxError::Gpio(error.into()))
         }
 
         fn set_high(&mut self) -> Result<(), Self::Error> {
-            self.pin.set_value(1).map_err(|_| LinuxError)
+            self.pin.set_value(1).map_err(|error| LinuxError::Gpio(error.into()))
         }
     }
 
+    impl InputPin for GpioWrapper {
+        fn is_high(&mut self) -> Result<bool, Self::Error> {
+            self.pin.is_high().map_err(LinuxError::Gpio)
+        }
+
+        fn is_low(&mut self) -> Result<bool, Self::Error> {
+            self.pin.is_low().map_err(LinuxError::Gpio)
+        }
+    }
+
+    /// Default mount point of the Linux PWM sysfs class.
+    pub const SYSFS_PWM_ROOT: &str = "/sys/class/pwm";
+
+    /// PWM channel driven through the Linux sysfs PWM interface.
+    ///
+    /// On construction the channel is exported under `<root>/pwmchipN`, its
+    /// period is programmed from the requested frequency and the output is
+    /// enabled with a duty cycle of zero. Duty cycle values in the range
+    /// `0..=max_duty` are scaled to nanoseconds of the period. The channel is
+    /// disabled when the wrapper is dropped, and unexported again if this
+    /// wrapper exported it.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // pwmchip0, channel 1, 20 kHz, duty range 0-1000
+    /// let pwm = PwmWrapper::new(0, 1, 20_000.0, 1000)?;
+    /// ```
     pub struct PwmWrapper {
-        chip: u32,
+        chip_path: PathBuf,
+        channel_path: PathBuf,
         number: u32,
+        period_ns: u64,
         max_duty: u16,
+        exported: bool,
     }
 
     impl PwmWrapper {
-        pub fn new(chip: u32, number: u32, max_duty: u16) -> Self {
-            Self { chip, number, max_duty }
+        /// Export and enable `pwmchip<chip>/pwm<number>` under [`SYSFS_PWM_ROOT`].
+        pub fn new(chip: u32, number: u32, frequency: f64, max_duty: u16) -> io::Result<Self> {
+            Self::with_sysfs_root(SYSFS_PWM_ROOT, chip, number, frequency, max_duty)
+        }
+
+        /// Export and enable a PWM channel below an arbitrary sysfs root.
+        ///
+        /// This behaves like [`PwmWrapper::new`] but resolves `pwmchipN` relative
+        /// to `root`, which allows running against a fake directory tree. The
+        /// export step is skipped when the channel directory already exists.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidInput` if `frequency` does not yield a period of at
+        /// least one nanosecond, or any I/O error raised by the sysfs writes.
+        pub fn with_sysfs_root<R: AsRef<Path>>(
+            root: R,
+            chip: u32,
+            number: u32,
+            frequency: f64,
+            max_duty: u16
+        ) -> io::Result<Self> {
+            let period_ns = (1e9 / frequency).round();
+            if frequency.is_nan() || frequency <= 0.0 || period_ns < 1.0 {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid PWM frequency"));
+            }
+
+            let chip_path = root.as_ref().join(format!("pwmchip{}", chip));
+            let channel_path = chip_path.join(format!("pwm{}", number));
+            let exported = !channel_path.exists();
+            if exported {
+                fs::write(chip_path.join("export"), number.to_string())?;
+            }
+
+            let pwm = Self {
+                chip_path,
+                channel_path,
+                number,
+                period_ns: period_ns as u64,
+                max_duty,
+                exported,
+            };
+            // duty_cycle must never exceed period, so clear it before changing the period
+            pwm.write_attr("duty_cycle", 0)?;
+            pwm.write_attr("period", pwm.period_ns)?;
+            pwm.write_attr("enable", 1)?;
+            Ok(pwm)
+        }
+
+        /// Period of the PWM signal in nanoseconds.
+        pub fn period_ns(&self) -> u64 {
+            self.period_ns
+        }
+
+        fn write_attr(&self, attr: &str, value: u64) -> io::Result<()> {
+            fs::write(self.channel_path.join(attr), value.to_string())
+        }
+    }
+
+    impl Drop for PwmWrapper {
+        fn drop(&mut self) {
+            let _ = self.write_attr("enable", 0);
+            // A channel exported by someone else stays exported
+            if self.exported {
+                let _ = fs::write(self.chip_path.join("unexport"), self.number.to_string());
+            }
         }
     }
 
@@ -584,7 +1546,349 @@ pub mod linux {
         }
 
         fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
-            Ok(())
+            let duty_ns = if self.max_duty == 0 {
+                0
+            } else {
+                (self.period_ns as u128 * duty.min(self.max_duty) as u128 / self.max_duty as u128) as u64
+            };
+            self.write_attr("duty_cycle", duty_ns).map_err(LinuxError::Pwm)
         }
     }
-}
\ No newline at end of file
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Creates `<tmp>/pwmchip0` with an `export` file, optionally with `pwm1` already present.
+        fn fake_sysfs(with_channel: bool) -> PathBuf {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            let root = std::env::temp_dir().join(format!(
+                "motor-driver-hal-pwm-{}-{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            ));
+            let chip = root.join("pwmchip0");
+            fs::create_dir_all(&chip).unwrap();
+            fs::write(chip.join("export"), "").unwrap();
+            fs::write(chip.join("unexport"), "").unwrap();
+            if with_channel {
+                fs::create_dir_all(chip.join("pwm1")).unwrap();
+            }
+            root
+        }
+
+        fn read(path: PathBuf) -> String {
+            fs::read_to_string(path).unwrap()
+        }
+
+        #[test]
+        fn programs_period_duty_and_enable() {
+            let root = fake_sysfs(true);
+            let channel = root.join("pwmchip0/pwm1");
+
+            let mut pwm = PwmWrapper::with_sysfs_root(&root, 0, 1, 20_000.0, 1000).unwrap();
+            assert_eq!(pwm.period_ns(), 50_000);
+            assert_eq!(read(channel.join("period")), "50000");
+            assert_eq!(read(channel.join("duty_cycle")), "0");
+            assert_eq!(read(channel.join("enable")), "1");
+
+            pwm.set_duty_cycle(250).unwrap();
+            assert_eq!(read(channel.join("duty_cycle")), "12500");
+            pwm.set_duty_cycle(2000).unwrap();
+            assert_eq!(read(channel.join("duty_cycle")), "50000");
+
+            drop(pwm);
+            assert_eq!(read(channel.join("enable")), "0");
+            fs::remove_dir_all(root).unwrap();
+        }
+
+        #[test]
+        fn leaves_foreign_channel_exported() {
+            let root = fake_sysfs(true);
+            drop(PwmWrapper::with_sysfs_root(&root, 0, 1, 1000.0, 1000).unwrap());
+            assert_eq!(read(root.join("pwmchip0/export")), "");
+            assert_eq!(read(root.join("pwmchip0/unexport")), "");
+            fs::remove_dir_all(root).unwrap();
+        }
+
+        #[test]
+        fn exports_and_unexports_own_channel() {
+            // The fake tree has no kernel to create pwm1 on export, so the
+            // attribute writes fail after the export and dropping the
+            // half-built wrapper unexports the channel again
+            let root = fake_sysfs(false);
+            assert!(PwmWrapper::with_sysfs_root(&root, 0, 1, 1000.0, 1000).is_err());
+            assert_eq!(read(root.join("pwmchip0/export")), "1");
+            assert_eq!(read(root.join("pwmchip0/unexport")), "1");
+            fs::remove_dir_all(root).unwrap();
+        }
+
+        #[test]
+        fn rejects_invalid_frequency() {
+            let root = fake_sysfs(true);
+            for frequency in [0.0, -1.0, f64::NAN, 1e10] {
+                let error = PwmWrapper::with_sysfs_root(&root, 0, 1, frequency, 1000).err().unwrap();
+                assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+            }
+            fs::remove_dir_all(root).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::current::{CurrentScaling, CurrentSense};
+    use crate::mock::{Event, Log, MockAdc, MockPin, MockPwm};
+
+    type Wrapper<'a> = MotorDriverWrapper<MockPin, MockPin, MockPwm, MockPwm, MockPin, NoEncoder, NoSense, &'a MockClock>;
+
+    #[test]
+    fn direction_pins_encode_brake_and_coast() {
+        let (log, clock) = (Log::new(), MockClock::new());
+        let mut motor: Wrapper = MotorDriverBuilder::new()
+            .with_single_enable(log.pin("en"))
+            .with_single_pwm(log.pwm("pwm"))
+            .with_direction_pins(log.pin("in1"), log.pin("in2"))
+            .with_dead_time(500)
+            .with_clock(&clock)
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        let pins = |log: &Log| (log.level("in1"), log.level("in2"), log.duty("pwm"));
+
+        motor.set_speed(600).unwrap();
+        assert_eq!(pins(&log), (Some(true), Some(false), Some(600)));
+        motor.brake().unwrap();
+        assert_eq!(pins(&log), (Some(true), Some(true), Some(1000)));
+        motor.stop().unwrap();
+        assert_eq!(pins(&log), (Some(false), Some(false), Some(0)));
+
+        // Brake and coast are not driving directions, so reversing after them still waits out the dead time
+        log.take();
+        motor.set_speed(-600).unwrap();
+        assert_eq!(pins(&log), (Some(false), Some(true), Some(0)));
+        clock.advance(500);
+        motor.update().unwrap();
+        assert_eq!(log.duty("pwm"), Some(600));
+    }
+
+    #[test]
+    fn watchdog_brake_also_disables_the_bridge() {
+        let (log, clock) = (Log::new(), MockClock::new());
+        let mut motor = MotorDriverBuilder::<_, MockPin, _, _>::new()
+            .with_single_enable(log.pin("en"))
+            .with_dual_pwm(log.pwm("pwm1"), log.pwm("pwm2"))
+            .with_watchdog(Watchdog::new(100).unwrap().with_action(WatchdogAction::Brake))
+            .with_clock(&clock)
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        motor.set_speed(400).unwrap();
+
+        clock.advance(99_000);
+        assert_eq!(motor.update(), Ok(400));
+        log.take();
+        clock.advance(1_000);
+        assert_eq!(motor.update(), Err(MotorDriverError::WatchdogTimeout));
+        assert_eq!(log.take(), [
+            Event::Duty("pwm1", 1000),
+            Event::Duty("pwm2", 1000),
+            Event::Pin("en", false),
+        ]);
+        assert_eq!(motor.state(), DriverState::Disabled);
+
+        // Still expired until fed again
+        assert_eq!(motor.update(), Err(MotorDriverError::WatchdogTimeout));
+        motor.enable().unwrap();
+        assert_eq!(motor.update(), Ok(0));
+    }
+
+    #[test]
+    fn try_build_accepts_explicit_no_enable_and_ppr() {
+        let log = Log::new();
+        let motor = MotorDriverBuilder::<MockPin, MockPin, _, MockPwm>::new()
+            .with_enable_pins(EnablePins::None)
+            .with_single_pwm(log.pwm("pwm"))
+            .with_ppr(360)
+            .try_build();
+        assert!(motor.is_ok());
+
+        let unset = MotorDriverBuilder::<MockPin, MockPin, _, MockPwm>::new()
+            .with_single_pwm(log.pwm("pwm"))
+            .try_build();
+        assert_eq!(unset.err(), Some(ConfigError::MissingEnable));
+    }
+
+    #[test]
+    fn build_and_init_leaves_validation_to_try_build() {
+        let log = Log::new();
+        // Bridges with the enable tied high and no PWM channel still build
+        let motor = MotorDriverBuilder::<MockPin, MockPin, MockPwm, MockPwm>::new().build_and_init();
+        assert!(motor.is_ok());
+
+        let result: Result<_, MotorDriverError> = MotorDriverBuilder::<_, MockPin, _, MockPwm>::new()
+            .with_single_enable(log.pin("en"))
+            .with_single_pwm(log.pwm("pwm"))
+            .with_initial_speed(-1200)
+            .try_build()
+            .map_err(MotorDriverError::from);
+        assert_eq!(
+            result.err(),
+            Some(MotorDriverError::Config(ConfigError::InitialSpeedOutOfRange { speed: -1200, max_duty: 1000 }))
+        );
+    }
+
+    #[test]
+    fn slow_decay_holds_one_input_high_and_inverts_the_other() {
+        let log = Log::new();
+        let mut motor = MotorDriverBuilder::<_, MockPin, _, _>::new()
+            .with_single_enable(log.pin("en"))
+            .with_dual_pwm(log.pwm("in1"), log.pwm("in2"))
+            .with_decay_mode(DecayMode::Slow)
+            .with_clock(MockClock::new())
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        log.take();
+
+        motor.set_speed(300).unwrap();
+        assert_eq!(log.take(), [Event::Duty("in1", 1000), Event::Duty("in2", 700)]);
+        motor.set_speed(-300).unwrap();
+        assert_eq!(log.take(), [Event::Duty("in1", 700), Event::Duty("in2", 1000)]);
+        motor.set_speed(-1000).unwrap();
+        assert_eq!(log.take(), [Event::Duty("in1", 0), Event::Duty("in2", 1000)]);
+
+        // Braking still shorts both inputs high
+        motor.brake().unwrap();
+        assert_eq!(log.take(), [Event::Duty("in1", 1000), Event::Duty("in2", 1000)]);
+    }
+
+    #[test]
+    fn locked_antiphase_maps_speed_around_half_duty() {
+        let log = Log::new();
+        let mut motor = MotorDriverBuilder::<_, MockPin, _, MockPwm>::new()
+            .with_single_enable(log.pin("en"))
+            .with_single_pwm(log.pwm("pwm"))
+            .with_drive_mode(DriveMode::LockedAntiphase)
+            .with_clock(MockClock::new())
+            .build_and_init()
+            .unwrap();
+        assert_eq!(log.duty("pwm"), Some(500));
+        motor.enable().unwrap();
+        log.take();
+
+        for (speed, duty) in [(0, 500), (400, 700), (-400, 300), (1000, 1000), (-1000, 0)] {
+            motor.set_speed(speed).unwrap();
+            assert_eq!(log.duty("pwm"), Some(duty), "speed {speed}");
+        }
+
+        motor.stop().unwrap();
+        assert_eq!(log.duty("pwm"), Some(500));
+    }
+
+    #[test]
+    fn locked_antiphase_drives_complementary_channels() {
+        let log = Log::new();
+        let mut motor = MotorDriverBuilder::<_, MockPin, _, _>::new()
+            .with_single_enable(log.pin("en"))
+            .with_dual_pwm(log.pwm("pwm1"), log.pwm("pwm2"))
+            .with_drive_mode(DriveMode::LockedAntiphase)
+            .with_clock(MockClock::new())
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        log.take();
+
+        motor.set_speed(600).unwrap();
+        assert_eq!(log.take(), [Event::Duty("pwm1", 800), Event::Duty("pwm2", 200)]);
+        motor.set_speed(-600).unwrap();
+        assert_eq!(log.take(), [Event::Duty("pwm1", 200), Event::Duty("pwm2", 800)]);
+    }
+
+    #[test]
+    fn sign_magnitude_drives_direction_pin_and_magnitude() {
+        let log = Log::new();
+        let mut motor = MotorDriverBuilder::<_, MockPin, _, MockPwm>::new()
+            .with_single_enable(log.pin("en"))
+            .with_single_pwm(log.pwm("pwm"))
+            .with_direction_pin(log.pin("dir"))
+            .with_clock(MockClock::new())
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        log.take();
+
+        motor.set_speed(400).unwrap();
+        assert_eq!(log.take(), [Event::Pin("dir", true), Event::Duty("pwm", 400)]);
+        motor.set_speed(250).unwrap();
+        assert_eq!(log.take(), [Event::Duty("pwm", 250)]);
+        motor.set_speed(-400).unwrap();
+        assert_eq!(log.take(), [Event::Pin("dir", false), Event::Duty("pwm", 400)]);
+        motor.stop().unwrap();
+        assert_eq!(log.duty("pwm"), Some(0));
+    }
+
+    #[test]
+    fn latched_nfault_refuses_to_drive() {
+        let log = Log::new();
+        let nfault = log.pin("nfault");
+        nfault.drive(true);
+        let mut motor = MotorDriverBuilder::<_, MockPin, _, MockPwm>::new()
+            .with_single_enable(log.pin("en"))
+            .with_single_pwm(log.pwm("pwm"))
+            .with_fault_pin(nfault.clone())
+            .with_clock(MockClock::new())
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+
+        nfault.drive(false);
+        assert_eq!(motor.check_fault(), Ok(FaultStatus::GENERIC));
+        nfault.drive(true);
+        log.take();
+        assert_eq!(motor.set_speed(500), Err(MotorDriverError::HardwareFault));
+        assert_eq!(motor.state(), DriverState::Faulted);
+        assert_eq!(log.duty("pwm"), None);
+
+        assert_eq!(motor.clear_fault(), Ok(FaultStatus::empty()));
+        assert_eq!(motor.state(), DriverState::Disabled);
+        motor.enable().unwrap();
+        motor.set_speed(500).unwrap();
+        assert_eq!(log.duty("pwm"), Some(500));
+    }
+
+    #[test]
+    fn supervision_disables_even_when_the_pwm_write_fails() {
+        let (log, clock, adc) = (Log::new(), MockClock::new(), MockAdc::default());
+        let mut motor = MotorDriverBuilder::<_, MockPin, _, MockPwm>::new()
+            .with_single_enable(log.pin("en"))
+            .with_single_pwm(log.pwm("pwm"))
+            .with_watchdog(Watchdog::new(100).unwrap().with_action(WatchdogAction::Brake))
+            .with_current_sense(CurrentSense::new(adc.clone(), CurrentScaling::Shunt { ohms: 1.0, gain: 1.0 }).unwrap())
+            .with_current_limit(CurrentLimit::new(2.0, 5.0).unwrap())
+            .with_clock(&clock)
+            .build_and_init()
+            .unwrap();
+        motor.enable().unwrap();
+        motor.set_speed(400).unwrap();
+
+        log.fail_pwm(true);
+        clock.advance(100_000);
+        assert!(matches!(motor.update(), Err(MotorDriverError::PwmError(_))));
+        assert_eq!(motor.state(), DriverState::Disabled);
+        assert_eq!(log.level("en"), Some(false));
+
+        log.fail_pwm(false);
+        motor.feed();
+        motor.enable().unwrap();
+        motor.set_speed(400).unwrap();
+        log.fail_pwm(true);
+        adc.set(6.0);
+        assert!(matches!(motor.update(), Err(MotorDriverError::PwmError(_))));
+        assert_eq!(motor.state(), DriverState::Faulted);
+        assert_eq!(log.level("en"), Some(false));
+    }
+}